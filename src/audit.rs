@@ -0,0 +1,111 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use chrono::{SecondsFormat, Utc};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyIOError;
+use pyo3::prelude::*;
+use serde_json::json;
+
+enum AuditSink {
+    Console,
+    File(File),
+}
+
+struct AuditConfig {
+    sink: AuditSink,
+    principal: Option<String>,
+}
+
+// 未启用时只做一次原子读取
+static AUDIT_ENABLED: AtomicBool = AtomicBool::new(false);
+static AUDIT_CONFIG: Lazy<Mutex<Option<AuditConfig>>> = Lazy::new(|| Mutex::new(None));
+
+/// 配置出站请求审计日志，与 set_debug 相互独立
+///
+/// 每个发出的请求写一行 JSON；target 为 None 时关闭，"console" 输出到标准错误，
+/// 其余视为文件路径（目录则写入其中的 audit.log），文件以追加方式打开。
+#[pyfunction]
+#[pyo3(signature = (target=None, principal=None))]
+pub fn set_audit_log(target: Option<String>, principal: Option<String>) -> PyResult<()> {
+    let mut cfg = AUDIT_CONFIG.lock().unwrap();
+    let sink = match target {
+        None => {
+            AUDIT_ENABLED.store(false, Ordering::Release);
+            *cfg = None;
+            return Ok(());
+        }
+        Some(t) if t.eq_ignore_ascii_case("console") => AuditSink::Console,
+        Some(t) => {
+            let path = Path::new(&t);
+            let path = if path.is_dir() { path.join("audit.log") } else { path.to_path_buf() };
+            let file = OpenOptions::new().create(true).append(true).open(&path)
+                .map_err(|e| PyIOError::new_err(format!("Failed to open audit log '{}': {}", path.display(), e)))?;
+            AuditSink::File(file)
+        }
+    };
+    *cfg = Some(AuditConfig { sink, principal });
+    AUDIT_ENABLED.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// 把审计日志文件写入磁盘
+pub(crate) fn flush() {
+    if let Some(AuditConfig { sink: AuditSink::File(file), .. }) = AUDIT_CONFIG.lock().unwrap().as_mut() {
+        let _ = file.flush().and_then(|_| file.sync_data());
+    }
+}
+
+pub fn audit_enabled() -> bool {
+    AUDIT_ENABLED.load(Ordering::Acquire)
+}
+
+/// 一次出站请求的审计记录
+pub struct AuditRecord<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub status: u16,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+    pub duration: f64,
+    pub tag: Option<&'a str>,
+    pub error: Option<&'a str>,
+}
+
+/// 写入一条审计记录；URL 只保留主机和路径，不记录查询参数和凭据
+pub fn audit_log(record: AuditRecord) {
+    if !audit_enabled() { return; }
+    let (host, path) = match url::Url::parse(record.url) {
+        Ok(u) => {
+            let host = match (u.host_str(), u.port()) {
+                (Some(h), Some(p)) => format!("{}:{}", h, p),
+                (Some(h), None) => h.to_string(),
+                (None, _) => String::new(),
+            };
+            (host, u.path().to_string())
+        }
+        Err(_) => (String::new(), String::new()),
+    };
+
+    let mut cfg = AUDIT_CONFIG.lock().unwrap();
+    let Some(cfg) = cfg.as_mut() else { return };
+    let line = json!({
+        "ts": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "method": record.method,
+        "host": host,
+        "path": path,
+        "status": record.status,
+        "bytes_sent": record.bytes_sent,
+        "bytes_received": record.bytes_received,
+        "duration": (record.duration * 1000.0).round() / 1000.0,
+        "tag": record.tag,
+        "principal": cfg.principal,
+        "error": record.error,
+    }).to_string();
+    match &mut cfg.sink {
+        AuditSink::Console => eprintln!("{}", line),
+        AuditSink::File(file) => { let _ = writeln!(file, "{}", line); }
+    }
+}
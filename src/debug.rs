@@ -1,34 +1,184 @@
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::Path;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
+use chrono::{Local, SecondsFormat};
 use once_cell::sync::Lazy;
-use pyo3::pyfunction;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
 use reqwest::StatusCode;
-use serde_json::Value;
+use regex::Regex;
+use serde_json::{json, Value};
+use url::Url;
 
 #[derive(Clone)]
 enum DebugTarget {
     Console,
     File(String),
+    /// Python logging 中的 logger 名称
+    Logger(String),
+}
+
+// 转发到 Python logging 时未指定名称使用的 logger
+const DEFAULT_LOGGER: &str = "rusty_req";
+
+/// 日志级别，数值与 Python logging 一致
+#[derive(Clone, Copy)]
+pub enum Level {
+    Debug = 10,
+    Warning = 30,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Debug => "DEBUG",
+            Level::Warning => "WARNING",
+        }
+    }
+}
+
+/// 文件达到 max_bytes 时轮转为 debug.log.1 … debug.log.<max_files>
+#[derive(Clone, Copy)]
+struct Rotation {
+    max_bytes: u64,
+    max_files: usize,
 }
 
 #[derive(Clone)]
 struct DebugConfig {
     enabled: bool,
     target: DebugTarget,
+    json: bool,
+    rotation: Option<Rotation>,
 }
 
 static DEBUG_CONFIG: Lazy<RwLock<DebugConfig>> = Lazy::new(|| {
-    RwLock::new(DebugConfig { enabled: false, target: DebugTarget::Console })
+    RwLock::new(DebugConfig { enabled: false, target: DebugTarget::Console, json: false, rotation: None })
 });
+// 轮转和写入需要互斥，避免多个线程同时重命名文件
+static FILE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// 默认隐藏的请求头
+pub(crate) const DEFAULT_DENY_HEADERS: [&str; 6] = ["authorization", "proxy-authorization", "cookie", "set-cookie", "x-api-key", "x-auth-token"];
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// 调试日志的脱敏规则
+struct Redaction {
+    /// 隐藏取值的请求头（小写）
+    deny: Vec<String>,
+    /// 设置后只显示这些请求头的取值（小写）
+    allow: Option<Vec<String>>,
+    /// 响应体中需要遮盖的内容；有捕获组时只遮盖捕获组
+    body: Vec<Regex>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Redaction { deny: DEFAULT_DENY_HEADERS.iter().map(|h| h.to_string()).collect(), allow: None, body: Vec::new() }
+    }
+}
+
+static REDACTION: Lazy<RwLock<Redaction>> = Lazy::new(|| RwLock::new(Redaction::default()));
 
+/// 设置调试日志的脱敏规则，替换之前的设置
+///
+/// deny_headers 为 None 时使用默认列表（Authorization、Cookie 等）；URL 和代理地址中的密码始终隐藏。
 #[pyfunction]
-pub fn set_debug(enabled: bool, target: Option<String>) {
-    let mut cfg = DEBUG_CONFIG.write().unwrap();
-    cfg.enabled = enabled;
-    cfg.target = match target {
+#[pyo3(signature = (deny_headers=None, allow_headers=None, body_patterns=None))]
+pub fn set_debug_redaction(
+    deny_headers: Option<Vec<String>>,
+    allow_headers: Option<Vec<String>>,
+    body_patterns: Option<Vec<String>>,
+) -> PyResult<()> {
+    let lower = |names: Vec<String>| names.into_iter().map(|n| n.to_ascii_lowercase()).collect::<Vec<_>>();
+    let body = body_patterns.unwrap_or_default().iter()
+        .map(|p| Regex::new(p).map_err(|e| PyValueError::new_err(format!("Invalid body pattern '{}': {}", p, e))))
+        .collect::<PyResult<Vec<_>>>()?;
+    let defaults = Redaction::default();
+    *REDACTION.write().unwrap() = Redaction {
+        deny: deny_headers.map(lower).unwrap_or(defaults.deny),
+        allow: allow_headers.map(lower),
+        body,
+    };
+    Ok(())
+}
+
+impl Redaction {
+    fn headers(&self, headers: &serde_json::Map<String, Value>) -> serde_json::Map<String, Value> {
+        headers.iter().map(|(name, value)| {
+            let lower = name.to_ascii_lowercase();
+            let hidden = self.deny.contains(&lower) || self.allow.as_ref().is_some_and(|allow| !allow.contains(&lower));
+            (name.clone(), if hidden { Value::from(REDACTED) } else { value.clone() })
+        }).collect()
+    }
+
+    fn body(&self, text: &str) -> String {
+        self.body.iter().fold(text.to_string(), |text, re| mask(&text, re))
+    }
+}
+
+/// 遮盖匹配的内容；有捕获组时只遮盖捕获组，保留键名等上下文
+fn mask(text: &str, re: &Regex) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in re.captures_iter(text) {
+        let groups: Vec<_> = if caps.len() > 1 { caps.iter().skip(1).flatten().collect() } else { caps.get(0).into_iter().collect() };
+        for group in groups {
+            if group.start() < last {
+                continue;
+            }
+            out.push_str(&text[last..group.start()]);
+            out.push_str(REDACTED);
+            last = group.end();
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// 隐藏 URL 中的密码，替换为 ***（方括号在 URL 中会被转义）
+fn redact_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// 未设置 max_files 时保留的轮转文件数
+const DEFAULT_MAX_FILES: usize = 5;
+
+#[pyfunction]
+#[pyo3(signature = (enabled, target=None, format=None, max_bytes=None, max_files=None))]
+pub fn set_debug(
+    enabled: bool,
+    target: Option<String>,
+    format: Option<String>,
+    max_bytes: Option<u64>,
+    max_files: Option<usize>,
+) -> PyResult<()> {
+    let json = match format.as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => return Err(PyValueError::new_err(format!("Invalid format: '{}'. Valid values: text, json", other))),
+    };
+    if max_bytes == Some(0) {
+        return Err(PyValueError::new_err("max_bytes must be at least 1"));
+    }
+    if max_files == Some(0) {
+        return Err(PyValueError::new_err("max_files must be at least 1"));
+    }
+    if max_files.is_some() && max_bytes.is_none() {
+        return Err(PyValueError::new_err("max_files requires max_bytes"));
+    }
+    let target = match target {
         Some(t) if t.to_lowercase() == "console" || t.is_empty() => DebugTarget::Console,
+        // "logging" 或 "logging:<logger 名称>"
+        Some(t) if t == "logging" => DebugTarget::Logger(DEFAULT_LOGGER.to_string()),
+        Some(t) if t.starts_with("logging:") => DebugTarget::Logger(t["logging:".len()..].to_string()),
         Some(t) => {
             let path = Path::new(&t);
             if path.is_dir() { DebugTarget::File(path.join("debug.log").to_string_lossy().to_string()) }
@@ -36,6 +186,16 @@ pub fn set_debug(enabled: bool, target: Option<String>) {
         },
         None => DebugTarget::Console,
     };
+    if max_bytes.is_some() && !matches!(target, DebugTarget::File(_)) {
+        return Err(PyValueError::new_err("max_bytes and max_files only apply to a log file target"));
+    }
+
+    let mut cfg = DEBUG_CONFIG.write().unwrap();
+    cfg.enabled = enabled;
+    cfg.target = target;
+    cfg.json = json;
+    cfg.rotation = max_bytes.map(|max_bytes| Rotation { max_bytes, max_files: max_files.unwrap_or(DEFAULT_MAX_FILES) });
+    Ok(())
 }
 
 pub fn debug_log(
@@ -47,16 +207,111 @@ pub fn debug_log(
     response: &Value,
     proxy: Option<&str>,
     proxy_auth: Option<&str>,
+    auth_scheme: Option<&str>,
 ) {
-    if !DEBUG_CONFIG.read().unwrap().enabled { return; }
+    let (enabled, json) = {
+        let cfg = DEBUG_CONFIG.read().unwrap();
+        (cfg.enabled, cfg.json)
+    };
+    if !enabled { return; }
+
+    // 凭据本身不写入日志，请求头和响应体按脱敏规则处理
+    let (url, proxy) = (redact_url(url), proxy.map(redact_url));
+    let (headers, response) = {
+        let redaction = REDACTION.read().unwrap();
+        let mut response = response.clone();
+        if let Some(content) = response.get("content").and_then(|c| c.as_str()) {
+            response["content"] = Value::from(redaction.body(content));
+        }
+        if let Some(Value::Object(headers)) = response.get("headers") {
+            response["headers"] = Value::Object(redaction.headers(headers));
+        }
+        (redaction.headers(headers), response)
+    };
+    if json {
+        let mut record = json!({
+            "event": "response",
+            "tag": tag,
+            "method": method,
+            "url": url,
+            "status": status.as_u16(),
+            "headers": headers,
+            "response": response,
+        });
+        if let Some(p) = &proxy { record["proxy"] = Value::from(p.as_str()); }
+        if let Some(auth) = proxy_auth { record["proxy_auth"] = Value::from(auth); }
+        if let Some(scheme) = auth_scheme { record["auth"] = Value::from(scheme); }
+        emit(Level::Debug, &format!("[{}] {} {} {}", tag, method, url, status), Some(record));
+        return;
+    }
 
     let mut msg = format!("\n==== [{}] ====\nMethod: {}\nURL: {}\nStatus: {}\n", tag, method, url, status);
     msg.push_str(&format!("Headers: {:?}\nResponse: {}\n", headers, response));
-    if let Some(p) = proxy { msg.push_str(&format!("Proxy: {}\n", p)); }
+    if let Some(p) = &proxy { msg.push_str(&format!("Proxy: {}\n", p)); }
     if let Some(auth) = proxy_auth { msg.push_str(&format!("Proxy Auth: {}\n", auth)); }
+    if let Some(scheme) = auth_scheme { msg.push_str(&format!("Auth: {} [REDACTED]\n", scheme)); }
+
+    write_log(Level::Debug, &msg);
+}
+
+pub fn debug_enabled() -> bool {
+    DEBUG_CONFIG.read().unwrap().enabled
+}
+
+/// 写入 set_debug 指定的输出位置，不受 enabled 开关影响
+///
+/// 转发到 Python logging 时由 logger 的级别决定是否输出。
+pub fn write_log(level: Level, msg: &str) {
+    emit(level, msg, None);
+}
+
+/// 写入一条日志；format="json" 时每条一行，fields 中的字段合并到记录中
+fn emit(level: Level, msg: &str, fields: Option<Value>) {
+    // 先释放配置锁再获取 GIL，避免与持有 GIL 调用 set_debug 的线程互相等待
+    let cfg = DEBUG_CONFIG.read().unwrap().clone();
+    let line = if cfg.json {
+        let mut record = json!({
+            "time": Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            "level": level.name(),
+            "message": msg.trim(),
+        });
+        if let (Some(Value::Object(fields)), Value::Object(record)) = (fields, &mut record) {
+            record.extend(fields);
+        }
+        record.to_string()
+    } else {
+        msg.to_string()
+    };
+    match cfg.target {
+        DebugTarget::Console => println!("{}", line),
+        DebugTarget::File(path) => append(&path, &line, cfg.rotation),
+        DebugTarget::Logger(name) => Python::with_gil(|py| {
+            let logged = py.import("logging")
+                .and_then(|logging| logging.call_method1("getLogger", (name,)))
+                .and_then(|logger| logger.call_method1("log", (level as i32, line.trim())));
+            if let Err(e) = logged {
+                e.print(py);
+            }
+        }),
+    }
+}
+
+fn append(path: &str, line: &str, rotation: Option<Rotation>) {
+    let _guard = FILE_LOCK.lock().unwrap();
+    if let Some(rotation) = rotation {
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 + 1 > rotation.max_bytes {
+            rotate(path, rotation.max_files);
+        }
+    }
+    let _ = OpenOptions::new().create(true).append(true).open(path).map(|mut f| writeln!(f, "{}", line));
+}
 
-    match &DEBUG_CONFIG.read().unwrap().target {
-        DebugTarget::Console => println!("{}", msg),
-        DebugTarget::File(path) => { let _ = OpenOptions::new().create(true).append(true).open(path).map(|mut f| writeln!(f, "{}", msg)); }
+/// debug.log → debug.log.1 → … → debug.log.<max_files>，最旧的文件被删除
+fn rotate(path: &str, max_files: usize) {
+    let _ = fs::remove_file(format!("{}.{}", path, max_files));
+    for n in (1..max_files).rev() {
+        let _ = fs::rename(format!("{}.{}", path, n), format!("{}.{}", path, n + 1));
     }
+    let _ = fs::rename(path, format!("{}.1", path));
 }
@@ -0,0 +1,51 @@
+use pyo3::prelude::*;
+use serde_json::json;
+use crate::network::client::cache_capacity;
+use crate::request::config::SUPPORTED_COMPRESSION;
+use crate::request::{isolation, limiter};
+use crate::utils::json_to_py;
+use crate::GLOBAL_CLIENT_CONFIG;
+
+/// 当前 wheel 编译进来的功能和运行时限制
+///
+/// features 中的取值在编译时确定；limits 反映 configure_global_client 等设置后的当前值。
+#[pyfunction]
+pub fn capabilities(py: Python) -> PyResult<PyObject> {
+    let global = GLOBAL_CLIENT_CONFIG.read().unwrap().clone();
+    let (max_in_flight, max_rps, max_connections_per_host) = limiter::limits();
+    let value = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": {
+            "http2": true,
+            "http3": false,
+            "socks": true,
+            "tls_backend": "native-tls",
+            "tls13_min_version": false,
+            "compression": SUPPORTED_COMPRESSION,
+            "brotli": SUPPORTED_COMPRESSION.contains(&"br"),
+            "zstd": SUPPORTED_COMPRESSION.contains(&"zstd"),
+            "doh": false,
+            "websocket": true,
+            "sse": true,
+            "pac": true,
+            "jsonpath": true,
+            "opentelemetry": true,
+            "unix_socket": cfg!(unix),
+            "interface_binding": cfg!(unix),
+        },
+        "limits": {
+            "max_in_flight": max_in_flight,
+            "max_rps": max_rps,
+            "max_connections_per_host": max_connections_per_host,
+            "scheduler": limiter::scheduler(),
+            "max_cached_clients": cache_capacity(),
+            "isolated_worker_threads": isolation::worker_threads(),
+            "max_response_headers": global.max_response_headers,
+            "max_header_bytes": global.max_header_bytes,
+            "max_response_bytes": global.max_response_bytes,
+            "memory_threshold": global.memory_threshold,
+            "default_timeout": global.timeout.unwrap_or(30.0),
+        },
+    });
+    json_to_py(py, &value)
+}
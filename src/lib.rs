@@ -3,16 +3,45 @@
 mod network;
 mod request;
 mod debug;
+mod audit;
+mod stats;
+mod telemetry;
+mod features;
 mod utils;
+mod testing;
+mod report;
 
 use std::process::Command;
+use std::sync::RwLock;
 use pyo3::prelude::*;
 use once_cell::sync::Lazy;
-use tokio::sync::Mutex;
 use reqwest::Client;
-pub use network::{HttpVersion, ProxyConfig};
-pub use request::{RequestItem, fetch_single, fetch_requests, set_global_proxy};
-pub use crate::debug::set_debug;
+pub use network::{CaBundle, ClientIdentity, HttpVersion, ProxyConfig, TlsVersion};
+pub use network::client::{close_idle_connections, evict_host};
+pub use network::{connect_websocket, WebSocket};
+pub use network::dns::flush_dns_cache;
+pub use network::pool_stats::get_pool_stats;
+pub use request::{RequestItem, fetch_single, fetch_requests, set_global_proxy, set_default_headers, configure_global_client, GlobalClientConfig};
+pub use request::{PreparedRequest, prepare, send, Session};
+pub use request::OAuth2ClientCredentials;
+pub use request::{register_serializer, unregister_serializer, register_transform, unregister_transform, set_host_profile, remove_host_profile, set_hooks, set_middleware};
+pub use request::set_watchdog;
+pub use request::replay::replay;
+pub use request::{retry_failed, RampUp, RetryPolicy};
+pub use request::fetch_sse;
+pub use request::{fetch_stream, ResponseStream};
+pub use request::{download, ChecksumMismatch};
+pub use request::{fetch_jsonrpc, JsonRpcRequest};
+pub use request::configure_isolated_pool;
+pub use request::{configure_response_cache, clear_response_cache};
+pub use request::{export_validators, import_validators, clear_validators};
+pub use report::generate_report;
+pub use testing::{inject_fault, clear_faults, use_virtual_clock, advance_clock, use_cassette, use_mock_transport, MockTransport, TestServer};
+pub use crate::debug::{set_debug, set_debug_redaction};
+pub use crate::audit::set_audit_log;
+pub use crate::stats::{get_stats, reset_stats};
+pub use crate::telemetry::configure_tracing;
+pub use crate::features::capabilities;
 pub use request::concurrency::ConcurrencyMode;
 use crate::network::SslVerify;
 
@@ -28,24 +57,26 @@ pub static DEFAULT_USER_AGENT: Lazy<String> = Lazy::new(|| {
     format!("Rust/{} rusty-req/{}", rust_version, env!("CARGO_PKG_VERSION"))
 });
 
-pub static GLOBAL_CLIENT: Lazy<Mutex<Client>> = Lazy::new(|| {
-    Mutex::new(
-        Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .gzip(true)
-            .brotli(true)
-            .deflate(true)
-            .user_agent(&*DEFAULT_USER_AGENT)  // 复用静态变量
-            .build()
-            .expect("Failed to create HTTP client"),
-    )
+// 创建后不再替换，Client 内部是 Arc，clone 无需加锁
+pub static GLOBAL_CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .gzip(true)
+        .brotli(true)
+        .deflate(true)
+        .user_agent(&*DEFAULT_USER_AGENT)  // 复用静态变量
+        .build()
+        .expect("Failed to create HTTP client")
 });
 
 // 移除单独的 DEFAULT_USER_AGENT 定义
-pub static GLOBAL_PROXY: Lazy<Mutex<Option<ProxyConfig>>> = Lazy::new(|| Mutex::new(None));
+// 每个请求都会读取，写入只发生在 set_global_proxy，使用读写锁避免批量启动时串行
+pub static GLOBAL_PROXY: Lazy<RwLock<Option<ProxyConfig>>> = Lazy::new(|| RwLock::new(None));
+
+pub static GLOBAL_CLIENT_CONFIG: Lazy<RwLock<GlobalClientConfig>> = Lazy::new(|| RwLock::new(GlobalClientConfig::default()));
 
 #[pymodule]
-fn rusty_req(_py: Python, m: &PyModule) -> PyResult<()> {
+fn rusty_req(py: Python, m: &PyModule) -> PyResult<()> {
     // 添加版本信息
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
 
@@ -55,13 +86,70 @@ fn rusty_req(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<RequestItem>()?;
     m.add_class::<HttpVersion>()?;
     m.add_class::<SslVerify>()?;
+    m.add_class::<PreparedRequest>()?;
+    m.add_class::<OAuth2ClientCredentials>()?;
+    m.add_class::<ClientIdentity>()?;
+    m.add_class::<CaBundle>()?;
+    m.add_class::<TlsVersion>()?;
+    m.add_class::<RetryPolicy>()?;
+    m.add_class::<RampUp>()?;
+    m.add_class::<WebSocket>()?;
+    m.add_class::<Session>()?;
 
     // 暴露函数
     use pyo3::wrap_pyfunction;
     m.add_function(wrap_pyfunction!(set_debug, m)?)?;
+    m.add_function(wrap_pyfunction!(set_debug_redaction, m)?)?;
+    m.add_function(wrap_pyfunction!(set_audit_log, m)?)?;
+    m.add_function(wrap_pyfunction!(get_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(reset_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_tracing, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities, m)?)?;
     m.add_function(wrap_pyfunction!(fetch_single, m)?)?;
     m.add_function(wrap_pyfunction!(fetch_requests, m)?)?;
     m.add_function(wrap_pyfunction!(set_global_proxy, m)?)?;
+    m.add_function(wrap_pyfunction!(set_default_headers, m)?)?;
+    m.add_function(wrap_pyfunction!(set_host_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(remove_host_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(set_hooks, m)?)?;
+    m.add_function(wrap_pyfunction!(set_middleware, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_global_client, m)?)?;
+    m.add_function(wrap_pyfunction!(prepare, m)?)?;
+    m.add_function(wrap_pyfunction!(send, m)?)?;
+    m.add_function(wrap_pyfunction!(close_idle_connections, m)?)?;
+    m.add_function(wrap_pyfunction!(evict_host, m)?)?;
+    m.add_function(wrap_pyfunction!(get_pool_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(register_serializer, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_serializer, m)?)?;
+    m.add_function(wrap_pyfunction!(register_transform, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_transform, m)?)?;
+    m.add_function(wrap_pyfunction!(set_watchdog, m)?)?;
+    m.add_function(wrap_pyfunction!(inject_fault, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_faults, m)?)?;
+    m.add_function(wrap_pyfunction!(use_cassette, m)?)?;
+    m.add_function(wrap_pyfunction!(use_mock_transport, m)?)?;
+    m.add_class::<MockTransport>()?;
+    m.add_class::<TestServer>()?;
+    m.add_function(wrap_pyfunction!(use_virtual_clock, m)?)?;
+    m.add_function(wrap_pyfunction!(advance_clock, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_report, m)?)?;
+    m.add_function(wrap_pyfunction!(replay, m)?)?;
+    m.add_function(wrap_pyfunction!(retry_failed, m)?)?;
+    m.add_function(wrap_pyfunction!(connect_websocket, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_sse, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_stream, m)?)?;
+    m.add_class::<ResponseStream>()?;
+    m.add_function(wrap_pyfunction!(download, m)?)?;
+    m.add("ChecksumMismatch", py.get_type::<ChecksumMismatch>())?;
+    m.add_function(wrap_pyfunction!(fetch_jsonrpc, m)?)?;
+    m.add_class::<JsonRpcRequest>()?;
+    m.add_function(wrap_pyfunction!(flush_dns_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_isolated_pool, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_response_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_response_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(export_validators, m)?)?;
+    m.add_function(wrap_pyfunction!(import_validators, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_validators, m)?)?;
 
     Ok(())
 }
@@ -0,0 +1,61 @@
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use reqwest::Certificate;
+
+/// 额外信任的根证书（PEM），在系统根证书之外使用
+#[pyclass]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CaBundle {
+    pem: Vec<u8>,
+}
+
+#[pymethods]
+impl CaBundle {
+    /// 从 PEM 文件加载，文件中可以包含多个证书
+    #[new]
+    fn new(path: String) -> PyResult<Self> {
+        let pem = std::fs::read(&path)
+            .map_err(|e| PyIOError::new_err(format!("Failed to read '{}': {}", path, e)))?;
+        Self::load(pem)
+    }
+
+    #[staticmethod]
+    fn from_pem(pem: &PyBytes) -> PyResult<Self> {
+        Self::load(pem.as_bytes().to_vec())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CaBundle({} certificates)", self.certificates().map(|c| c.len()).unwrap_or(0))
+    }
+}
+
+impl CaBundle {
+    fn load(pem: Vec<u8>) -> PyResult<Self> {
+        let bundle = CaBundle { pem };
+        let count = bundle.certificates().map_err(PyValueError::new_err)?.len();
+        if count == 0 {
+            return Err(PyValueError::new_err("CA bundle contains no certificates"));
+        }
+        Ok(bundle)
+    }
+
+    pub(crate) fn certificates(&self) -> Result<Vec<Certificate>, String> {
+        Certificate::from_pem_bundle(&self.pem).map_err(|e| format!("Invalid CA bundle: {}", e))
+    }
+
+    pub(crate) fn pem(&self) -> &[u8] {
+        &self.pem
+    }
+
+    /// 逐个拆分 PEM 块，供不经过 reqwest 的连接（如 WebSocket）使用
+    pub(crate) fn native_certificates(&self) -> Result<Vec<native_tls::Certificate>, String> {
+        const END: &str = "-----END CERTIFICATE-----";
+        let pem = String::from_utf8_lossy(&self.pem);
+        pem.split_inclusive(END)
+            .filter(|block| block.contains(END))
+            .map(|block| native_tls::Certificate::from_pem(block.trim().as_bytes())
+                .map_err(|e| format!("Invalid CA bundle: {}", e)))
+            .collect()
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use pyo3::pyfunction;
+use reqwest::{redirect, Client, Proxy};
+use url::Url;
+use crate::network::{CaBundle, ClientIdentity, HttpVersion, ProxyConfig, TlsVersion};
+use crate::network::dns::{IpVersion, SystemResolver};
+use crate::network::relay::{relay_for, Upstream};
+use crate::network::tunnel::with_credentials;
+use crate::network::proxy_config::{bypasses, Bypass};
+use crate::network::local_address::resolve_local_address;
+use crate::network::impersonate::Impersonate;
+use crate::network::pac::{self, PacDns};
+use crate::network::rustls_config;
+use crate::network::pool_stats;
+use crate::network::redirect::build_policy;
+use crate::{DEFAULT_USER_AGENT, GLOBAL_CLIENT_CONFIG};
+
+/// 构建客户端所需的选项，由请求参数和全局配置合并得到
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ClientOptions {
+    pub proxy_config: Option<ProxyConfig>,
+    pub http_version: HttpVersion,
+    pub ssl_verify: bool,
+    pub connect_timeout: Option<Duration>,
+    pub allow_redirects: bool,
+    pub max_redirects: usize,
+    pub dns_timeout: Option<Duration>,
+    pub identity: Option<ClientIdentity>,
+    pub ca_bundle: Option<CaBundle>,
+    pub decompress: bool,
+    pub tls_min_version: Option<TlsVersion>,
+    pub tls_max_version: Option<TlsVersion>,
+    pub unix_socket: Option<String>,
+    pub resolve: Option<BTreeMap<String, SocketAddr>>,
+    pub ip_version: IpVersion,
+    pub local_address: Option<String>,
+    pub isolated: bool,
+    pub keepalive_interval: Option<Duration>,
+    pub user_agent: Option<String>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout: Option<Duration>,
+    /// 主机名（小写）到 SPKI 指纹列表的映射，设置时改用 rustls 在握手中校验
+    pub cert_pins: Option<BTreeMap<String, Vec<String>>>,
+    /// 模拟浏览器的 TLS ClientHello，设置时改用 rustls
+    pub impersonate: Option<Impersonate>,
+}
+
+impl ClientOptions {
+    pub(crate) fn redirect_policy(&self) -> redirect::Policy {
+        build_policy(self.allow_redirects, self.max_redirects)
+    }
+}
+
+/// 客户端缓存的键：目标 scheme + 客户端选项
+///
+/// 客户端只按 scheme 选择代理，与目标主机无关；选项相同的请求共享同一个客户端，
+/// 连接池在客户端内部按主机区分。
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    scheme: String,
+    options: ClientOptions,
+}
+
+struct CachedClient {
+    client: Client,
+    // 使用过该客户端的主机，供 evict_host 查找
+    hosts: HashSet<String>,
+    last_used: u64,
+}
+
+/// 按最近使用淘汰的客户端缓存
+#[derive(Default)]
+struct ClientCache {
+    entries: HashMap<ClientKey, CachedClient>,
+    tick: u64,
+}
+
+impl ClientCache {
+    fn get(&mut self, key: &ClientKey, host: &str) -> Option<Client> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.tick;
+        if !entry.hosts.contains(host) {
+            entry.hosts.insert(host.to_string());
+        }
+        Some(entry.client.clone())
+    }
+
+    fn insert(&mut self, key: ClientKey, host: String, client: Client, capacity: usize) {
+        self.tick += 1;
+        let entry = CachedClient { client, hosts: HashSet::from([host]), last_used: self.tick };
+        self.entries.insert(key, entry);
+        while self.entries.len() > capacity {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k.clone()) else { break };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+// 未通过 configure_global_client(max_cached_clients=...) 设置时的缓存容量
+const DEFAULT_MAX_CACHED_CLIENTS: usize = 64;
+
+static CLIENT_CACHE: Lazy<Mutex<ClientCache>> = Lazy::new(|| Mutex::new(ClientCache::default()));
+
+pub(crate) fn cache_capacity() -> usize {
+    GLOBAL_CLIENT_CONFIG.read().unwrap().max_cached_clients.unwrap_or(DEFAULT_MAX_CACHED_CLIENTS)
+}
+
+pub(crate) async fn create_reqwest_client(
+    request_url: &str,
+    options: &ClientOptions,
+) -> Result<Client, Box<dyn std::error::Error>> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .gzip(options.decompress)
+        .brotli(options.decompress)
+        .deflate(options.decompress)
+        .redirect(options.redirect_policy())
+        .dns_resolver(Arc::new(SystemResolver::new(
+            options.dns_timeout,
+            options.ip_version,
+            options.resolve.clone().unwrap_or_default(),
+        )))
+        .tls_info(true)
+        .user_agent(options.user_agent.as_deref().unwrap_or(&DEFAULT_USER_AGENT));
+
+    builder = options.http_version.apply_to_builder(builder);
+
+    if let Some(max_idle) = options.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(idle_timeout) = options.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(idle_timeout);
+    }
+
+    if let Some(connect_timeout) = options.connect_timeout {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+
+    // 空闲连接保活：TCP keepalive 探测，HTTP/2 连接额外发送 PING，避免被 NAT 静默断开
+    if let Some(interval) = options.keepalive_interval {
+        builder = builder
+            .tcp_keepalive(interval)
+            .http2_keep_alive_interval(interval)
+            .http2_keep_alive_while_idle(true);
+    }
+
+    if options.cert_pins.is_some() || options.impersonate.is_some() {
+        // 证书指纹校验和 ClientHello 定制需要 rustls；ssl_verify、TLS 版本、客户端证书和根证书由 rustls_config 处理
+        builder = builder.use_preconfigured_tls(rustls_config::build(options)?);
+    } else {
+        if !options.ssl_verify {
+            builder = builder
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true);
+        }
+
+        // native-tls 无法强制 TLS 1.3，最高版本为 1.3 时等同于不限制
+        if let Some(min) = options.tls_min_version {
+            if min == TlsVersion::Tls1_3 {
+                return Err("tls_min_version TLS1_3 is not supported by the native TLS backend".into());
+            }
+            builder = builder.min_tls_version(min.to_reqwest());
+        }
+        if let Some(max) = options.tls_max_version.filter(|v| *v != TlsVersion::Tls1_3) {
+            builder = builder.max_tls_version(max.to_reqwest());
+        }
+
+        // mTLS 客户端证书
+        if let Some(identity) = &options.identity {
+            builder = builder.identity(identity.to_reqwest()?);
+        }
+
+        // 额外信任的根证书，如企业代理的私有 CA
+        if let Some(ca_bundle) = &options.ca_bundle {
+            for cert in ca_bundle.certificates()? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+    }
+
+    // Unix socket 经本地中转端口转发，忽略所有代理配置
+    if let Some(path) = &options.unix_socket {
+        let relay = relay_for(Upstream::Unix(path.clone())).await?;
+        return Ok(builder.no_proxy().proxy(Proxy::all(relay)?).build()?);
+    }
+
+    // 环境变量中的代理按 trust_env 由 ProxyConfig::effective 合并，不使用 reqwest 的自动检测
+    builder = builder.no_proxy();
+    let proxy_config = ProxyConfig::effective(options.proxy_config.as_ref());
+
+    // 绑定出站连接的本地地址；代理链由本地中转端口发起连接，不绑定
+    let has_chain = proxy_config.as_ref().and_then(|c| c.chain.as_ref()).is_some_and(|c| !c.is_empty());
+    if let Some(local) = options.local_address.as_deref().filter(|_| !has_chain) {
+        builder = builder.local_address(resolve_local_address(local, options.ip_version)?);
+    }
+
+    if let Some(config) = &proxy_config { // 解包 Option<ProxyConfig>
+        let rules = config.bypass_rules();
+        if let Some(chain) = config.chain.as_ref().filter(|c| !c.is_empty()) {
+            // 代理链经本地中转端口转发，忽略 all / http / https
+            let relay = Url::parse(&relay_for(Upstream::Chain(chain.clone())).await?)?;
+            builder = builder.proxy(bypassable(relay, None, rules));
+        } else if let Some(location) = &config.pac {
+            // PAC 脚本对每个请求地址（包括重定向）单独求值
+            let script = pac::load(location).await?;
+            let config = config.clone();
+            let dns = PacDns { resolve: options.resolve.clone(), timeout: options.dns_timeout };
+            builder = builder.proxy(Proxy::custom(move |url| {
+                if url.host_str().is_some_and(|host| bypasses(&rules, host)) {
+                    return None;
+                }
+                let proxy = script.proxy_for(url, &dns)?;
+                with_credentials(&config, proxy.as_str()).ok()
+            }));
+        } else if let Some(all_proxy) = &config.all {
+            // socks5h:// 由代理解析目标域名，socks5:// 在本地解析
+            builder = builder.proxy(bypassable(with_credentials(config, all_proxy)?, None, rules));
+        } else {
+            // 如果没有 all_proxy，则根据 scheme 判断
+            let parsed = Url::parse(request_url)?; // 使用请求的 url 来判断 scheme
+            match parsed.scheme() {
+                "http" => {
+                    if let Some(http_proxy) = &config.http {
+                        builder = builder.proxy(bypassable(with_credentials(config, http_proxy)?, Some("http"), rules));
+                    }
+                }
+                "https" => {
+                    if let Some(https_proxy) = &config.https {
+                        builder = builder.proxy(bypassable(with_credentials(config, https_proxy)?, Some("https"), rules));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+/// 按请求地址决定是否走代理：scheme 不符或命中 no_proxy 时直连
+///
+/// 每次请求（包括重定向后的地址）都会重新判断。
+fn bypassable(proxy_url: Url, scheme: Option<&'static str>, rules: Vec<Bypass>) -> Proxy {
+    Proxy::custom(move |url| {
+        if scheme.is_some_and(|s| url.scheme() != s) {
+            return None;
+        }
+        if url.host_str().is_some_and(|host| bypasses(&rules, host)) {
+            return None;
+        }
+        Some(proxy_url.clone())
+    })
+}
+
+/// 获取缓存的客户端，不存在时创建
+pub(crate) async fn get_or_create_client(
+    request_url: &str,
+    options: &ClientOptions,
+) -> Result<Client, Box<dyn std::error::Error>> {
+    let parsed = Url::parse(request_url)?;
+    let host = parsed.host_str().unwrap_or("").to_lowercase();
+    let key = ClientKey { scheme: parsed.scheme().to_string(), options: options.clone() };
+
+    if let Some(client) = CLIENT_CACHE.lock().unwrap().get(&key, &host) {
+        return Ok(client);
+    }
+
+    let client = create_reqwest_client(request_url, options).await?;
+    CLIENT_CACHE.lock().unwrap().insert(key, host, client.clone(), cache_capacity());
+    Ok(client)
+}
+
+/// 关闭所有空闲连接，之后的请求会重新解析 DNS 并建立新连接
+///
+/// 进行中的请求持有自己的客户端引用，不受影响。
+#[pyfunction]
+pub fn close_idle_connections() {
+    CLIENT_CACHE.lock().unwrap().entries.clear();
+    pool_stats::forget_idle(None);
+}
+
+/// 移除访问过指定主机的客户端，返回被移除的客户端数量
+///
+/// 客户端由相同配置的主机共享，这些主机的空闲连接也会一并关闭。
+#[pyfunction]
+pub fn evict_host(host: String) -> usize {
+    let host = host.trim().to_lowercase();
+    let mut cache = CLIENT_CACHE.lock().unwrap();
+    let before = cache.entries.len();
+    cache.entries.retain(|_, entry| !entry.hosts.contains(&host));
+    pool_stats::forget_idle(Some(&host));
+    before - cache.entries.len()
+}
@@ -0,0 +1,255 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::net::{IpAddr, SocketAddr};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use hyper::client::connect::dns::Name;
+use once_cell::sync::Lazy;
+use pyo3::pyfunction;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use crate::request::watchdog::{set_current_phase, Phase};
+use crate::GLOBAL_CLIENT_CONFIG;
+
+/// 一次请求期间的 DNS 解析情况
+#[derive(Default)]
+pub struct DnsTrace {
+    /// 解析累计耗时，连接复用或命中缓存时为 None
+    pub elapsed: Option<Duration>,
+    /// 最近一次解析得到的地址，用于连接失败时报告目标 IP
+    pub addrs: Vec<SocketAddr>,
+    /// 新建连接时解析的主机名（小写，含静态解析和命中缓存），代理连接解析的是代理主机
+    pub hosts: Vec<String>,
+}
+
+tokio::task_local! {
+    static DNS_TRACE: RefCell<DnsTrace>;
+}
+
+/// 执行 future 并记录期间的 DNS 解析
+pub async fn track_dns<F: Future>(fut: F) -> (F::Output, DnsTrace) {
+    DNS_TRACE
+        .scope(RefCell::new(DnsTrace::default()), async move {
+            let output = fut.await;
+            (output, DNS_TRACE.with(|trace| trace.take()))
+        })
+        .await
+}
+
+fn record_addrs(addrs: &[SocketAddr]) {
+    let _ = DNS_TRACE.try_with(|trace| trace.borrow_mut().addrs = addrs.to_vec());
+}
+
+/// 解析 resolve 参数：主机名到 "IP" 或 "IP:port" 的映射，主机名统一为小写
+///
+/// 与 ClientBuilder::resolve 一致，端口由 URL 决定，映射中的端口不生效。
+pub(crate) fn parse_resolve(overrides: &HashMap<String, String>) -> PyResult<BTreeMap<String, SocketAddr>> {
+    overrides.iter().map(|(host, addr)| {
+        let addr = addr.trim();
+        let parsed = addr.parse::<SocketAddr>()
+            .or_else(|_| addr.trim_matches(['[', ']']).parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 0)))
+            .map_err(|_| PyValueError::new_err(format!(
+                "Invalid resolve address for '{}': '{}'. Expected IP or IP:port", host, addr
+            )))?;
+        Ok((host.trim().to_ascii_lowercase(), parsed))
+    }).collect()
+}
+
+/// 解析结果的地址族
+///
+/// Auto 保持系统返回的顺序，由连接器按 Happy Eyeballs 在 IPv6 和 IPv4 之间回退。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IpVersion {
+    #[default]
+    Auto,
+    V4,
+    V6,
+}
+
+impl IpVersion {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Ok(IpVersion::Auto),
+            "v4" | "ipv4" => Ok(IpVersion::V4),
+            "v6" | "ipv6" => Ok(IpVersion::V6),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid ip_version: '{}'. Valid values: v4, v6, auto",
+                name
+            ))),
+        }
+    }
+
+    fn accepts(&self, addr: &SocketAddr) -> bool {
+        match self {
+            IpVersion::Auto => true,
+            IpVersion::V4 => addr.is_ipv4(),
+            IpVersion::V6 => addr.is_ipv6(),
+        }
+    }
+}
+
+/// DNS 解析失败，与连接错误区分开
+#[derive(Debug)]
+pub struct DnsError {
+    pub host: String,
+    pub timed_out: bool,
+    pub cause: String,
+}
+
+impl fmt::Display for DnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.timed_out {
+            write!(f, "DNS resolution for '{}' timed out: {}", self.host, self.cause)
+        } else {
+            write!(f, "DNS resolution for '{}' failed: {}", self.host, self.cause)
+        }
+    }
+}
+
+impl std::error::Error for DnsError {}
+
+impl DnsError {
+    /// 在错误链中查找 DNS 错误
+    pub fn find<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a DnsError> {
+        let mut current = Some(err);
+        while let Some(e) = current {
+            if let Some(dns) = e.downcast_ref::<DnsError>() {
+                return Some(dns);
+            }
+            current = e.source();
+        }
+        None
+    }
+}
+
+/// 系统配置的 DNS 服务器（仅用于错误信息）
+pub fn system_nameservers() -> Vec<String> {
+    std::fs::read_to_string("/etc/resolv.conf")
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("nameserver"))
+                .map(|ns| ns.trim().to_string())
+                .filter(|ns| !ns.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 使用系统解析器，并对单次解析设置超时
+///
+/// 静态解析（resolve 参数）也在这里处理而不交给 ClientBuilder::resolve，保证每次新建连接都经过解析器并被记录。
+pub struct SystemResolver {
+    timeout: Option<Duration>,
+    ip_version: IpVersion,
+    overrides: BTreeMap<String, SocketAddr>,
+}
+
+impl SystemResolver {
+    pub fn new(timeout: Option<Duration>, ip_version: IpVersion, overrides: BTreeMap<String, SocketAddr>) -> Self {
+        Self { timeout, ip_version, overrides }
+    }
+}
+
+/// 按地址族过滤，没有符合的地址时报告为 DNS 错误
+fn filter_family(host: String, addrs: Vec<SocketAddr>, ip_version: IpVersion) -> Result<Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    let addrs: Vec<SocketAddr> = addrs.into_iter().filter(|addr| ip_version.accepts(addr)).collect();
+    record_addrs(&addrs);
+    if addrs.is_empty() {
+        let family = if ip_version == IpVersion::V4 { "IPv4" } else { "IPv6" };
+        return Err(Box::new(DnsError { host, timed_out: false, cause: format!("no {} address found", family) }));
+    }
+    Ok(Box::new(addrs.into_iter()))
+}
+
+// 解析结果和过期时间
+type CacheEntry = (Vec<SocketAddr>, Instant);
+
+// 进程内 DNS 缓存，按主机名索引
+static DNS_CACHE: Lazy<Mutex<HashMap<String, CacheEntry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 缓存有效期，未通过 configure_global_client(dns_cache_ttl=...) 开启时不缓存
+pub(crate) fn cache_ttl() -> Option<Duration> {
+    GLOBAL_CLIENT_CONFIG.read().unwrap().dns_cache_ttl
+        .filter(|ttl| *ttl > 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+pub(crate) fn cached(host: &str) -> Option<Vec<SocketAddr>> {
+    let mut cache = DNS_CACHE.lock().unwrap();
+    match cache.get(host) {
+        Some((addrs, expires)) if *expires > Instant::now() => Some(addrs.clone()),
+        Some(_) => {
+            cache.remove(host);
+            None
+        }
+        None => None,
+    }
+}
+
+/// 清空 DNS 缓存，返回清除的条目数
+#[pyfunction]
+pub fn flush_dns_cache() -> usize {
+    let mut cache = DNS_CACHE.lock().unwrap();
+    let count = cache.len();
+    cache.clear();
+    count
+}
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let timeout = self.timeout;
+        let ip_version = self.ip_version;
+        let fixed = self.overrides.get(&name.as_str().to_ascii_lowercase()).copied();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let _ = DNS_TRACE.try_with(|trace| trace.borrow_mut().hosts.push(host.to_ascii_lowercase()));
+            // 端口由连接器按 URL 设置，与 ClientBuilder::resolve 相同
+            if let Some(addr) = fixed {
+                record_addrs(&[addr]);
+                return Ok(Box::new(std::iter::once(addr)) as Addrs);
+            }
+            let ttl = cache_ttl();
+            if ttl.is_some() {
+                if let Some(addrs) = cached(&host) {
+                    return filter_family(host, addrs, ip_version);
+                }
+            }
+            set_current_phase(Phase::Resolving);
+            let started = Instant::now();
+            let lookup = tokio::net::lookup_host((host.clone(), 0));
+            let resolved = match timeout {
+                Some(limit) => match tokio::time::timeout(limit, lookup).await {
+                    Ok(resolved) => resolved,
+                    Err(_) => {
+                        return Err(Box::new(DnsError {
+                            host,
+                            timed_out: true,
+                            cause: format!("no answer within {:.2} seconds", limit.as_secs_f64()),
+                        }) as _);
+                    }
+                },
+                None => lookup.await,
+            };
+
+            set_current_phase(Phase::AwaitingHeaders);
+            let _ = DNS_TRACE.try_with(|trace| {
+                let mut trace = trace.borrow_mut();
+                trace.elapsed = Some(trace.elapsed.unwrap_or_default() + started.elapsed());
+            });
+            match resolved {
+                Ok(addrs) => {
+                    let addrs: Vec<SocketAddr> = addrs.collect();
+                    if let Some(ttl) = ttl {
+                        DNS_CACHE.lock().unwrap().insert(host.clone(), (addrs.clone(), Instant::now() + ttl));
+                    }
+                    filter_family(host, addrs, ip_version)
+                }
+                Err(e) => Err(Box::new(DnsError { host, timed_out: false, cause: e.to_string() }) as _),
+            }
+        })
+    }
+}
@@ -4,7 +4,7 @@ use pyo3::exceptions::PyValueError;
 use reqwest::ClientBuilder;
 
 #[pyclass]
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum HttpVersion {
     #[pyo3(name = "AUTO")]
     Auto,           // 自动协商（默认）
@@ -89,6 +89,15 @@ impl HttpVersion {
 }
 
 impl HttpVersion {
+    /// 自行构建 TLS 配置时在握手中声明的 ALPN 协议
+    pub(crate) fn alpn_protocols(&self) -> Vec<Vec<u8>> {
+        match self {
+            HttpVersion::Http1Only => vec![b"http/1.1".to_vec()],
+            HttpVersion::Http2PriorKnowledge => vec![b"h2".to_vec()],
+            HttpVersion::Auto | HttpVersion::Http2 => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        }
+    }
+
     // 转换为 reqwest 配置（这个方法不需要 #[pymethods] 标记）
     pub(crate) fn apply_to_builder(&self, builder: ClientBuilder) -> ClientBuilder {
         match self {
@@ -0,0 +1,93 @@
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use std::io::Cursor;
+use reqwest::Identity;
+use rustls_pemfile::Item;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum IdentitySource {
+    Pem { cert: Vec<u8>, key: Vec<u8> },
+    Pkcs12 { der: Vec<u8>, password: String },
+}
+
+/// mTLS 客户端证书，创建时读取并校验证书文件
+#[pyclass]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ClientIdentity {
+    source: IdentitySource,
+}
+
+fn read_file(path: &str) -> PyResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| PyIOError::new_err(format!("Failed to read '{}': {}", path, e)))
+}
+
+#[pymethods]
+impl ClientIdentity {
+    /// PEM 格式的证书和 PKCS#8 私钥
+    #[new]
+    fn new(cert: String, key: String) -> PyResult<Self> {
+        Self::from_pem(cert, key)
+    }
+
+    #[staticmethod]
+    fn from_pem(cert: String, key: String) -> PyResult<Self> {
+        let identity = ClientIdentity {
+            source: IdentitySource::Pem { cert: read_file(&cert)?, key: read_file(&key)? },
+        };
+        identity.to_reqwest().map_err(PyValueError::new_err)?;
+        Ok(identity)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (path, password=None))]
+    fn from_pkcs12(path: String, password: Option<String>) -> PyResult<Self> {
+        let identity = ClientIdentity {
+            source: IdentitySource::Pkcs12 { der: read_file(&path)?, password: password.unwrap_or_default() },
+        };
+        identity.to_reqwest().map_err(PyValueError::new_err)?;
+        Ok(identity)
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.source {
+            IdentitySource::Pem { .. } => "ClientIdentity(pem)".to_string(),
+            IdentitySource::Pkcs12 { .. } => "ClientIdentity(pkcs12)".to_string(),
+        }
+    }
+}
+
+impl ClientIdentity {
+    pub(crate) fn to_reqwest(&self) -> Result<Identity, String> {
+        match &self.source {
+            IdentitySource::Pem { cert, key } => Identity::from_pkcs8_pem(cert, key),
+            IdentitySource::Pkcs12 { der, password } => Identity::from_pkcs12_der(der, password),
+        }
+        .map_err(|e| format!("Invalid client identity: {}", e))
+    }
+
+    pub(crate) fn to_native_tls(&self) -> Result<native_tls::Identity, String> {
+        match &self.source {
+            IdentitySource::Pem { cert, key } => native_tls::Identity::from_pkcs8(cert, key),
+            IdentitySource::Pkcs12 { der, password } => native_tls::Identity::from_pkcs12(der, password),
+        }
+        .map_err(|e| format!("Invalid client identity: {}", e))
+    }
+
+    /// rustls 使用的证书链和私钥，只支持 PEM
+    pub(crate) fn to_rustls(&self) -> Result<(Vec<rustls::Certificate>, rustls::PrivateKey), String> {
+        let IdentitySource::Pem { cert, key } = &self.source else {
+            return Err("PKCS#12 client identities are not supported together with cert_pins; use ClientIdentity.from_pem".to_string());
+        };
+        let certs = rustls_pemfile::certs(&mut Cursor::new(cert))
+            .map_err(|e| format!("Invalid client identity: {}", e))?;
+        let key = rustls_pemfile::read_all(&mut Cursor::new(key))
+            .map_err(|e| format!("Invalid client identity: {}", e))?
+            .into_iter()
+            .find_map(|item| match item {
+                Item::PKCS8Key(der) | Item::RSAKey(der) | Item::ECKey(der) => Some(der),
+                _ => None,
+            })
+            .ok_or("Invalid client identity: no private key found")?;
+        Ok((certs.into_iter().map(rustls::Certificate).collect(), rustls::PrivateKey(key)))
+    }
+}
@@ -0,0 +1,95 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use rustls::{cipher_suite, kx_group, SupportedCipherSuite, SupportedKxGroup};
+
+/// 浏览器模板：请求头及其顺序，以及 TLS ClientHello 中的加密套件、密钥交换组和 ALPN
+///
+/// ClientHello 由 rustls 发送，按浏览器顺序声明 rustls 支持的套件和组；GREASE、扩展顺序和
+/// rustls 不支持的套件（如 CBC 套件）无法模拟，JA3/JA4 指纹与真实浏览器接近但不完全相同。
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Impersonate {
+    Chrome,
+    Firefox,
+}
+
+const CHROME_HEADERS: &[(&str, &str)] = &[
+    ("sec-ch-ua", "\"Chromium\";v=\"124\", \"Google Chrome\";v=\"124\", \"Not-A.Brand\";v=\"99\""),
+    ("sec-ch-ua-mobile", "?0"),
+    ("sec-ch-ua-platform", "\"Windows\""),
+    ("upgrade-insecure-requests", "1"),
+    ("user-agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36"),
+    ("accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"),
+    ("sec-fetch-site", "none"),
+    ("sec-fetch-mode", "navigate"),
+    ("sec-fetch-user", "?1"),
+    ("sec-fetch-dest", "document"),
+    ("accept-encoding", "gzip, deflate, br"),
+    ("accept-language", "en-US,en;q=0.9"),
+];
+
+const FIREFOX_HEADERS: &[(&str, &str)] = &[
+    ("user-agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0"),
+    ("accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8"),
+    ("accept-language", "en-US,en;q=0.5"),
+    ("accept-encoding", "gzip, deflate, br"),
+    ("upgrade-insecure-requests", "1"),
+    ("sec-fetch-dest", "document"),
+    ("sec-fetch-mode", "navigate"),
+    ("sec-fetch-site", "none"),
+    ("sec-fetch-user", "?1"),
+];
+
+impl Impersonate {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "chrome" => Ok(Impersonate::Chrome),
+            "firefox" => Ok(Impersonate::Firefox),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid impersonate: '{}'. Valid values: chrome, firefox",
+                name
+            ))),
+        }
+    }
+
+    pub fn headers(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Impersonate::Chrome => CHROME_HEADERS,
+            Impersonate::Firefox => FIREFOX_HEADERS,
+        }
+    }
+
+    /// ClientHello 中的加密套件，按浏览器顺序
+    pub(crate) fn cipher_suites(&self) -> Vec<SupportedCipherSuite> {
+        match self {
+            Impersonate::Chrome => vec![
+                cipher_suite::TLS13_AES_128_GCM_SHA256,
+                cipher_suite::TLS13_AES_256_GCM_SHA384,
+                cipher_suite::TLS13_CHACHA20_POLY1305_SHA256,
+                cipher_suite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+                cipher_suite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                cipher_suite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+                cipher_suite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                cipher_suite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+                cipher_suite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            ],
+            Impersonate::Firefox => vec![
+                cipher_suite::TLS13_AES_128_GCM_SHA256,
+                cipher_suite::TLS13_CHACHA20_POLY1305_SHA256,
+                cipher_suite::TLS13_AES_256_GCM_SHA384,
+                cipher_suite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+                cipher_suite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                cipher_suite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+                cipher_suite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+                cipher_suite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+                cipher_suite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            ],
+        }
+    }
+
+    /// supported_groups 扩展中的密钥交换组，按浏览器顺序
+    pub(crate) fn kx_groups(&self) -> Vec<&'static SupportedKxGroup> {
+        match self {
+            Impersonate::Chrome | Impersonate::Firefox => vec![&kx_group::X25519, &kx_group::SECP256R1, &kx_group::SECP384R1],
+        }
+    }
+}
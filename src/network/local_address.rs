@@ -0,0 +1,84 @@
+use std::io;
+use std::net::IpAddr;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use crate::network::dns::IpVersion;
+
+/// 校验 local_address：本机 IP 或网卡名
+pub(crate) fn check_local_address(value: &str) -> PyResult<()> {
+    if value.trim().parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+    match interface_addresses(value.trim()) {
+        Ok(addrs) if !addrs.is_empty() => Ok(()),
+        Ok(_) => Err(PyValueError::new_err(format!(
+            "Invalid local_address: '{}' is neither an IP address nor a network interface with an address",
+            value
+        ))),
+        Err(e) => Err(PyValueError::new_err(format!("Invalid local_address '{}': {}", value, e))),
+    }
+}
+
+/// 解析出站连接绑定的本地地址，网卡名按 ip_version 选择其地址
+pub(crate) fn resolve_local_address(value: &str, ip_version: IpVersion) -> io::Result<IpAddr> {
+    let value = value.trim();
+    if let Ok(ip) = value.parse::<IpAddr>() {
+        return Ok(ip);
+    }
+    // 链路本地 IPv6 地址需要 scope id，不适合绑定
+    let addrs: Vec<IpAddr> = interface_addresses(value)?
+        .into_iter()
+        .filter(|ip| !matches!(ip, IpAddr::V6(v6) if v6.segments()[0] & 0xffc0 == 0xfe80))
+        .collect();
+    let pick = |v4: bool| addrs.iter().copied().find(|ip| ip.is_ipv4() == v4);
+    match ip_version {
+        IpVersion::V4 => pick(true),
+        IpVersion::V6 => pick(false),
+        IpVersion::Auto => pick(true).or_else(|| pick(false)),
+    }
+    .ok_or_else(|| io::Error::new(
+        io::ErrorKind::AddrNotAvailable,
+        format!("network interface '{}' has no usable address", value),
+    ))
+}
+
+/// 读取网卡上配置的地址
+#[cfg(unix)]
+fn interface_addresses(name: &str) -> io::Result<Vec<IpAddr>> {
+    use std::ffi::CStr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let mut addrs = Vec::new();
+    let mut head: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: getifaddrs 成功后链表在 freeifaddrs 之前一直有效
+    unsafe {
+        if libc::getifaddrs(&mut head) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut current = head;
+        while !current.is_null() {
+            let ifa = &*current;
+            if !ifa.ifa_addr.is_null() && CStr::from_ptr(ifa.ifa_name).to_bytes() == name.as_bytes() {
+                match (*ifa.ifa_addr).sa_family as libc::c_int {
+                    libc::AF_INET => {
+                        let sin = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                        addrs.push(IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr))));
+                    }
+                    libc::AF_INET6 => {
+                        let sin6 = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                        addrs.push(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)));
+                    }
+                    _ => {}
+                }
+            }
+            current = ifa.ifa_next;
+        }
+        libc::freeifaddrs(head);
+    }
+    Ok(addrs)
+}
+
+#[cfg(not(unix))]
+fn interface_addresses(_name: &str) -> io::Result<Vec<IpAddr>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "binding to a network interface by name is only supported on Unix"))
+}
@@ -2,8 +2,29 @@
 pub mod http_version;
 pub mod proxy_config;
 pub mod ssl_verify;  // 新增
+pub mod redirect;
+pub mod client;
+pub mod dns;
+pub mod identity;
+pub mod ca_bundle;
+pub mod pinning;
+pub mod rustls_config;
+pub mod tls_version;
+pub mod impersonate;
+pub mod tls_info;
+pub mod tunnel;
+pub mod relay;
+pub mod websocket;
+pub mod local_address;
+pub mod socket_error;
+pub mod pac;
+pub mod pool_stats;
 
 // 重新导出，方便外部使用
 pub use http_version::HttpVersion;
 pub use proxy_config::ProxyConfig;
-pub use ssl_verify::SslVerify;  // 新增导出
\ No newline at end of file
+pub use ssl_verify::{SslVerify, SslVerifyArg};  // 新增导出
+pub use identity::ClientIdentity;
+pub use ca_bundle::CaBundle;
+pub use tls_version::TlsVersion;
+pub use websocket::{connect_websocket, WebSocket};
@@ -0,0 +1,878 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use url::Url;
+use crate::debug::{debug_enabled, write_log, Level};
+use crate::network::dns;
+
+// PAC 文件加载后在进程内缓存，按地址索引
+static PAC_CACHE: Lazy<tokio::sync::Mutex<HashMap<String, Arc<PacScript>>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+// 函数调用的最大嵌套深度，防止递归脚本耗尽栈
+const MAX_DEPTH: usize = 64;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+// dnsResolve 的默认等待上限，以及成功（未开启 DNS 缓存时）和失败结果的缓存时间
+const DNS_TIMEOUT: Duration = Duration::from_secs(2);
+const DNS_TTL: Duration = Duration::from_secs(60);
+const DNS_NEGATIVE_TTL: Duration = Duration::from_secs(5);
+
+/// 加载并解析 PAC 文件，支持 http(s):// 地址、file:// 地址和本地路径
+pub(crate) async fn load(location: &str) -> Result<Arc<PacScript>, String> {
+    let mut cache = PAC_CACHE.lock().await;
+    if let Some(script) = cache.get(location) {
+        return Ok(script.clone());
+    }
+
+    let source = if location.starts_with("http://") || location.starts_with("https://") {
+        // PAC 文件本身直连获取
+        let client = reqwest::Client::builder().no_proxy().timeout(FETCH_TIMEOUT).build()
+            .map_err(|e| e.to_string())?;
+        client.get(location).send().await
+            .and_then(|res| res.error_for_status())
+            .map_err(|e| format!("Failed to fetch PAC file '{}': {}", location, e))?
+            .text().await
+            .map_err(|e| format!("Failed to read PAC file '{}': {}", location, e))?
+    } else {
+        let path = match Url::parse(location) {
+            Ok(url) if url.scheme() == "file" => url.to_file_path()
+                .map_err(|_| format!("Invalid PAC file URL '{}'", location))?,
+            _ => location.into(),
+        };
+        tokio::fs::read_to_string(&path).await
+            .map_err(|e| format!("Failed to read PAC file '{}': {}", location, e))?
+    };
+
+    let script = Arc::new(PacScript::parse(&source).map_err(|e| format!("Invalid PAC file '{}': {}", location, e))?);
+    cache.insert(location.to_string(), script.clone());
+    Ok(script)
+}
+
+/// 解析 FindProxyForURL 的返回值，取第一个支持的条目；DIRECT 返回 None
+///
+/// 返回值形如 "PROXY proxy:8080; SOCKS socks:1080; DIRECT"。
+pub(crate) fn parse_result(result: &str) -> Option<Url> {
+    for entry in result.split(';') {
+        let mut parts = entry.split_whitespace();
+        let (Some(kind), target) = (parts.next(), parts.next()) else { continue };
+        let scheme = match (kind.to_ascii_uppercase().as_str(), target) {
+            ("DIRECT", _) => return None,
+            ("PROXY" | "HTTP", Some(_)) => "http",
+            ("HTTPS", Some(_)) => "https",
+            ("SOCKS" | "SOCKS5", Some(_)) => "socks5",
+            // SOCKS4 等不支持的类型尝试下一项
+            _ => continue,
+        };
+        if let Ok(url) = Url::parse(&format!("{}://{}", scheme, target.unwrap_or_default())) {
+            return Some(url);
+        }
+    }
+    None
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+// 按长度从长到短排列，优先匹配较长的运算符
+const PUNCTS: [&str; 24] = [
+    "===", "!==", "==", "!=", "<=", ">=", "&&", "||",
+    "(", ")", "{", "}", ";", ",", "=", "!", "+", "-", "<", ">", "?", ":", ".", "%",
+];
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                let Some(&ch) = chars.get(i) else { return Err("unterminated string".to_string()) };
+                i += 1;
+                match ch {
+                    _ if ch == c => break,
+                    '\\' => {
+                        let escaped = chars.get(i).copied().ok_or("unterminated string")?;
+                        i += 1;
+                        s.push(match escaped { 'n' => '\n', 't' => '\t', 'r' => '\r', other => other });
+                    }
+                    _ => s.push(ch),
+                }
+            }
+            tokens.push(Token::Str(s));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(Token::Num(text.parse().map_err(|_| format!("invalid number '{}'", text))?));
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let punct = PUNCTS.iter().find(|p| rest.starts_with(**p))
+                .ok_or_else(|| format!("unexpected character '{}'", c))?;
+            tokens.push(Token::Punct(punct));
+            i += punct.len();
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Expr {
+    Value(Value),
+    Var(String),
+    Assign(String, Box<Expr>),
+    Call(String, Vec<Expr>),
+    Method(Box<Expr>, String, Vec<Expr>),
+    Property(Box<Expr>, String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Var(Vec<(String, Option<Expr>)>),
+    Expr(Expr),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    Return(Option<Expr>),
+    Block(Vec<Stmt>),
+}
+
+#[derive(Debug)]
+struct Function {
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let matched = self.is_punct(punct);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at token {}", punct, self.pos))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Ok(name.clone())
+            }
+            _ => Err(format!("expected identifier at token {}", self.pos)),
+        }
+    }
+
+    fn program(&mut self) -> Result<(HashMap<String, Function>, Vec<Stmt>), String> {
+        let mut functions = HashMap::new();
+        let mut globals = Vec::new();
+        while self.peek().is_some() {
+            if self.is_keyword("function") {
+                self.pos += 1;
+                let name = self.ident()?;
+                functions.insert(name, self.function()?);
+            } else {
+                globals.push(self.statement()?);
+            }
+        }
+        Ok((functions, globals))
+    }
+
+    fn function(&mut self) -> Result<Function, String> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.eat(")") {
+            params.push(self.ident()?);
+            if !self.is_punct(")") {
+                self.expect(",")?;
+            }
+        }
+        self.expect("{")?;
+        Ok(Function { params, body: self.block()? })
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut body = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return Err("unexpected end of script, expected '}'".to_string());
+            }
+            body.push(self.statement()?);
+        }
+        Ok(body)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        if self.eat("{") {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        if self.eat(";") {
+            return Ok(Stmt::Block(Vec::new()));
+        }
+        if self.is_keyword("var") || self.is_keyword("let") || self.is_keyword("const") {
+            self.pos += 1;
+            let mut decls = Vec::new();
+            loop {
+                let name = self.ident()?;
+                let init = if self.eat("=") { Some(self.expression()?) } else { None };
+                decls.push((name, init));
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.eat(";");
+            return Ok(Stmt::Var(decls));
+        }
+        if self.is_keyword("if") {
+            self.pos += 1;
+            self.expect("(")?;
+            let cond = self.expression()?;
+            self.expect(")")?;
+            let then = Box::new(self.statement()?);
+            let otherwise = if self.is_keyword("else") {
+                self.pos += 1;
+                Some(Box::new(self.statement()?))
+            } else {
+                None
+            };
+            return Ok(Stmt::If(cond, then, otherwise));
+        }
+        if self.is_keyword("return") {
+            self.pos += 1;
+            let value = if self.is_punct(";") || self.is_punct("}") { None } else { Some(self.expression()?) };
+            self.eat(";");
+            return Ok(Stmt::Return(value));
+        }
+        let expr = self.expression()?;
+        self.eat(";");
+        Ok(Stmt::Expr(expr))
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        if let (Some(Token::Ident(name)), Some(Token::Punct("="))) = (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            let name = name.clone();
+            self.pos += 2;
+            return Ok(Expr::Assign(name, Box::new(self.expression()?)));
+        }
+        let cond = self.binary(0)?;
+        if self.eat("?") {
+            let then = self.expression()?;
+            self.expect(":")?;
+            let otherwise = self.expression()?;
+            return Ok(Expr::Ternary(Box::new(cond), Box::new(then), Box::new(otherwise)));
+        }
+        Ok(cond)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[&str]; 5] = [
+            &["||"],
+            &["&&"],
+            &["===", "!==", "==", "!="],
+            &["<=", ">=", "<", ">"],
+            &["+", "-", "%"],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Punct(op)) = self.peek() {
+            let op = *op;
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let mut expr = self.primary()?;
+        while self.eat(".") {
+            let name = self.ident()?;
+            expr = if self.is_punct("(") {
+                Expr::Method(Box::new(expr), name, self.arguments()?)
+            } else {
+                Expr::Property(Box::new(expr), name)
+            };
+        }
+        Ok(expr)
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        while !self.eat(")") {
+            args.push(self.expression()?);
+            if !self.is_punct(")") {
+                self.expect(",")?;
+            }
+        }
+        Ok(args)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Str(s)) => {
+                self.pos += 1;
+                Ok(Expr::Value(Value::Str(s)))
+            }
+            Some(Token::Num(n)) => {
+                self.pos += 1;
+                Ok(Expr::Value(Value::Num(n)))
+            }
+            Some(Token::Punct("(")) => {
+                self.pos += 1;
+                let expr = self.expression()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                match name.as_str() {
+                    "true" => Ok(Expr::Value(Value::Bool(true))),
+                    "false" => Ok(Expr::Value(Value::Bool(false))),
+                    "null" | "undefined" => Ok(Expr::Value(Value::Null)),
+                    _ if self.is_punct("(") => Ok(Expr::Call(name, self.arguments()?)),
+                    _ => Ok(Expr::Var(name)),
+                }
+            }
+            other => Err(format!("unexpected token {:?}", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Num(n) => *n != 0.0 && !n.is_nan(),
+            Value::Bool(b) => *b,
+            Value::Null => false,
+        }
+    }
+
+    fn as_string(&self) -> String {
+        match self {
+            Value::Str(s) => s.clone(),
+            Value::Num(n) if n.fract() == 0.0 && n.is_finite() => format!("{}", *n as i64),
+            Value::Num(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Null => "null".to_string(),
+        }
+    }
+
+    fn as_number(&self) -> f64 {
+        match self {
+            Value::Num(n) => *n,
+            Value::Str(s) => s.trim().parse().unwrap_or(f64::NAN),
+            Value::Bool(b) => *b as u8 as f64,
+            Value::Null => 0.0,
+        }
+    }
+
+    fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Num(_), Value::Str(_)) | (Value::Str(_), Value::Num(_)) => self.as_number() == other.as_number(),
+            _ => self == other,
+        }
+    }
+}
+
+enum Flow {
+    Normal,
+    Return(Value),
+}
+
+/// 解析后的 PAC 脚本
+///
+/// 只实现 PAC 文件常用的 JavaScript 子集：函数、var、if/else、return、三元和逻辑运算、
+/// 字符串方法（toLowerCase、indexOf、substring 等）以及标准的 PAC 辅助函数。
+pub(crate) struct PacScript {
+    functions: HashMap<String, Function>,
+    globals: HashMap<String, Value>,
+    // dnsResolve / isInNet 的解析结果和过期时间，解析失败也缓存（时间较短）
+    dns: Mutex<HashMap<String, (Option<IpAddr>, Instant)>>,
+}
+
+/// dnsResolve / isInNet 使用的解析设置，取自使用该脚本的客户端
+#[derive(Clone, Default)]
+pub(crate) struct PacDns {
+    /// 静态解析，优先于系统解析器
+    pub resolve: Option<BTreeMap<String, SocketAddr>>,
+    /// 单次解析的等待上限，未设置时使用 DNS_TIMEOUT
+    pub timeout: Option<Duration>,
+}
+
+/// 一次脚本求值：脚本和本次使用的解析设置
+struct Eval<'a> {
+    script: &'a PacScript,
+    dns: &'a PacDns,
+}
+
+impl PacScript {
+    pub(crate) fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser { tokens: tokenize(source)?, pos: 0 };
+        let (functions, statements) = parser.program()?;
+        if !functions.contains_key("FindProxyForURL") {
+            return Err("FindProxyForURL is not defined".to_string());
+        }
+        let mut script = PacScript { functions, globals: HashMap::new(), dns: Mutex::new(HashMap::new()) };
+        let mut globals = HashMap::new();
+        let eval = Eval { script: &script, dns: &PacDns::default() };
+        for stmt in &statements {
+            eval.exec(stmt, &mut globals, 0)?;
+        }
+        script.globals = globals;
+        Ok(script)
+    }
+
+    /// 对请求地址执行 FindProxyForURL；与浏览器一致，https 地址只传入 scheme 和主机
+    pub(crate) fn find_proxy(&self, url: &Url, dns: &PacDns) -> Result<String, String> {
+        let host = url.host_str().unwrap_or_default().trim_matches(['[', ']']).to_string();
+        let url = if url.scheme() == "https" || url.scheme() == "wss" {
+            format!("{}://{}/", url.scheme(), url.host_str().unwrap_or_default())
+        } else {
+            url.to_string()
+        };
+        let value = Eval { script: self, dns }.call("FindProxyForURL", vec![Value::Str(url), Value::Str(host)], 0)?;
+        Ok(value.as_string())
+    }
+
+    /// 请求地址对应的代理，DIRECT 或脚本执行失败时直连
+    pub(crate) fn proxy_for(&self, url: &Url, dns: &PacDns) -> Option<Url> {
+        match self.find_proxy(url, dns) {
+            Ok(result) => parse_result(&result),
+            Err(e) => {
+                if debug_enabled() {
+                    write_log(Level::Warning, &format!("PAC evaluation failed for {}: {}, connecting directly", url, e));
+                }
+                None
+            }
+        }
+    }
+
+}
+
+impl Eval<'_> {
+    fn exec(&self, stmt: &Stmt, scope: &mut HashMap<String, Value>, depth: usize) -> Result<Flow, String> {
+        match stmt {
+            Stmt::Var(decls) => {
+                for (name, init) in decls {
+                    let value = match init {
+                        Some(expr) => self.eval(expr, scope, depth)?,
+                        None => Value::Null,
+                    };
+                    scope.insert(name.clone(), value);
+                }
+            }
+            Stmt::Expr(expr) => {
+                self.eval(expr, scope, depth)?;
+            }
+            Stmt::If(cond, then, otherwise) => {
+                if self.eval(cond, scope, depth)?.truthy() {
+                    return self.exec(then, scope, depth);
+                } else if let Some(otherwise) = otherwise {
+                    return self.exec(otherwise, scope, depth);
+                }
+            }
+            Stmt::Return(value) => {
+                let value = match value {
+                    Some(expr) => self.eval(expr, scope, depth)?,
+                    None => Value::Null,
+                };
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Block(body) => {
+                for stmt in body {
+                    if let Flow::Return(value) = self.exec(stmt, scope, depth)? {
+                        return Ok(Flow::Return(value));
+                    }
+                }
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn eval(&self, expr: &Expr, scope: &mut HashMap<String, Value>, depth: usize) -> Result<Value, String> {
+        Ok(match expr {
+            Expr::Value(value) => value.clone(),
+            Expr::Var(name) => scope.get(name).or_else(|| self.script.globals.get(name)).cloned()
+                .ok_or_else(|| format!("{} is not defined", name))?,
+            Expr::Assign(name, value) => {
+                let value = self.eval(value, scope, depth)?;
+                scope.insert(name.clone(), value.clone());
+                value
+            }
+            Expr::Call(name, args) => {
+                let args = args.iter().map(|a| self.eval(a, scope, depth)).collect::<Result<Vec<_>, _>>()?;
+                self.call(name, args, depth + 1)?
+            }
+            Expr::Method(target, name, args) => {
+                let target = self.eval(target, scope, depth)?.as_string();
+                let args = args.iter().map(|a| self.eval(a, scope, depth)).collect::<Result<Vec<_>, _>>()?;
+                string_method(&target, name, &args)?
+            }
+            Expr::Property(target, name) => match name.as_str() {
+                "length" => Value::Num(self.eval(target, scope, depth)?.as_string().chars().count() as f64),
+                _ => return Err(format!("unsupported property '{}'", name)),
+            },
+            Expr::Not(inner) => Value::Bool(!self.eval(inner, scope, depth)?.truthy()),
+            Expr::Neg(inner) => Value::Num(-self.eval(inner, scope, depth)?.as_number()),
+            Expr::Ternary(cond, then, otherwise) => {
+                if self.eval(cond, scope, depth)?.truthy() {
+                    self.eval(then, scope, depth)?
+                } else {
+                    self.eval(otherwise, scope, depth)?
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, scope, depth)?;
+                // 逻辑运算短路
+                match *op {
+                    "||" if left.truthy() => return Ok(left),
+                    "&&" if !left.truthy() => return Ok(left),
+                    "||" | "&&" => return self.eval(right, scope, depth),
+                    _ => {}
+                }
+                let right = self.eval(right, scope, depth)?;
+                match *op {
+                    "==" => Value::Bool(left.loose_eq(&right)),
+                    "!=" => Value::Bool(!left.loose_eq(&right)),
+                    "===" => Value::Bool(left == right),
+                    "!==" => Value::Bool(left != right),
+                    "+" => match (&left, &right) {
+                        (Value::Str(_), _) | (_, Value::Str(_)) => Value::Str(left.as_string() + &right.as_string()),
+                        _ => Value::Num(left.as_number() + right.as_number()),
+                    },
+                    "-" => Value::Num(left.as_number() - right.as_number()),
+                    "%" => Value::Num(left.as_number() % right.as_number()),
+                    cmp => {
+                        let ordering = match (&left, &right) {
+                            (Value::Str(a), Value::Str(b)) => a.partial_cmp(b),
+                            _ => left.as_number().partial_cmp(&right.as_number()),
+                        };
+                        Value::Bool(match (cmp, ordering) {
+                            (_, None) => false,
+                            ("<", Some(o)) => o.is_lt(),
+                            (">", Some(o)) => o.is_gt(),
+                            ("<=", Some(o)) => o.is_le(),
+                            (_, Some(o)) => o.is_ge(),
+                        })
+                    }
+                }
+            }
+        })
+    }
+
+    fn call(&self, name: &str, args: Vec<Value>, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("maximum call depth exceeded".to_string());
+        }
+        if let Some(function) = self.script.functions.get(name) {
+            let mut scope: HashMap<String, Value> = function.params.iter().cloned()
+                .zip(args.into_iter().chain(std::iter::repeat(Value::Null)))
+                .collect();
+            for stmt in &function.body {
+                if let Flow::Return(value) = self.exec(stmt, &mut scope, depth)? {
+                    return Ok(value);
+                }
+            }
+            return Ok(Value::Null);
+        }
+
+        let arg = |i: usize| args.get(i).map(|v| v.as_string()).unwrap_or_default();
+        Ok(match name {
+            "isPlainHostName" => Value::Bool(!arg(0).contains('.')),
+            "dnsDomainIs" => Value::Bool(arg(0).to_lowercase().ends_with(&arg(1).to_lowercase())),
+            "localHostOrDomainIs" => {
+                let (host, full) = (arg(0).to_lowercase(), arg(1).to_lowercase());
+                Value::Bool(host == full || (!host.contains('.') && full.starts_with(&format!("{}.", host))))
+            }
+            "dnsDomainLevels" => Value::Num(arg(0).matches('.').count() as f64),
+            "shExpMatch" => Value::Bool(glob_match(&arg(0), &arg(1))),
+            "isResolvable" => Value::Bool(self.resolve(&arg(0)).is_some()),
+            "dnsResolve" => self.resolve(&arg(0)).map(|ip| Value::Str(ip.to_string())).unwrap_or(Value::Null),
+            "myIpAddress" => Value::Str(my_ip_address().to_string()),
+            "isInNet" => {
+                let (Ok(pattern), Ok(mask)) = (arg(1).parse::<Ipv4Addr>(), arg(2).parse::<Ipv4Addr>()) else {
+                    return Ok(Value::Bool(false));
+                };
+                let mask = u32::from(mask);
+                Value::Bool(match self.resolve(&arg(0)) {
+                    Some(IpAddr::V4(ip)) => u32::from(ip) & mask == u32::from(pattern) & mask,
+                    _ => false,
+                })
+            }
+            "convert_addr" => Value::Num(arg(0).parse::<Ipv4Addr>().map(|ip| u32::from(ip) as f64).unwrap_or(0.0)),
+            "alert" => Value::Null,
+            _ => return Err(format!("unsupported function '{}'", name)),
+        })
+    }
+
+    /// 解析主机名：静态解析、DNS 缓存、脚本内缓存，最后在锁外查询系统解析器
+    fn resolve(&self, host: &str) -> Option<IpAddr> {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Some(ip);
+        }
+        let host = host.to_lowercase();
+        if let Some(addr) = self.dns.resolve.as_ref().and_then(|resolve| resolve.get(&host)) {
+            return Some(addr.ip());
+        }
+        if let Some(addrs) = dns::cached(&host) {
+            return preferred(&addrs);
+        }
+        if let Some((ip, expires)) = self.script.dns.lock().unwrap().get(&host) {
+            if *expires > Instant::now() {
+                return *ip;
+            }
+        }
+        let ip = lookup(&host, self.dns.timeout.unwrap_or(DNS_TIMEOUT));
+        let ttl = match ip {
+            Some(_) => dns::cache_ttl().unwrap_or(DNS_TTL),
+            None => DNS_NEGATIVE_TTL,
+        };
+        self.script.dns.lock().unwrap().insert(host, (ip, Instant::now() + ttl));
+        ip
+    }
+}
+
+/// PAC 的 dnsResolve 返回 IPv4 地址，没有时取第一个地址
+fn preferred(addrs: &[SocketAddr]) -> Option<IpAddr> {
+    addrs.iter().map(|a| a.ip()).find(IpAddr::is_ipv4).or(addrs.first().map(|a| a.ip()))
+}
+
+/// 在独立线程中查询系统解析器，最多等待 timeout
+///
+/// PAC 在 reqwest 的同步代理回调中求值，无法 await；多线程运行时下用 block_in_place 让出工作线程。
+fn lookup(host: &str, timeout: Duration) -> Option<IpAddr> {
+    let (tx, rx) = mpsc::channel();
+    let name = host.to_string();
+    std::thread::spawn(move || {
+        let _ = tx.send((name.as_str(), 0).to_socket_addrs().map(|addrs| addrs.collect::<Vec<_>>()));
+    });
+    let wait = || rx.recv_timeout(timeout).ok()?.ok();
+    let multi_thread = Handle::try_current().is_ok_and(|h| h.runtime_flavor() == RuntimeFlavor::MultiThread);
+    let addrs = if multi_thread { tokio::task::block_in_place(wait) } else { wait() }?;
+    preferred(&addrs)
+}
+
+fn string_method(target: &str, name: &str, args: &[Value]) -> Result<Value, String> {
+    let arg = |i: usize| args.get(i).map(|v| v.as_string()).unwrap_or_default();
+    let chars: Vec<char> = target.chars().collect();
+    let index = |i: usize, default: usize| {
+        args.get(i).map(|v| v.as_number()).filter(|n| !n.is_nan())
+            .map(|n| (n.max(0.0) as usize).min(chars.len()))
+            .unwrap_or(default)
+    };
+    Ok(match name {
+        "toLowerCase" => Value::Str(target.to_lowercase()),
+        "toUpperCase" => Value::Str(target.to_uppercase()),
+        "indexOf" => Value::Num(match target.find(&arg(0)) {
+            Some(byte) => target[..byte].chars().count() as f64,
+            None => -1.0,
+        }),
+        "startsWith" => Value::Bool(target.starts_with(&arg(0))),
+        "endsWith" => Value::Bool(target.ends_with(&arg(0))),
+        "substring" => {
+            let (a, b) = (index(0, 0), index(1, chars.len()));
+            Value::Str(chars[a.min(b)..a.max(b)].iter().collect())
+        }
+        "charAt" => Value::Str(chars.get(index(0, 0)).map(|c| c.to_string()).unwrap_or_default()),
+        _ => return Err(format!("unsupported method '{}'", name)),
+    })
+}
+
+/// shExpMatch 的通配符匹配，* 匹配任意字符串，? 匹配单个字符
+fn glob_match(text: &str, pattern: &str) -> bool {
+    let (text, pattern): (Vec<char>, Vec<char>) = (text.chars().collect(), pattern.chars().collect());
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            t += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 本机出口地址：向公网地址"连接"一个 UDP socket（不发送数据）后读取本地地址
+fn my_ip_address() -> IpAddr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| socket.connect("8.8.8.8:80").map(|_| socket))
+        .and_then(|socket| socket.local_addr())
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        var corp = "corp.example.com"; /* 全局变量 */
+        function FindProxyForURL(url, host) {
+            if (isPlainHostName(host) || dnsDomainIs(host, ".local")) return "DIRECT";
+            if (shExpMatch(url, "https://*")) return "HTTPS secure:443";
+            if (isInNet(host, "10.0.0.0", "255.0.0.0")) return "SOCKS socks:1080; DIRECT";
+            if (host.toLowerCase() == corp) return 'PROXY ' + "corp:" + (8000 + 80);
+            return url.substring(0, 7) === "http://" ? "PROXY default:3128" : "DIRECT";
+        }
+    "#;
+
+    fn find(script: &PacScript, url: &str, dns: &PacDns) -> String {
+        script.find_proxy(&Url::parse(url).unwrap(), dns).unwrap()
+    }
+
+    #[test]
+    fn tokenize_literals_and_comments() {
+        let tokens = tokenize("if (a === 'x\\'y') return 1.5; // 注释\n/* 块 */ b").unwrap();
+        assert_eq!(tokens, vec![
+            Token::Ident("if".to_string()),
+            Token::Punct("("),
+            Token::Ident("a".to_string()),
+            Token::Punct("==="),
+            Token::Str("x'y".to_string()),
+            Token::Punct(")"),
+            Token::Ident("return".to_string()),
+            Token::Num(1.5),
+            Token::Punct(";"),
+            Token::Ident("b".to_string()),
+        ]);
+        assert!(tokenize("var s = \"open").is_err());
+        assert!(tokenize("a # b").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(PacScript::parse("function f() { return 1; }").err().as_deref(), Some("FindProxyForURL is not defined"));
+        assert!(PacScript::parse("function FindProxyForURL(url, host) { return ; ").is_err());
+        assert!(PacScript::parse("function FindProxyForURL(url, host) { return (1 + ; }").is_err());
+    }
+
+    #[test]
+    fn find_proxy_for_url() {
+        let script = PacScript::parse(SCRIPT).unwrap();
+        let dns = PacDns {
+            resolve: Some(BTreeMap::from([("db.internal".to_string(), "10.1.2.3:0".parse().unwrap())])),
+            timeout: None,
+        };
+        assert_eq!(find(&script, "http://intranet/", &dns), "DIRECT");
+        assert_eq!(find(&script, "http://printer.local/", &dns), "DIRECT");
+        // https 地址只传入 scheme 和主机
+        assert_eq!(find(&script, "https://example.com/path?q=1", &dns), "HTTPS secure:443");
+        assert_eq!(find(&script, "http://db.internal/", &dns), "SOCKS socks:1080; DIRECT");
+        assert_eq!(find(&script, "http://10.9.9.9/", &dns), "SOCKS socks:1080; DIRECT");
+        assert_eq!(find(&script, "http://CORP.example.com/", &dns), "PROXY corp:8080");
+        assert_eq!(find(&script, "http://192.168.1.1/", &dns), "PROXY default:3128");
+        assert_eq!(script.proxy_for(&Url::parse("http://intranet/").unwrap(), &dns), None);
+        assert_eq!(script.proxy_for(&Url::parse("http://db.internal/").unwrap(), &dns).unwrap().as_str(), "socks5://socks:1080");
+    }
+
+    #[test]
+    fn recursion_is_bounded() {
+        let script = PacScript::parse("function FindProxyForURL(url, host) { return FindProxyForURL(url, host); }").unwrap();
+        assert!(script.find_proxy(&Url::parse("http://a/").unwrap(), &PacDns::default()).is_err());
+    }
+
+    #[test]
+    fn parse_result_entries() {
+        assert_eq!(parse_result("DIRECT; PROXY a:1"), None);
+        assert_eq!(parse_result("SOCKS4 old:1080; PROXY a:8080").unwrap().as_str(), "http://a:8080/");
+        assert_eq!(parse_result("SOCKS5 s:1080").unwrap().as_str(), "socks5://s:1080");
+        assert_eq!(parse_result(""), None);
+    }
+
+    #[test]
+    fn failed_lookups_expire() {
+        let script = PacScript::parse("function FindProxyForURL(url, host) { return dnsResolve(host); }").unwrap();
+        let dns = PacDns { resolve: None, timeout: Some(Duration::from_millis(500)) };
+        assert_eq!(find(&script, "http://no-such-host.invalid/", &dns), "null");
+        let cache = script.dns.lock().unwrap();
+        let (ip, expires) = cache.get("no-such-host.invalid").unwrap();
+        assert_eq!(*ip, None);
+        assert!(*expires <= Instant::now() + DNS_NEGATIVE_TTL);
+    }
+}
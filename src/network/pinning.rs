@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::SystemTime;
+use base64::Engine;
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, CertificateError, RootCertStore, ServerName};
+use sha2::{Digest, Sha256};
+
+const PIN_PREFIX: &str = "sha256/";
+
+/// 解析 "sha256/<base64>" 格式的 SPKI 指纹
+pub(crate) fn parse_pin(pin: &str) -> Result<Vec<u8>, String> {
+    let encoded = pin.trim().strip_prefix(PIN_PREFIX)
+        .ok_or_else(|| format!("Invalid pin '{}': expected 'sha256/<base64>'", pin))?;
+    let hash = base64::engine::general_purpose::STANDARD.decode(encoded)
+        .map_err(|e| format!("Invalid pin '{}': {}", pin, e))?;
+    if hash.len() != 32 {
+        return Err(format!("Invalid pin '{}': expected a 32-byte SHA-256 hash", pin));
+    }
+    Ok(hash)
+}
+
+/// 计算证书公钥（SubjectPublicKeyInfo）的 SHA-256
+fn spki_sha256(cert_der: &[u8]) -> Result<Vec<u8>, String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| format!("Failed to parse peer certificate: {}", e))?;
+    Ok(Sha256::digest(cert.tbs_certificate.subject_pki.raw).to_vec())
+}
+
+/// 服务端证书与指纹不匹配，作为握手错误的来源返回
+#[derive(Debug)]
+pub(crate) struct PinMismatch(String);
+
+impl fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for PinMismatch {}
+
+/// 在 TLS 握手中校验证书：先按 ssl_verify 做常规链校验，再检查配置了指纹的主机（没有指纹时只做常规校验）
+///
+/// 校验失败时握手中止，请求头和请求体不会发送；重定向的每一跳都会重新握手校验。
+pub(crate) struct PinningVerifier {
+    // ssl_verify=False 时为 None，只检查指纹
+    inner: Option<WebPkiVerifier>,
+    pins: BTreeMap<String, Vec<Vec<u8>>>,
+}
+
+impl PinningVerifier {
+    pub(crate) fn new(roots: Option<RootCertStore>, pins: &BTreeMap<String, Vec<String>>) -> Self {
+        let pins = pins.iter()
+            .map(|(host, pins)| (host.clone(), pins.iter().filter_map(|p| parse_pin(p).ok()).collect()))
+            .collect();
+        PinningVerifier { inner: roots.map(|roots| WebPkiVerifier::new(roots, None)), pins }
+    }
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Some(inner) = &self.inner {
+            inner.verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)?;
+        }
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_lowercase(),
+            ServerName::IpAddress(ip) => ip.to_string(),
+            _ => return Ok(ServerCertVerified::assertion()),
+        };
+        let Some(expected) = self.pins.get(&host) else { return Ok(ServerCertVerified::assertion()) };
+        let actual = spki_sha256(&end_entity.0)
+            .map_err(|e| rustls::Error::InvalidCertificate(CertificateError::Other(Arc::new(PinMismatch(e)))))?;
+        if expected.contains(&actual) {
+            return Ok(ServerCertVerified::assertion());
+        }
+        let message = format!(
+            "Certificate for {} does not match any pin (got {}{})",
+            host,
+            PIN_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(actual)
+        );
+        Err(rustls::Error::InvalidCertificate(CertificateError::Other(Arc::new(PinMismatch(message)))))
+    }
+}
+
+/// 在请求错误链中查找指纹不匹配
+///
+/// 握手错误被包装在（可能多层的）io::Error 中，io::Error::source 会跳过被包装的错误，需要用 get_ref 取出。
+pub(crate) fn find_pin_mismatch<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a PinMismatch> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(rustls::Error::InvalidCertificate(CertificateError::Other(other))) = e.downcast_ref::<rustls::Error>() {
+            if let Some(mismatch) = other.downcast_ref::<PinMismatch>() {
+                return Some(mismatch);
+            }
+        }
+        current = match e.downcast_ref::<io::Error>().and_then(io::Error::get_ref) {
+            Some(inner) => Some(inner as &(dyn Error + 'static)),
+            None => e.source(),
+        };
+    }
+    None
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use crate::GLOBAL_CLIENT_CONFIG;
+
+// 与 reqwest 连接池的默认空闲超时一致
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// 单个主机（host:port）的连接使用情况
+///
+/// reqwest 不暴露连接池内部状态，这里根据每个请求是否新建了连接（是否经过解析器）推算：
+/// 请求结束后连接视为空闲，超过 pool_idle_timeout 或 pool_max_idle_per_host 的空闲连接视为已关闭。
+/// 服务端主动关闭的连接无法观察到，open / idle / in_use 只是估计值。
+#[derive(Default)]
+struct HostPool {
+    requests: u64,
+    new_connections: u64,
+    reused: u64,
+    in_use: usize,
+    // 每个空闲连接开始空闲的时间，最早的在前
+    idle: VecDeque<Instant>,
+}
+
+static POOLS: Lazy<Mutex<HashMap<String, HostPool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn pool_limits() -> (Duration, usize) {
+    let global = GLOBAL_CLIENT_CONFIG.read().unwrap();
+    (
+        global.pool_idle_timeout.map(Duration::from_secs_f64).unwrap_or(DEFAULT_IDLE_TIMEOUT),
+        global.pool_max_idle_per_host.unwrap_or(usize::MAX),
+    )
+}
+
+impl HostPool {
+    fn expire(&mut self, now: Instant, idle_timeout: Duration) {
+        while self.idle.front().is_some_and(|since| now.duration_since(*since) >= idle_timeout) {
+            self.idle.pop_front();
+        }
+    }
+}
+
+/// 请求占用的连接，drop 时连接回到空闲状态
+pub(crate) struct ConnectionUse {
+    host: String,
+    // HTTP/2 多路复用时复用的是正在使用的连接，不单独占用
+    holds: bool,
+}
+
+/// 记录一次收到响应的请求；new_connection 表示本次请求新建了连接
+pub(crate) fn record(host: &str, new_connection: bool) -> ConnectionUse {
+    let (idle_timeout, _) = pool_limits();
+    let now = Instant::now();
+    let mut pools = POOLS.lock().unwrap();
+    let pool = pools.entry(host.to_string()).or_default();
+    pool.expire(now, idle_timeout);
+    pool.requests += 1;
+    let holds = if new_connection {
+        pool.new_connections += 1;
+        true
+    } else {
+        pool.reused += 1;
+        // 最近空闲的连接最先被复用
+        pool.idle.pop_back().is_some()
+    };
+    if holds {
+        pool.in_use += 1;
+    }
+    ConnectionUse { host: host.to_string(), holds }
+}
+
+impl Drop for ConnectionUse {
+    fn drop(&mut self) {
+        if !self.holds {
+            return;
+        }
+        let (_, max_idle) = pool_limits();
+        let mut pools = POOLS.lock().unwrap();
+        if let Some(pool) = pools.get_mut(&self.host) {
+            pool.in_use = pool.in_use.saturating_sub(1);
+            pool.idle.push_back(Instant::now());
+            while pool.idle.len() > max_idle {
+                pool.idle.pop_front();
+            }
+        }
+    }
+}
+
+/// 关闭空闲连接后清除对应的空闲计数；host 为 None 时清除所有主机
+pub(crate) fn forget_idle(host: Option<&str>) {
+    let mut pools = POOLS.lock().unwrap();
+    for (key, pool) in pools.iter_mut() {
+        let name = key.rsplit_once(':').map_or(key.as_str(), |(name, _)| name);
+        if host.is_none() || host == Some(name) {
+            pool.idle.clear();
+        }
+    }
+}
+
+/// 每个主机（host:port）的连接池统计
+///
+/// open / idle / in_use 为估计值；requests、new_connections、reused 为进程启动以来的累计值。
+/// 以 IP 地址访问的主机无法判断是否新建连接，不在统计中；模拟传输、缓存命中和配置了代理的请求也不计入。
+#[pyfunction]
+pub fn get_pool_stats(py: Python) -> PyResult<PyObject> {
+    let (idle_timeout, _) = pool_limits();
+    let now = Instant::now();
+    let mut pools = POOLS.lock().unwrap();
+    let stats = PyDict::new(py);
+    for (host, pool) in pools.iter_mut() {
+        pool.expire(now, idle_timeout);
+        let entry = PyDict::new(py);
+        entry.set_item("open", pool.in_use + pool.idle.len())?;
+        entry.set_item("idle", pool.idle.len())?;
+        entry.set_item("in_use", pool.in_use)?;
+        entry.set_item("requests", pool.requests)?;
+        entry.set_item("new_connections", pool.new_connections)?;
+        entry.set_item("reused", pool.reused)?;
+        stats.set_item(host, entry)?;
+    }
+    Ok(stats.into_py(py))
+}
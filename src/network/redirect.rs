@@ -0,0 +1,51 @@
+use std::cell::RefCell;
+use std::future::Future;
+use reqwest::redirect;
+use serde_json::Value;
+
+/// 一次重定向：返回 3xx 的地址及其状态码
+#[derive(Clone, Debug)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+}
+
+impl RedirectHop {
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({ "url": self.url, "status": self.status })
+    }
+}
+
+tokio::task_local! {
+    // 当前请求的重定向记录，由 track_redirects 设置作用域
+    static REDIRECT_CHAIN: RefCell<Vec<RedirectHop>>;
+}
+
+/// 构建重定向策略，跟随的每一跳都会记录到当前任务的重定向链中
+pub fn build_policy(allow_redirects: bool, max_redirects: usize) -> redirect::Policy {
+    if !allow_redirects {
+        return redirect::Policy::none();
+    }
+
+    redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= max_redirects {
+            return attempt.error("too many redirects");
+        }
+        if let Some(from) = attempt.previous().last() {
+            let hop = RedirectHop { url: from.to_string(), status: attempt.status().as_u16() };
+            let _ = REDIRECT_CHAIN.try_with(|chain| chain.borrow_mut().push(hop));
+        }
+        attempt.follow()
+    })
+}
+
+/// 执行 future 并收集期间发生的重定向
+pub async fn track_redirects<F: Future>(fut: F) -> (F::Output, Vec<RedirectHop>) {
+    REDIRECT_CHAIN
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = fut.await;
+            let chain = REDIRECT_CHAIN.with(|chain| chain.take());
+            (output, chain)
+        })
+        .await
+}
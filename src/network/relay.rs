@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use url::Url;
+use crate::debug::{debug_enabled, write_log, Level};
+use crate::network::tunnel::{connect_via, TUNNEL_SCHEMES};
+
+/// 本地中转端口背后的实际连接方式
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Upstream {
+    /// 依次经过多个代理
+    Chain(Vec<String>),
+    /// Unix domain socket 路径
+    Unix(String),
+}
+
+// 每个上游对应一个本地中转端口，相同的上游复用
+static RELAYS: Lazy<Mutex<HashMap<Upstream, SocketAddr>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 校验代理链中的地址
+pub(crate) fn parse_chain(chain: &[String]) -> Result<Vec<Url>, String> {
+    if chain.len() < 2 {
+        return Err("proxy chain needs at least two proxies".to_string());
+    }
+    chain.iter().map(|raw| {
+        let url = Url::parse(raw).map_err(|e| format!("Invalid proxy URL '{}': {}", raw, e))?;
+        if !TUNNEL_SCHEMES.contains(&url.scheme()) || url.host_str().is_none() {
+            return Err(format!(
+                "Invalid chained proxy '{}': expected http://, socks5:// or socks5h:// with a host", raw
+            ));
+        }
+        Ok(url)
+    }).collect()
+}
+
+/// 返回上游对应的本地 SOCKS5 中转地址，reqwest 把它当作普通代理使用
+///
+/// reqwest 不支持自定义连接器，因此在本地监听一个回环地址上的 SOCKS5 端口，
+/// 每个连接按上游建立（代理链的嵌套隧道或 Unix socket）后双向转发。
+pub(crate) async fn relay_for(upstream: Upstream) -> Result<String, String> {
+    let mut relays = RELAYS.lock().await;
+    if let Some(addr) = relays.get(&upstream) {
+        return Ok(format!("socks5h://{}", addr));
+    }
+    if let Upstream::Chain(chain) = &upstream {
+        parse_chain(chain)?;
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await
+        .map_err(|e| format!("Failed to start proxy chain relay: {}", e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let target = upstream.clone();
+    tokio::spawn(async move {
+        while let Ok((inbound, _)) = listener.accept().await {
+            let target = target.clone();
+            tokio::spawn(async move {
+                // 失败原因只在调试模式下输出，reqwest 侧只能看到通用的连接错误
+                if let Err(e) = serve(inbound, &target).await {
+                    if debug_enabled() {
+                        write_log(Level::Warning, &format!("Local relay error: {}", e));
+                    }
+                }
+            });
+        }
+    });
+    relays.insert(upstream, addr);
+    Ok(format!("socks5h://{}", addr))
+}
+
+/// 校验 RequestItem / fetch_single 的 unix_socket 参数
+pub(crate) fn check_unix_socket(path: &str) -> PyResult<()> {
+    if !cfg!(unix) {
+        return Err(PyValueError::new_err("unix_socket is only supported on Unix platforms"));
+    }
+    if path.trim().is_empty() {
+        return Err(PyValueError::new_err("unix_socket must not be empty"));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn connect_unix(path: &str) -> io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(_path: &str) -> io::Result<TcpStream> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "unix_socket is only supported on Unix platforms"))
+}
+
+/// 处理一个来自 reqwest 的 SOCKS5 连接（无认证，只支持 CONNECT）
+async fn serve(mut inbound: TcpStream, upstream: &Upstream) -> io::Result<()> {
+    let mut head = [0u8; 2];
+    inbound.read_exact(&mut head).await?;
+    let mut methods = vec![0u8; head[1] as usize];
+    inbound.read_exact(&mut methods).await?;
+    inbound.write_all(&[0x05, 0x00]).await?;
+
+    let mut request = [0u8; 4];
+    inbound.read_exact(&mut request).await?;
+    if request[1] != 0x01 {
+        // 不支持的命令
+        inbound.write_all(&[0x05, 0x07, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+        return Ok(());
+    }
+    let host = match request[3] {
+        0x01 => {
+            let mut ip = [0u8; 4];
+            inbound.read_exact(&mut ip).await?;
+            std::net::Ipv4Addr::from(ip).to_string()
+        }
+        0x04 => {
+            let mut ip = [0u8; 16];
+            inbound.read_exact(&mut ip).await?;
+            std::net::Ipv6Addr::from(ip).to_string()
+        }
+        0x03 => {
+            let len = inbound.read_u8().await? as usize;
+            let mut name = vec![0u8; len];
+            inbound.read_exact(&mut name).await?;
+            String::from_utf8_lossy(&name).to_string()
+        }
+        _ => {
+            inbound.write_all(&[0x05, 0x08, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+            return Ok(());
+        }
+    };
+    let port = inbound.read_u16().await?;
+
+    // Unix socket 忽略请求的目标地址，HTTP 请求中的 Host 仍来自 URL
+    let connected = match upstream {
+        Upstream::Chain(chain) => match parse_chain(chain) {
+            Ok(hops) => connect_via(&hops, &host, port).await.map(Outbound::Tcp),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+        },
+        Upstream::Unix(path) => connect_unix(path).await.map(Outbound::Unix),
+    };
+    let outbound = match connected {
+        Ok(stream) => stream,
+        Err(e) => {
+            // 通用失败，reqwest 会返回连接错误
+            inbound.write_all(&[0x05, 0x01, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+            return Err(e);
+        }
+    };
+    inbound.write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+    match outbound {
+        Outbound::Tcp(mut stream) => tokio::io::copy_bidirectional(&mut inbound, &mut stream).await?,
+        Outbound::Unix(mut stream) => tokio::io::copy_bidirectional(&mut inbound, &mut stream).await?,
+    };
+    Ok(())
+}
+
+// Unix socket 的类型随平台不同
+enum Outbound<U> {
+    Tcp(TcpStream),
+    Unix(U),
+}
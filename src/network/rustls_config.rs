@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::sync::Arc;
+use once_cell::sync::Lazy;
+use rustls::{Certificate, ClientConfig, RootCertStore, SupportedProtocolVersion};
+use crate::network::client::ClientOptions;
+use crate::network::pinning::PinningVerifier;
+use crate::network::TlsVersion;
+
+// 系统根证书只加载一次，加载失败的证书跳过
+static NATIVE_ROOTS: Lazy<Vec<Certificate>> = Lazy::new(|| {
+    rustls_native_certs::load_native_certs()
+        .map(|certs| certs.into_iter().map(|c| Certificate(c.0)).collect())
+        .unwrap_or_default()
+});
+
+/// 按 tls_min_version / tls_max_version 选择协议版本，rustls 只支持 TLS 1.2 和 1.3
+fn protocol_versions(options: &ClientOptions) -> Result<Vec<&'static SupportedProtocolVersion>, String> {
+    let min = options.tls_min_version.unwrap_or(TlsVersion::Tls1_2);
+    let max = options.tls_max_version.unwrap_or(TlsVersion::Tls1_3);
+    if max < TlsVersion::Tls1_2 {
+        return Err("tls_max_version below TLS1_2 is not supported with cert_pins or impersonate".to_string());
+    }
+    let mut versions = Vec::new();
+    if min <= TlsVersion::Tls1_2 {
+        versions.push(&rustls::version::TLS12);
+    }
+    if max == TlsVersion::Tls1_3 {
+        versions.push(&rustls::version::TLS13);
+    }
+    Ok(versions)
+}
+
+fn root_store(options: &ClientOptions) -> Result<RootCertStore, String> {
+    let mut roots = RootCertStore::empty();
+    for cert in NATIVE_ROOTS.iter() {
+        // 个别系统证书无法解析时忽略
+        let _ = roots.add(cert);
+    }
+    if let Some(ca_bundle) = &options.ca_bundle {
+        let certs = rustls_pemfile::certs(&mut Cursor::new(ca_bundle.pem()))
+            .map_err(|e| format!("Invalid CA bundle: {}", e))?;
+        for cert in certs {
+            roots.add(&Certificate(cert)).map_err(|e| format!("Invalid CA bundle: {}", e))?;
+        }
+    }
+    Ok(roots)
+}
+
+/// 需要定制握手时（证书指纹、模拟浏览器 ClientHello）使用的 rustls 配置
+///
+/// reqwest 原样使用预先构建的配置，根证书、客户端证书、TLS 版本和 ALPN 都需要在这里设置。
+pub(crate) fn build(options: &ClientOptions) -> Result<ClientConfig, String> {
+    let roots = if options.ssl_verify { Some(root_store(options)?) } else { None };
+    let verifier = Arc::new(PinningVerifier::new(roots, options.cert_pins.as_ref().unwrap_or(&BTreeMap::new())));
+
+    let (suites, groups) = match options.impersonate {
+        Some(profile) => (profile.cipher_suites(), profile.kx_groups()),
+        None => (rustls::DEFAULT_CIPHER_SUITES.to_vec(), rustls::ALL_KX_GROUPS.to_vec()),
+    };
+    let builder = ClientConfig::builder()
+        .with_cipher_suites(&suites)
+        .with_kx_groups(&groups)
+        .with_protocol_versions(&protocol_versions(options)?)
+        .map_err(|e| format!("Invalid TLS version range: {}", e))?
+        .with_custom_certificate_verifier(verifier);
+
+    let mut config = match &options.identity {
+        Some(identity) => {
+            let (certs, key) = identity.to_rustls()?;
+            builder.with_client_auth_cert(certs, key).map_err(|e| format!("Invalid client identity: {}", e))?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = options.http_version.alpn_protocols();
+    Ok(config)
+}
@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::io;
+
+/// 在错误链中查找带操作系统错误码的 IO 错误
+pub(crate) fn find_os_error<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a io::Error> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(io_err) = e.downcast_ref::<io::Error>().filter(|e| e.raw_os_error().is_some()) {
+            return Some(io_err);
+        }
+        current = e.source();
+    }
+    None
+}
+
+/// 常见网络错误码的符号名，用于区分防火墙丢包、主机不可达和端口未监听
+#[cfg(unix)]
+pub(crate) fn errno_name(errno: i32) -> Option<&'static str> {
+    let name = match errno {
+        libc::ECONNREFUSED => "ECONNREFUSED",
+        libc::ECONNRESET => "ECONNRESET",
+        libc::ECONNABORTED => "ECONNABORTED",
+        libc::ETIMEDOUT => "ETIMEDOUT",
+        libc::EHOSTUNREACH => "EHOSTUNREACH",
+        libc::EHOSTDOWN => "EHOSTDOWN",
+        libc::ENETUNREACH => "ENETUNREACH",
+        libc::ENETDOWN => "ENETDOWN",
+        libc::EADDRNOTAVAIL => "EADDRNOTAVAIL",
+        libc::EADDRINUSE => "EADDRINUSE",
+        libc::EPIPE => "EPIPE",
+        libc::EACCES => "EACCES",
+        libc::EPERM => "EPERM",
+        libc::EMFILE => "EMFILE",
+        libc::ENFILE => "ENFILE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn errno_name(_errno: i32) -> Option<&'static str> {
+    None
+}
@@ -1,4 +1,4 @@
-use pyo3::{pyclass, pymethods};
+use pyo3::{pyclass, pymethods, FromPyObject};
 
 #[pyclass]
 #[derive(Clone, Debug)]
@@ -7,6 +7,7 @@ pub struct SslVerify(bool);
 #[pymethods]
 impl SslVerify {
     #[new]
+    #[pyo3(signature = (verify=true))]
     fn new(verify: bool) -> Self {
         SslVerify(verify)
     }
@@ -15,4 +16,24 @@ impl SslVerify {
     pub fn get(&self) -> bool {
         self.0
     }
-}
\ No newline at end of file
+
+    fn __repr__(&self) -> String {
+        format!("SslVerify({})", if self.0 { "True" } else { "False" })
+    }
+}
+
+/// ssl_verify 参数既可以传 bool，也可以传 SslVerify
+#[derive(FromPyObject)]
+pub enum SslVerifyArg {
+    Flag(bool),
+    Config(SslVerify),
+}
+
+impl SslVerifyArg {
+    pub fn get(&self) -> bool {
+        match self {
+            SslVerifyArg::Flag(verify) => *verify,
+            SslVerifyArg::Config(config) => config.get(),
+        }
+    }
+}
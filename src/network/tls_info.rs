@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use reqwest::tls::TlsInfo;
+use reqwest::Response;
+use serde_json::{json, Value};
+use x509_parser::extensions::GeneralName;
+
+fn format_timestamp(timestamp: i64) -> Value {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|t| Value::String(t.to_rfc3339()))
+        .unwrap_or(Value::Null)
+}
+
+/// 服务端证书摘要：subject、issuer、有效期、剩余天数、SAN 和序列号
+///
+/// native-tls 不提供协商的 TLS 版本和加密套件，这里只包含证书信息。
+pub(crate) fn tls_summary(res: &Response) -> Option<Value> {
+    let der = res.extensions().get::<TlsInfo>()?.peer_certificate()?;
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+
+    let validity = cert.validity();
+    let not_after = validity.not_after.timestamp();
+    let days_remaining = (not_after - Utc::now().timestamp()) / 86_400;
+    let dns_names: Vec<Value> = cert.subject_alternative_name().ok().flatten()
+        .map(|san| san.value.general_names.iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(Value::String(dns.to_string())),
+                _ => None,
+            })
+            .collect())
+        .unwrap_or_default();
+
+    Some(json!({
+        "peer_certificate": {
+            "subject": cert.subject().to_string(),
+            "issuer": cert.issuer().to_string(),
+            "not_before": format_timestamp(validity.not_before.timestamp()),
+            "not_after": format_timestamp(not_after),
+            "days_remaining": days_remaining,
+            "serial": cert.raw_serial_as_string(),
+            "dns_names": dns_names,
+        }
+    }))
+}
@@ -0,0 +1,93 @@
+// src/tls_version.rs
+use pyo3::{pyclass, pymethods, PyResult};
+use pyo3::exceptions::PyValueError;
+use reqwest::tls::Version;
+
+#[pyclass]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum TlsVersion {
+    #[pyo3(name = "TLS1_0")]
+    Tls1_0,
+    #[pyo3(name = "TLS1_1")]
+    Tls1_1,
+    #[pyo3(name = "TLS1_2")]
+    Tls1_2,
+    #[pyo3(name = "TLS1_3")]
+    Tls1_3,
+}
+
+#[pymethods]
+impl TlsVersion {
+    // 类属性常量
+    #[classattr]
+    const TLS1_0: TlsVersion = TlsVersion::Tls1_0;
+
+    #[classattr]
+    const TLS1_1: TlsVersion = TlsVersion::Tls1_1;
+
+    #[classattr]
+    const TLS1_2: TlsVersion = TlsVersion::Tls1_2;
+
+    #[classattr]
+    const TLS1_3: TlsVersion = TlsVersion::Tls1_3;
+
+    fn __str__(&self) -> &'static str {
+        match self {
+            TlsVersion::Tls1_0 => "TLS1_0",
+            TlsVersion::Tls1_1 => "TLS1_1",
+            TlsVersion::Tls1_2 => "TLS1_2",
+            TlsVersion::Tls1_3 => "TLS1_3",
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TlsVersion.{}", self.__str__())
+    }
+
+    // 从字符串创建，如 "1.2" 或 "TLS1_2"
+    #[staticmethod]
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s.to_uppercase().replace(['.', '_', 'V'], "").as_str() {
+            "10" | "TLS10" => Ok(TlsVersion::Tls1_0),
+            "11" | "TLS11" => Ok(TlsVersion::Tls1_1),
+            "12" | "TLS12" => Ok(TlsVersion::Tls1_2),
+            "13" | "TLS13" => Ok(TlsVersion::Tls1_3),
+            _ => Err(PyValueError::new_err(
+                format!("Invalid TLS version: '{}'. Valid values: TLS1_0, TLS1_1, TLS1_2, TLS1_3", s)
+            )),
+        }
+    }
+}
+
+impl TlsVersion {
+    pub(crate) fn to_reqwest(self) -> Version {
+        match self {
+            TlsVersion::Tls1_0 => Version::TLS_1_0,
+            TlsVersion::Tls1_1 => Version::TLS_1_1,
+            TlsVersion::Tls1_2 => Version::TLS_1_2,
+            TlsVersion::Tls1_3 => Version::TLS_1_3,
+        }
+    }
+
+    /// native-tls 不支持指定 TLS 1.3，返回 None
+    pub(crate) fn to_native_tls(self) -> Option<native_tls::Protocol> {
+        match self {
+            TlsVersion::Tls1_0 => Some(native_tls::Protocol::Tlsv10),
+            TlsVersion::Tls1_1 => Some(native_tls::Protocol::Tlsv11),
+            TlsVersion::Tls1_2 => Some(native_tls::Protocol::Tlsv12),
+            TlsVersion::Tls1_3 => None,
+        }
+    }
+}
+
+/// 校验版本范围
+pub(crate) fn check_tls_range(min: Option<TlsVersion>, max: Option<TlsVersion>) -> PyResult<()> {
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(PyValueError::new_err(format!(
+                "tls_min_version {} is higher than tls_max_version {}", min.__str__(), max.__str__()
+            )));
+        }
+    }
+    Ok(())
+}
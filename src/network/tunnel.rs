@@ -0,0 +1,202 @@
+use std::io;
+use std::net::IpAddr;
+use base64::Engine;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::Url;
+use crate::network::ProxyConfig;
+
+// 代理响应头的最大长度
+const MAX_CONNECT_RESPONSE: usize = 8 * 1024;
+
+/// 代理链中每一跳支持的 scheme
+pub(crate) const TUNNEL_SCHEMES: [&str; 3] = ["http", "socks5", "socks5h"];
+
+/// http / https / all 代理支持的 scheme；socks5h 由代理解析域名
+pub(crate) const PROXY_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+
+/// 校验代理地址
+pub(crate) fn parse_proxy_url(raw: &str) -> Result<Url, String> {
+    let url = Url::parse(raw).map_err(|e| format!("Invalid proxy URL '{}': {}", raw, e))?;
+    if !PROXY_SCHEMES.contains(&url.scheme()) || url.host_str().is_none() {
+        return Err(format!(
+            "Invalid proxy URL '{}': expected http://, https://, socks5:// or socks5h:// with a host", raw
+        ));
+    }
+    Ok(url)
+}
+
+/// 代理地址带上 ProxyConfig 中的用户名和密码
+pub(crate) fn with_credentials(config: &ProxyConfig, raw: &str) -> Result<Url, String> {
+    let mut url = parse_proxy_url(raw)?;
+    if let Some(user) = &config.username {
+        let _ = url.set_username(user);
+    }
+    if let Some(pass) = &config.password {
+        let _ = url.set_password(Some(pass));
+    }
+    Ok(url)
+}
+
+/// 按目标 scheme 选择代理地址，并带上 ProxyConfig 中的用户名和密码
+pub(crate) fn proxy_url_for(config: &ProxyConfig, scheme: &str) -> Result<Option<Url>, String> {
+    let raw = match (&config.all, scheme) {
+        (Some(all), _) => all,
+        (None, "https" | "wss") => match &config.https { Some(p) => p, None => return Ok(None) },
+        (None, _) => match &config.http { Some(p) => p, None => return Ok(None) },
+    };
+    with_credentials(config, raw).map(Some)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// 依次经过 hops 中的代理建立到目标主机的嵌套隧道，hops 为空时直连
+pub(crate) async fn connect_via(hops: &[Url], host: &str, port: u16) -> io::Result<TcpStream> {
+    let Some(first) = hops.first() else {
+        return TcpStream::connect((host, port)).await;
+    };
+    let first_host = first.host_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "proxy URL has no host"))?;
+    let mut stream = TcpStream::connect((first_host, default_port(first))).await?;
+
+    // 每一跳负责连接到下一跳，最后一跳连接到目标主机
+    for (i, hop) in hops.iter().enumerate() {
+        let (next_host, next_port) = match hops.get(i + 1) {
+            Some(next) => (next.host_str().unwrap_or_default().to_string(), default_port(next)),
+            None => (host.to_string(), port),
+        };
+        match hop.scheme() {
+            "http" => http_connect(&mut stream, hop, &next_host, next_port).await?,
+            "socks5" => socks5_connect(&mut stream, hop, &next_host, next_port, false).await?,
+            "socks5h" => socks5_connect(&mut stream, hop, &next_host, next_port, true).await?,
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("proxy scheme '{}' is not supported for tunnelling", other),
+                ));
+            }
+        }
+    }
+    Ok(stream)
+}
+
+fn default_port(proxy: &Url) -> u16 {
+    proxy.port().unwrap_or(if proxy.scheme() == "http" { 80 } else { 1080 })
+}
+
+/// HTTP 代理的 CONNECT 方法
+async fn http_connect(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16) -> io::Result<()> {
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if !proxy.username().is_empty() {
+        let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or(""));
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // 逐字节读取到响应头结束，避免吞掉隧道中的数据
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE {
+            return Err(invalid("proxy CONNECT response too large".to_string()));
+        }
+        if stream.read(&mut byte).await? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "proxy closed the connection during CONNECT"));
+        }
+        response.push(byte[0]);
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("proxy CONNECT to {}:{} failed: {}", host, port, status_line),
+        )),
+    }
+}
+
+/// SOCKS5 握手（RFC 1928），支持用户名密码认证（RFC 1929）
+///
+/// remote_dns 为 true（socks5h）时由代理解析域名，否则在本地解析。
+async fn socks5_connect(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16, remote_dns: bool) -> io::Result<()> {
+    let with_auth = !proxy.username().is_empty();
+    let methods: &[u8] = if with_auth { &[0x00, 0x02] } else { &[0x00] };
+    let mut greeting = vec![0x05, methods.len() as u8];
+    greeting.extend_from_slice(methods);
+    stream.write_all(&greeting).await?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    match reply {
+        [0x05, 0x00] => {}
+        [0x05, 0x02] if with_auth => {
+            let user = proxy.username().as_bytes();
+            let pass = proxy.password().unwrap_or("").as_bytes();
+            let mut auth = vec![0x01, user.len() as u8];
+            auth.extend_from_slice(user);
+            auth.push(pass.len() as u8);
+            auth.extend_from_slice(pass);
+            stream.write_all(&auth).await?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0x00 {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SOCKS5 authentication failed"));
+            }
+        }
+        _ => return Err(invalid("SOCKS5 proxy rejected all authentication methods".to_string())),
+    }
+
+    let mut request = vec![0x05, 0x01, 0x00];
+    let address = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) if remote_dns => None,
+        Err(_) => Some(tokio::net::lookup_host((host, port)).await?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("failed to resolve {}", host)))?
+            .ip()),
+    };
+    match address {
+        Some(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Some(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        None => {
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("SOCKS5 connect to {}:{} failed with code {}", host, port, head[1]),
+        ));
+    }
+    // 读掉代理返回的绑定地址
+    let remaining = match head[3] {
+        0x01 => 4 + 2,
+        0x04 => 16 + 2,
+        0x03 => stream.read_u8().await? as usize + 2,
+        other => return Err(invalid(format!("SOCKS5 reply has unknown address type {}", other))),
+    };
+    let mut bound = vec![0u8; remaining];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+use pyo3::exceptions::{PyConnectionError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{client_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream};
+use url::Url;
+use crate::network::relay::parse_chain;
+use crate::network::proxy_config::bypasses;
+use crate::network::pac::{self, PacDns};
+use crate::network::tunnel::{connect_via, proxy_url_for, with_credentials};
+use crate::network::{CaBundle, ClientIdentity, ProxyConfig, TlsVersion};
+use crate::network::tls_version::check_tls_range;
+use crate::{GLOBAL_CLIENT_CONFIG, GLOBAL_PROXY};
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 建立连接所需的 TLS 选项，由参数和全局配置合并得到
+struct TlsOptions {
+    ssl_verify: bool,
+    identity: Option<ClientIdentity>,
+    ca_bundle: Option<CaBundle>,
+    tls_min_version: Option<TlsVersion>,
+    tls_max_version: Option<TlsVersion>,
+}
+
+impl TlsOptions {
+    /// 与 create_reqwest_client 中相同的 TLS 规则
+    fn connector(&self) -> Result<native_tls::TlsConnector, String> {
+        let mut builder = native_tls::TlsConnector::builder();
+        if !self.ssl_verify {
+            builder.danger_accept_invalid_certs(true).danger_accept_invalid_hostnames(true);
+        }
+        if let Some(min) = self.tls_min_version {
+            let protocol = min.to_native_tls()
+                .ok_or("tls_min_version TLS1_3 is not supported by the native TLS backend")?;
+            builder.min_protocol_version(Some(protocol));
+        }
+        if let Some(max) = self.tls_max_version.and_then(|v| v.to_native_tls()) {
+            builder.max_protocol_version(Some(max));
+        }
+        if let Some(identity) = &self.identity {
+            builder.identity(identity.to_native_tls()?);
+        }
+        if let Some(ca_bundle) = &self.ca_bundle {
+            for cert in ca_bundle.native_certificates()? {
+                builder.add_root_certificate(cert);
+            }
+        }
+        builder.build().map_err(|e| format!("Failed to build TLS connector: {}", e))
+    }
+}
+
+/// WebSocket 连接，send / recv / ping / close 均返回 awaitable
+#[pyclass]
+pub struct WebSocket {
+    #[pyo3(get)]
+    url: String,
+    #[pyo3(get)]
+    subprotocol: Option<String>,
+    sink: Arc<Mutex<SplitSink<WsStream, Message>>>,
+    stream: Arc<Mutex<SplitStream<WsStream>>>,
+}
+
+fn ws_error(e: impl std::fmt::Display) -> PyErr {
+    PyConnectionError::new_err(format!("WebSocket error: {}", e))
+}
+
+/// 发送关闭帧并关闭连接，对端已关闭时忽略错误
+async fn close_sink(sink: &Mutex<SplitSink<WsStream, Message>>, code: u16, reason: Option<String>) {
+    let frame = CloseFrame { code: CloseCode::from(code), reason: reason.unwrap_or_default().into() };
+    let mut sink = sink.lock().await;
+    let _ = sink.send(Message::Close(Some(frame))).await;
+    let _ = sink.close().await;
+}
+
+impl WebSocket {
+    fn send_message<'py>(&self, py: Python<'py>, message: Message) -> PyResult<&'py PyAny> {
+        let sink = self.sink.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            sink.lock().await.send(message).await.map_err(ws_error)?;
+            Ok(())
+        })
+    }
+}
+
+#[pymethods]
+impl WebSocket {
+    /// 发送消息，str 作为文本帧，bytes 作为二进制帧
+    fn send<'py>(&self, py: Python<'py>, data: &PyAny) -> PyResult<&'py PyAny> {
+        let message = if let Ok(bytes) = data.downcast::<PyBytes>() {
+            Message::Binary(bytes.as_bytes().to_vec())
+        } else {
+            Message::Text(data.extract::<String>()?)
+        };
+        self.send_message(py, message)
+    }
+
+    /// 接收下一条消息：文本帧返回 str，二进制帧返回 bytes，连接关闭返回 None
+    ///
+    /// ping 会被自动回复，ping / pong 帧不会返回给调用方。
+    #[pyo3(signature = (timeout=None))]
+    fn recv<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<&'py PyAny> {
+        let stream = self.stream.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let next = async {
+                let mut stream = stream.lock().await;
+                loop {
+                    match stream.next().await {
+                        Some(Ok(Message::Text(text))) => return Ok(Some(Message::Text(text))),
+                        Some(Ok(Message::Binary(data))) => return Ok(Some(Message::Binary(data))),
+                        Some(Ok(Message::Close(_))) | None => return Ok(None),
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(ws_error(e)),
+                    }
+                }
+            };
+            let message = match timeout {
+                Some(secs) => tokio::time::timeout(Duration::from_secs_f64(secs), next).await
+                    .map_err(|_| PyTimeoutError::new_err(format!("No message received within {:.2} seconds", secs)))??,
+                None => next.await?,
+            };
+            Python::with_gil(|py| Ok(match message {
+                Some(Message::Text(text)) => text.into_py(py),
+                Some(Message::Binary(data)) => PyBytes::new(py, &data).into_py(py),
+                _ => py.None(),
+            }))
+        })
+    }
+
+    #[pyo3(signature = (payload=None))]
+    fn ping<'py>(&self, py: Python<'py>, payload: Option<&PyBytes>) -> PyResult<&'py PyAny> {
+        let payload = payload.map(|p| p.as_bytes().to_vec()).unwrap_or_default();
+        self.send_message(py, Message::Ping(payload))
+    }
+
+    #[pyo3(signature = (code=1000, reason=None))]
+    fn close<'py>(&self, py: Python<'py>, code: u16, reason: Option<String>) -> PyResult<&'py PyAny> {
+        let sink = self.sink.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            close_sink(&sink, code, reason).await;
+            Ok(())
+        })
+    }
+
+    fn __aenter__<'py>(slf: Py<Self>, py: Python<'py>) -> PyResult<&'py PyAny> {
+        pyo3_asyncio::tokio::future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// 退出 async with 时关闭连接，不吞掉异常
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+    ) -> PyResult<&'py PyAny> {
+        let sink = self.sink.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            close_sink(&sink, 1000, None).await;
+            Ok(false)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("WebSocket(url='{}')", self.url)
+    }
+}
+
+/// 建立 WebSocket 连接，代理、TLS 选项未指定时使用全局配置
+///
+/// 代理支持 http://（CONNECT 隧道）和 socks5:// / socks5h://。
+#[pyfunction]
+#[pyo3(signature = (url, headers=None, subprotocols=None, proxy=None, ssl_verify=None, identity=None, ca_bundle=None, tls_min_version=None, tls_max_version=None, connect_timeout=None))]
+pub fn connect_websocket<'py>(
+    py: Python<'py>,
+    url: String,
+    headers: Option<HashMap<String, String>>,
+    subprotocols: Option<Vec<String>>,
+    proxy: Option<ProxyConfig>,
+    ssl_verify: Option<bool>,
+    identity: Option<ClientIdentity>,
+    ca_bundle: Option<CaBundle>,
+    tls_min_version: Option<TlsVersion>,
+    tls_max_version: Option<TlsVersion>,
+    connect_timeout: Option<f64>,
+) -> PyResult<&'py PyAny> {
+    let parsed = Url::parse(&url).map_err(|e| PyValueError::new_err(format!("Invalid URL '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "ws" | "wss") {
+        return Err(PyValueError::new_err(format!("WebSocket URL must use ws:// or wss://, got '{}'", url)));
+    }
+    let host = parsed.host_str()
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid URL '{}': missing host", url)))?
+        .to_string();
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    check_tls_range(tls_min_version, tls_max_version)?;
+
+    let mut request = url.as_str().into_client_request().map_err(|e| PyValueError::new_err(e.to_string()))?;
+    for (name, value) in headers.unwrap_or_default() {
+        let name = tokio_tungstenite::tungstenite::http::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| PyValueError::new_err(format!("Invalid header name '{}': {}", name, e)))?;
+        let value = HeaderValue::from_str(&value)
+            .map_err(|e| PyValueError::new_err(format!("Invalid header value: {}", e)))?;
+        request.headers_mut().insert(name, value);
+    }
+    if let Some(protocols) = subprotocols.filter(|p| !p.is_empty()) {
+        let value = HeaderValue::from_str(&protocols.join(", "))
+            .map_err(|e| PyValueError::new_err(format!("Invalid subprotocol: {}", e)))?;
+        request.headers_mut().insert("Sec-WebSocket-Protocol", value);
+    }
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let proxy_config = match proxy {
+            Some(p) => Some(p),
+            None => GLOBAL_PROXY.read().unwrap().clone(),
+        };
+        let proxy_config = ProxyConfig::effective(proxy_config.as_ref());
+        let global_config = GLOBAL_CLIENT_CONFIG.read().unwrap().clone();
+        let tls = TlsOptions {
+            ssl_verify: ssl_verify.or(global_config.ssl_verify).unwrap_or(true),
+            identity: identity.or(global_config.identity),
+            ca_bundle: ca_bundle.or(global_config.ca_bundle),
+            tls_min_version: tls_min_version.or(global_config.tls_min_version),
+            tls_max_version: tls_max_version.or(global_config.tls_max_version),
+        };
+        let connect_timeout = connect_timeout.or(global_config.connect_timeout).unwrap_or(30.0);
+
+        let connector = if parsed.scheme() == "wss" {
+            Connector::NativeTls(tls.connector().map_err(PyValueError::new_err)?)
+        } else {
+            Connector::Plain
+        };
+        // 代理链或单个代理，依次建立隧道
+        let hops = match &proxy_config {
+            Some(config) if bypasses(&config.bypass_rules(), &host) => Vec::new(),
+            Some(config) => match config.chain.as_ref().filter(|c| !c.is_empty()) {
+                Some(chain) => parse_chain(chain).map_err(PyValueError::new_err)?,
+                None if config.pac.is_some() => {
+                    let script = pac::load(config.pac.as_deref().unwrap_or_default()).await
+                        .map_err(PyConnectionError::new_err)?;
+                    let dns = PacDns {
+                        resolve: global_config.resolve.clone(),
+                        timeout: global_config.dns_timeout.map(Duration::from_secs_f64),
+                    };
+                    match script.proxy_for(&parsed, &dns) {
+                        Some(proxy) => vec![with_credentials(config, proxy.as_str()).map_err(PyValueError::new_err)?],
+                        None => Vec::new(),
+                    }
+                }
+                None => proxy_url_for(config, parsed.scheme()).map_err(PyValueError::new_err)?.into_iter().collect(),
+            },
+            None => Vec::new(),
+        };
+
+        let connect = async {
+            let tcp = connect_via(&hops, &host, port).await
+            .map_err(|e| PyConnectionError::new_err(format!("Failed to connect to {}:{}: {}", host, port, e)))?;
+            client_async_tls_with_config(request, tcp, None, Some(connector)).await.map_err(ws_error)
+        };
+        let (ws, response) = tokio::time::timeout(Duration::from_secs_f64(connect_timeout), connect).await
+            .map_err(|_| PyTimeoutError::new_err(format!("Connect timeout after {:.2} seconds", connect_timeout)))??;
+
+        let subprotocol = response.headers().get("Sec-WebSocket-Protocol")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let (sink, stream) = ws.split();
+        Ok(WebSocket {
+            url,
+            subprotocol,
+            sink: Arc::new(Mutex::new(sink)),
+            stream: Arc::new(Mutex::new(stream)),
+        })
+    })
+}
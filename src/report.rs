@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use chrono::Local;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use serde_json::{json, Map, Value};
+use crate::utils::{json_to_py, py_to_json};
+
+const TOP_N: usize = 10;
+
+/// 结果中的 meta / exception 可能是 dict（fetch_requests）或 JSON 字符串（fetch_single）
+fn field(result: &Value, key: &str) -> Map<String, Value> {
+    match result.get(key) {
+        Some(Value::Object(map)) => map.clone(),
+        Some(Value::String(s)) => serde_json::from_str::<Value>(s).ok()
+            .and_then(|v| v.as_object().cloned())
+            .unwrap_or_default(),
+        _ => Map::new(),
+    }
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// 汇总批量请求结果：状态码分布、异常分布、耗时统计、最慢请求和错误样例
+fn build_report(results: &[Value], title: &str) -> Value {
+    let mut status_counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut exception_counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut timings = Vec::new();
+    let mut entries = Vec::new();
+    let mut error_samples = Vec::new();
+
+    for result in results {
+        let meta = field(result, "meta");
+        let exception = field(result, "exception");
+        let status = match result.get("http_status") {
+            Some(Value::String(s)) => s.clone(),
+            Some(v) => v.to_string(),
+            None => "0".to_string(),
+        };
+        *status_counts.entry(status.clone()).or_default() += 1;
+
+        let process_time = meta.get("process_time")
+            .and_then(|v| v.as_str().and_then(|s| s.parse::<f64>().ok()).or_else(|| v.as_f64()))
+            .unwrap_or(0.0);
+        timings.push(process_time);
+
+        let tag = meta.get("tag").cloned().unwrap_or(Value::Null);
+        let url = meta.get("final_url").cloned().unwrap_or(Value::Null);
+        entries.push(json!({"tag": tag, "url": url, "http_status": status, "process_time": process_time}));
+
+        if let Some(exc_type) = exception.get("type").and_then(|v| v.as_str()) {
+            *exception_counts.entry(exc_type.to_string()).or_default() += 1;
+            if error_samples.len() < TOP_N {
+                error_samples.push(json!({
+                    "tag": tag,
+                    "url": url,
+                    "type": exc_type,
+                    "message": exception.get("message").cloned().unwrap_or(Value::Null),
+                }));
+            }
+        }
+    }
+
+    let failed: u64 = exception_counts.values().sum();
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    entries.sort_by(|a, b| b["process_time"].as_f64().unwrap_or(0.0).total_cmp(&a["process_time"].as_f64().unwrap_or(0.0)));
+    entries.truncate(TOP_N);
+
+    json!({
+        "title": title,
+        "generated_at": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        "total": results.len(),
+        "succeeded": results.len() as u64 - failed,
+        "failed": failed,
+        "status_counts": status_counts,
+        "exception_counts": exception_counts,
+        "process_time": {
+            "min": sorted.first().copied().unwrap_or(0.0),
+            "avg": if sorted.is_empty() { 0.0 } else { sorted.iter().sum::<f64>() / sorted.len() as f64 },
+            "p50": percentile(&sorted, 50.0),
+            "p95": percentile(&sorted, 95.0),
+            "max": sorted.last().copied().unwrap_or(0.0),
+        },
+        "slowest": entries,
+        "error_samples": error_samples,
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => escape_html(s),
+        Value::Null => String::new(),
+        Value::Number(n) => n.as_f64().map(|f| if f.fract() == 0.0 { n.to_string() } else { format!("{:.4}", f) }).unwrap_or_default(),
+        other => escape_html(&other.to_string()),
+    }
+}
+
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let head: String = headers.iter().map(|h| format!("<th>{}</th>", h)).collect();
+    let body: String = rows.into_iter()
+        .map(|row| format!("<tr>{}</tr>", row.into_iter().map(|c| format!("<td>{}</td>", c)).collect::<String>()))
+        .collect();
+    format!("<table><tr>{}</tr>{}</table>", head, body)
+}
+
+fn render_html(report: &Value) -> String {
+    let counts = |key: &str| -> Vec<Vec<String>> {
+        report[key].as_object().map(|m| m.iter().map(|(k, v)| vec![escape_html(k), cell(v)]).collect()).unwrap_or_default()
+    };
+    let list = |key: &str, fields: &[&str]| -> Vec<Vec<String>> {
+        report[key].as_array()
+            .map(|rows| rows.iter().map(|row| fields.iter().map(|f| cell(&row[*f])).collect()).collect())
+            .unwrap_or_default()
+    };
+    let timing = &report["process_time"];
+
+    format!(
+        r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>{title}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; margin-bottom: 2em; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 10px; text-align: left; font-size: 14px; }}
+th {{ background: #f3f3f3; }}
+.summary span {{ display: inline-block; margin-right: 2em; font-size: 18px; }}
+</style></head><body>
+<h1>{title}</h1>
+<p>Generated at {generated_at}</p>
+<div class="summary"><span>Total: {total}</span><span>Succeeded: {succeeded}</span><span>Failed: {failed}</span></div>
+<h2>Process time (s)</h2>
+{timing}
+<h2>Status codes</h2>
+{statuses}
+<h2>Exceptions</h2>
+{exceptions}
+<h2>Slowest requests</h2>
+{slowest}
+<h2>Error samples</h2>
+{errors}
+</body></html>
+"#,
+        title = cell(&report["title"]),
+        generated_at = cell(&report["generated_at"]),
+        total = cell(&report["total"]),
+        succeeded = cell(&report["succeeded"]),
+        failed = cell(&report["failed"]),
+        timing = table(&["min", "avg", "p50", "p95", "max"], vec![
+            ["min", "avg", "p50", "p95", "max"].iter().map(|k| cell(&timing[*k])).collect()
+        ]),
+        statuses = table(&["http_status", "count"], counts("status_counts")),
+        exceptions = table(&["type", "count"], counts("exception_counts")),
+        slowest = table(&["tag", "url", "http_status", "process_time"], list("slowest", &["tag", "url", "http_status", "process_time"])),
+        errors = table(&["tag", "url", "type", "message"], list("error_samples", &["tag", "url", "type", "message"])),
+    )
+}
+
+/// 生成批量请求报告（html 或 json）并写入 path，返回汇总数据
+#[pyfunction]
+#[pyo3(signature = (results, path, format=None, title=None))]
+pub fn generate_report(
+    py: Python,
+    results: &PyList,
+    path: String,
+    format: Option<String>,
+    title: Option<String>,
+) -> PyResult<PyObject> {
+    let format = format.unwrap_or_else(|| {
+        if path.to_lowercase().ends_with(".json") { "json".to_string() } else { "html".to_string() }
+    }).to_lowercase();
+    if format != "html" && format != "json" {
+        return Err(PyValueError::new_err(format!("Invalid format: '{}'. Valid values: html, json", format)));
+    }
+
+    let results = results.iter().map(|r| py_to_json(py, r)).collect::<PyResult<Vec<_>>>()?;
+    let report = build_report(&results, title.as_deref().unwrap_or("rusty-req report"));
+    let content = if format == "json" {
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    } else {
+        render_html(&report)
+    };
+    std::fs::write(&path, content).map_err(|e| PyIOError::new_err(format!("Failed to write '{}': {}", path, e)))?;
+
+    json_to_py(py, &report)
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use serde_json::{Map, Value};
+
+/// 请求附带的断言
+pub(crate) struct Assertions<'a> {
+    pub json: Option<Value>,
+    pub contains: Option<&'a str>,
+    pub headers: Option<&'a HashMap<String, String>>,
+}
+
+impl Assertions<'_> {
+    fn is_empty(&self) -> bool {
+        self.json.is_none() && self.contains.is_none() && self.headers.is_none()
+    }
+
+    /// 执行断言，返回 {"passed": bool, "failures": [...]}，没有断言时返回 None
+    pub(crate) fn evaluate(&self, headers: &Map<String, Value>, content: &str) -> Option<Value> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut failures = Vec::new();
+
+        if let Some(expected) = &self.json {
+            match serde_json::from_str::<Value>(content) {
+                Ok(actual) => {
+                    if let Some(path) = json_mismatch(expected, &actual, "$") {
+                        failures.push(format!("assert_json: mismatch at {}", path));
+                    }
+                }
+                Err(e) => failures.push(format!("assert_json: response is not JSON ({})", e)),
+            }
+        }
+
+        if let Some(needle) = self.contains {
+            if !content.contains(needle) {
+                failures.push(format!("assert_contains: '{}' not found", needle));
+            }
+        }
+
+        if let Some(expected) = self.headers {
+            for (name, value) in expected {
+                let actual = headers.iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                    .and_then(|(_, v)| v.as_str());
+                match actual {
+                    // 忽略参数部分，如 "application/json; charset=utf-8" 匹配 "application/json"
+                    Some(actual) if actual == value || actual.split(';').next().map(str::trim) == Some(value.as_str()) => {}
+                    Some(actual) => failures.push(format!("assert_header: {} is '{}', expected '{}'", name, actual, value)),
+                    None => failures.push(format!("assert_header: {} is missing", name)),
+                }
+            }
+        }
+
+        Some(serde_json::json!({
+            "passed": failures.is_empty(),
+            "failures": failures,
+        }))
+    }
+}
+
+/// expected 是否为 actual 的子集，不匹配时返回第一个不匹配的路径
+fn json_mismatch(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(exp), Value::Object(act)) => exp.iter().find_map(|(key, value)| {
+            let child = format!("{}.{}", path, key);
+            match act.get(key) {
+                Some(actual_value) => json_mismatch(value, actual_value, &child),
+                None => Some(child),
+            }
+        }),
+        (Value::Array(exp), Value::Array(act)) => {
+            if exp.len() != act.len() {
+                return Some(path.to_string());
+            }
+            exp.iter().zip(act).enumerate()
+                .find_map(|(i, (e, a))| json_mismatch(e, a, &format!("{}[{}]", path, i)))
+        }
+        (Value::Number(e), Value::Number(a)) => (e.as_f64() != a.as_f64()).then(|| path.to_string()),
+        _ => (expected != actual).then(|| path.to_string()),
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+use reqwest::Response;
+use crate::request::spill::{SpillFile, Spilled};
+
+/// 响应体读取结果
+pub struct BodyRead {
+    pub bytes: Vec<u8>,
+    /// 超出读取时限，bytes 中为已收到的部分
+    pub timed_out: bool,
+    pub error: Option<String>,
+    /// 超出 max_bytes 时停止读取，bytes 为空
+    pub too_large: bool,
+    /// 超出 spill_after 时响应体写入临时文件，bytes 为空
+    pub spilled: Option<Spilled>,
+}
+
+impl BodyRead {
+    /// 响应体字节数，包括写入临时文件的部分
+    pub fn len(&self) -> usize {
+        self.spilled.as_ref().map_or(self.bytes.len(), |s| s.size)
+    }
+}
+
+/// 分块读取响应体，超出 read_timeout 时保留已读取的内容
+///
+/// max_bytes 限制线上收到的字节数：Content-Length 已超出时不读取，读取中超出时立即停止并丢弃已读内容；
+/// 解压后的大小由解压时单独限制。
+/// 收到的字节数超过 spill_after 时改为写入临时文件；读取出错或超出 max_bytes 时删除该文件。
+pub async fn read_body(mut res: Response, read_timeout: Option<Duration>, max_bytes: Option<usize>, spill_after: Option<usize>) -> BodyRead {
+    let deadline = read_timeout.map(|t| tokio::time::Instant::now() + t);
+    let mut body = BodyRead { bytes: Vec::new(), timed_out: false, error: None, too_large: false, spilled: None };
+    let mut spill: Option<SpillFile> = None;
+    let mut received = 0;
+    if let (Some(max), Some(len)) = (max_bytes, res.content_length()) {
+        if len > max as u64 {
+            body.too_large = true;
+            return body;
+        }
+    }
+
+    loop {
+        let next = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, res.chunk()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    body.timed_out = true;
+                    break;
+                }
+            },
+            None => res.chunk().await,
+        };
+
+        match next {
+            Ok(Some(chunk)) => {
+                received += chunk.len();
+                if max_bytes.is_some_and(|max| received > max) {
+                    body.bytes = Vec::new();
+                    body.too_large = true;
+                    break;
+                }
+                if spill.is_none() && spill_after.is_some_and(|threshold| received > threshold) {
+                    match SpillFile::create().await {
+                        Ok(file) => spill = Some(file),
+                        Err(e) => {
+                            body.error = Some(format!("Failed to create temporary file: {}", e));
+                            break;
+                        }
+                    }
+                }
+                match spill.as_mut() {
+                    Some(file) => {
+                        // 先写入之前缓存在内存中的部分
+                        let buffered = std::mem::take(&mut body.bytes);
+                        if let Err(e) = async { file.write(&buffered).await?; file.write(&chunk).await }.await {
+                            body.error = Some(format!("Failed to write temporary file: {}", e));
+                            break;
+                        }
+                    }
+                    None => body.bytes.extend_from_slice(&chunk),
+                }
+            }
+            Ok(None) => break,
+            Err(e) if e.is_timeout() => {
+                body.timed_out = true;
+                break;
+            }
+            Err(e) => {
+                body.error = Some(e.to_string());
+                break;
+            }
+        }
+    }
+
+    if let Some(file) = spill {
+        if body.error.is_some() || body.too_large {
+            file.discard().await;
+        } else {
+            match file.finish().await {
+                Ok(spilled) => body.spilled = Some(spilled),
+                Err(e) => body.error = Some(format!("Failed to write temporary file: {}", e)),
+            }
+        }
+    }
+    body
+}
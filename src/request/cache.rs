@@ -0,0 +1,551 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use reqwest::{Response, ResponseBuilderExt};
+use serde_json::{json, Value};
+use url::Url;
+use crate::request::limits::check_limit;
+use crate::request::PreparedRequest;
+use crate::utils::sha256_hex;
+
+/// 单个请求的缓存指令
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum CacheMode {
+    /// 不读取也不写入缓存，要求中间缓存不保存响应
+    Bypass,
+    /// 忽略已缓存的响应，强制向源站重新验证
+    Refresh,
+    /// 只使用已缓存的响应，不访问源站
+    OnlyIfCached,
+}
+
+impl CacheMode {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "bypass" => Ok(CacheMode::Bypass),
+            "refresh" => Ok(CacheMode::Refresh),
+            "only-if-cached" => Ok(CacheMode::OnlyIfCached),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid cache: '{}'. Valid values: bypass, refresh, only-if-cached",
+                name
+            ))),
+        }
+    }
+
+    /// 发给代理和 CDN 等中间缓存的 Cache-Control 请求头
+    pub(crate) fn cache_control(&self) -> &'static str {
+        match self {
+            CacheMode::Bypass => "no-store, no-cache",
+            CacheMode::Refresh => "no-cache",
+            CacheMode::OnlyIfCached => "only-if-cached",
+        }
+    }
+}
+
+const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+// 默认可以按启发式规则缓存的状态码（RFC 9110 15.1）
+const HEURISTIC_STATUS: &[u16] = &[200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+// 启发式新鲜期的上限
+const MAX_HEURISTIC_SECS: f64 = 24.0 * 3600.0;
+
+/// 缓存的响应，body 为线上收到的原始字节（未解压）
+#[derive(Clone)]
+struct Entry {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// 存入时间（Unix 秒）
+    stored_at: f64,
+    /// 存入时响应已有的 Age
+    initial_age: f64,
+    /// Vary 列出的请求头及存入时请求中的值
+    vary: Vec<(String, Option<String>)>,
+}
+
+impl Entry {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    fn size(&self) -> usize {
+        self.body.len() + self.headers.iter().map(|(k, v)| k.len() + v.len()).sum::<usize>()
+    }
+
+    fn age(&self, now: f64) -> f64 {
+        self.initial_age + (now - self.stored_at).max(0.0)
+    }
+
+    /// 新鲜期（秒）：max-age，其次 Expires - Date，最后按 Last-Modified 的 10% 估算
+    fn lifetime(&self) -> f64 {
+        let cc = directives(self.header("cache-control"));
+        if has(&cc, "no-cache") {
+            return 0.0;
+        }
+        if let Some(max_age) = value(&cc, "max-age").and_then(|v| v.parse::<f64>().ok()) {
+            return max_age;
+        }
+        let date = self.header("date").and_then(http_date).unwrap_or(self.stored_at);
+        if let Some(expires) = self.header("expires") {
+            // 无效的 Expires 表示已过期
+            return http_date(expires).map_or(0.0, |expires| (expires - date).max(0.0));
+        }
+        match self.header("last-modified").and_then(http_date) {
+            Some(modified) if HEURISTIC_STATUS.contains(&self.status) => ((date - modified) * 0.1).clamp(0.0, MAX_HEURISTIC_SECS),
+            _ => 0.0,
+        }
+    }
+
+    fn fresh(&self, now: f64) -> bool {
+        self.age(now) < self.lifetime()
+    }
+
+    fn has_validator(&self) -> bool {
+        self.header("etag").is_some() || self.header("last-modified").is_some()
+    }
+
+    fn matches(&self, request_headers: &[(String, String)]) -> bool {
+        self.vary.iter().all(|(name, stored)| request_value(request_headers, name) == stored.as_deref())
+    }
+
+    fn to_response(&self, url: &str, now: f64) -> Option<Response> {
+        let mut builder = http::Response::builder().status(self.status);
+        if let Ok(url) = Url::parse(url) {
+            builder = builder.url(url);
+        }
+        for (name, value) in self.headers.iter().filter(|(k, _)| !k.eq_ignore_ascii_case("age")) {
+            builder = builder.header(name, value);
+        }
+        builder = builder.header("age", (self.age(now) as u64).to_string());
+        builder.body(self.body.clone()).ok().map(Response::from)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "status": self.status,
+            "headers": self.headers,
+            "body": STANDARD.encode(&self.body),
+            "stored_at": self.stored_at,
+            "initial_age": self.initial_age,
+            "vary": self.vary,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let pairs = |v: &Value| -> Option<Vec<(String, Value)>> {
+            v.as_array()?.iter().map(|p| Some((p.get(0)?.as_str()?.to_string(), p.get(1)?.clone()))).collect()
+        };
+        Some(Entry {
+            status: value["status"].as_u64()? as u16,
+            headers: pairs(&value["headers"])?.into_iter().map(|(k, v)| Some((k, v.as_str()?.to_string()))).collect::<Option<_>>()?,
+            body: STANDARD.decode(value["body"].as_str()?).ok()?,
+            stored_at: value["stored_at"].as_f64()?,
+            initial_age: value["initial_age"].as_f64()?,
+            vary: pairs(&value["vary"])?.into_iter().map(|(k, v)| (k, v.as_str().map(str::to_string))).collect(),
+        })
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or_default()
+}
+
+fn http_date(value: &str) -> Option<f64> {
+    httpdate::parse_http_date(value.trim()).ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs_f64())
+}
+
+/// 解析 Cache-Control，指令名转为小写
+fn directives(value: Option<&str>) -> Vec<(String, Option<String>)> {
+    value.unwrap_or_default().split(',')
+        .filter_map(|d| {
+            let (name, value) = match d.split_once('=') {
+                Some((name, value)) => (name, Some(value.trim().trim_matches('"').to_string())),
+                None => (d, None),
+            };
+            let name = name.trim().to_ascii_lowercase();
+            (!name.is_empty()).then_some((name, value))
+        })
+        .collect()
+}
+
+fn has(directives: &[(String, Option<String>)], name: &str) -> bool {
+    directives.iter().any(|(n, _)| n == name)
+}
+
+fn value<'a>(directives: &'a [(String, Option<String>)], name: &str) -> Option<&'a str> {
+    directives.iter().find(|(n, _)| n == name).and_then(|(_, v)| v.as_deref())
+}
+
+fn request_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+}
+
+/// 缓存存储：内存中按总大小淘汰最久未使用的条目，或每个条目一个文件的目录
+enum Store {
+    Memory {
+        entries: HashMap<String, Entry>,
+        order: VecDeque<String>,
+        bytes: usize,
+        max_bytes: usize,
+    },
+    Directory(PathBuf),
+}
+
+static STORE: Lazy<Mutex<Option<Store>>> = Lazy::new(|| Mutex::new(None));
+
+impl Store {
+    fn file(dir: &std::path::Path, key: &str) -> PathBuf {
+        dir.join(format!("{}.json", sha256_hex(key.as_bytes())))
+    }
+}
+
+fn directory() -> Option<PathBuf> {
+    match STORE.lock().unwrap().as_ref()? {
+        Store::Directory(dir) => Some(dir.clone()),
+        Store::Memory { .. } => None,
+    }
+}
+
+async fn get(key: &str) -> Option<Entry> {
+    if let Some(dir) = directory() {
+        let bytes = tokio::fs::read(Store::file(&dir, key)).await.ok()?;
+        return Entry::from_json(&serde_json::from_slice(&bytes).ok()?);
+    }
+    let mut store = STORE.lock().unwrap();
+    let Some(Store::Memory { entries, order, .. }) = store.as_mut() else { return None };
+    let entry = entries.get(key)?.clone();
+    // 移到最近使用的位置
+    order.retain(|k| k != key);
+    order.push_back(key.to_string());
+    Some(entry)
+}
+
+async fn put(key: &str, entry: Entry) {
+    if let Some(dir) = directory() {
+        // 先写临时文件再重命名，读取方不会看到写了一半的条目
+        let path = Store::file(&dir, key);
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        let written = async {
+            tokio::fs::create_dir_all(&dir).await?;
+            tokio::fs::write(&tmp, entry.to_json().to_string()).await?;
+            tokio::fs::rename(&tmp, &path).await
+        }.await;
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        return;
+    }
+    let mut store = STORE.lock().unwrap();
+    let Some(Store::Memory { entries, order, bytes, max_bytes }) = store.as_mut() else { return };
+    let size = entry.size();
+    if size > *max_bytes {
+        return;
+    }
+    if let Some(old) = entries.insert(key.to_string(), entry) {
+        *bytes -= old.size();
+        order.retain(|k| k != key);
+    }
+    *bytes += size;
+    order.push_back(key.to_string());
+    while *bytes > *max_bytes {
+        let Some(oldest) = order.pop_front() else { break };
+        if let Some(old) = entries.remove(&oldest) {
+            *bytes -= old.size();
+        }
+    }
+}
+
+async fn remove(key: &str) {
+    if let Some(dir) = directory() {
+        let _ = tokio::fs::remove_file(Store::file(&dir, key)).await;
+        return;
+    }
+    let mut store = STORE.lock().unwrap();
+    if let Some(Store::Memory { entries, order, bytes, .. }) = store.as_mut() {
+        if let Some(old) = entries.remove(key) {
+            *bytes -= old.size();
+            order.retain(|k| k != key);
+        }
+    }
+}
+
+/// 缓存键：URL 和认证信息，不同凭据的响应互不共享
+fn key(url: &str, credentials: Option<&str>) -> String {
+    match credentials {
+        Some(credentials) => format!("{} {}", url, sha256_hex(credentials.as_bytes())),
+        None => url.to_string(),
+    }
+}
+
+/// 等待写入或更新缓存的请求
+pub(crate) struct Pending {
+    key: String,
+    url: String,
+    request_headers: Vec<(String, String)>,
+    /// 重新验证中的条目
+    stale: Option<Entry>,
+}
+
+/// 查询缓存的结果
+pub(crate) enum Lookup {
+    /// 未启用缓存或请求不使用缓存
+    Off,
+    /// 新鲜的缓存响应，不访问源站
+    Hit(Response),
+    /// 没有可用的缓存响应，发送后按响应头决定是否存入
+    Miss(Pending),
+    /// 缓存的响应已过期，带上这些条件请求头重新验证
+    Revalidate(Pending, Vec<(&'static str, String)>),
+    /// only-if-cached 且没有新鲜的缓存响应
+    Unavailable(Response),
+}
+
+/// 查询 GET 请求的缓存；extra_headers 为发送时追加的请求头（如 Accept-Encoding），参与 Vary 匹配
+pub(crate) async fn lookup(prepared: &PreparedRequest, mode: Option<CacheMode>, credentials: Option<&str>, extra_headers: &[(&str, &str)]) -> Lookup {
+    if STORE.lock().unwrap().is_none() || prepared.method != "GET" || mode == Some(CacheMode::Bypass) {
+        return Lookup::Off;
+    }
+    let request_cc = directives(request_value(&prepared.headers, "cache-control"));
+    if has(&request_cc, "no-store") {
+        return Lookup::Off;
+    }
+    let mut request_headers = prepared.headers.clone();
+    request_headers.extend(extra_headers.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+    let key = key(&prepared.url, credentials);
+    let now = now();
+    let entry = get(&key).await.filter(|entry| entry.matches(&request_headers));
+    let revalidate = mode == Some(CacheMode::Refresh)
+        || has(&request_cc, "no-cache")
+        || value(&request_cc, "max-age") == Some("0");
+    let pending = |stale| Pending { key: key.clone(), url: prepared.url.clone(), request_headers: request_headers.clone(), stale };
+
+    match entry {
+        Some(entry) if !revalidate && entry.fresh(now) => match entry.to_response(&prepared.url, now) {
+            Some(res) => Lookup::Hit(res),
+            None => Lookup::Miss(pending(None)),
+        },
+        _ if mode == Some(CacheMode::OnlyIfCached) || has(&request_cc, "only-if-cached") => {
+            let res = http::Response::builder().status(504).body(Vec::<u8>::new()).map(Response::from);
+            match res {
+                Ok(res) => Lookup::Unavailable(res),
+                Err(_) => Lookup::Off,
+            }
+        }
+        // 用户自行发送的条件请求，304 对应的是用户的校验值，不能用缓存的响应体代替
+        Some(_) if request_value(&request_headers, "if-none-match").is_some() || request_value(&request_headers, "if-modified-since").is_some() => {
+            Lookup::Miss(pending(None))
+        }
+        Some(entry) if entry.has_validator() => {
+            let mut conditions = Vec::new();
+            if let Some(etag) = entry.header("etag") {
+                conditions.push(("If-None-Match", etag.to_string()));
+            }
+            if let Some(modified) = entry.header("last-modified") {
+                conditions.push(("If-Modified-Since", modified.to_string()));
+            }
+            Lookup::Revalidate(pending(Some(entry)), conditions)
+        }
+        _ => Lookup::Miss(pending(None)),
+    }
+}
+
+impl Pending {
+    /// 304 响应：用新的响应头更新缓存条目，返回更新后的缓存响应
+    pub(crate) async fn refresh(mut self, not_modified: &Response) -> Option<Response> {
+        let mut entry = self.stale.take()?;
+        for (name, value) in not_modified.headers() {
+            let name = name.as_str();
+            // 304 的 Content-Length 描述的不是缓存的响应体
+            if name == "content-length" {
+                continue;
+            }
+            let Ok(value) = value.to_str() else { continue };
+            entry.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+            entry.headers.push((name.to_string(), value.to_string()));
+        }
+        entry.stored_at = now();
+        entry.initial_age = not_modified.headers().get("age")
+            .and_then(|v| v.to_str().ok()?.trim().parse().ok())
+            .unwrap_or(0.0);
+        let res = entry.to_response(&self.url, entry.stored_at);
+        put(&self.key, entry).await;
+        res
+    }
+
+    /// 按响应的 Cache-Control、Vary 和状态码决定是否存入缓存
+    pub(crate) async fn store(self, status: u16, headers: Vec<(String, String)>, body: &[u8]) {
+        let response_cc = directives(request_value(&headers, "cache-control"));
+        let vary = request_value(&headers, "vary").unwrap_or_default().to_string();
+        if has(&response_cc, "no-store") || vary.trim() == "*" {
+            remove(&self.key).await;
+            return;
+        }
+        let explicit = has(&response_cc, "max-age") || has(&response_cc, "public") || request_value(&headers, "expires").is_some();
+        if !explicit && !HEURISTIC_STATUS.contains(&status) {
+            return;
+        }
+        let entry = Entry {
+            status,
+            initial_age: request_value(&headers, "age").and_then(|v| v.trim().parse().ok()).unwrap_or(0.0),
+            vary: vary.split(',')
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    let value = request_value(&self.request_headers, &name).map(str::to_string);
+                    (name, value)
+                })
+                .collect(),
+            headers,
+            body: body.to_vec(),
+            stored_at: now(),
+        };
+        // 既不新鲜也无法重新验证的响应没有缓存价值
+        if entry.lifetime() > 0.0 || entry.has_validator() {
+            put(&self.key, entry).await;
+        }
+    }
+}
+
+/// 不安全的方法成功后，该地址的缓存失效（RFC 9111 4.4）
+pub(crate) async fn invalidate(method: &str, url: &str, credentials: Option<&str>, status: u16) {
+    if matches!(method, "GET" | "HEAD" | "OPTIONS" | "TRACE") || !(200..400).contains(&status) || STORE.lock().unwrap().is_none() {
+        return;
+    }
+    remove(&key(url, credentials)).await;
+}
+
+/// 启用或关闭响应缓存（RFC 9111 私有缓存）
+///
+/// 指定 directory 时每个响应一个文件，否则保存在内存中，总大小超过 max_bytes 时淘汰最久未使用的。
+#[pyfunction]
+#[pyo3(signature = (enabled=true, max_bytes=None, directory=None))]
+pub fn configure_response_cache(enabled: bool, max_bytes: Option<usize>, directory: Option<String>) -> PyResult<()> {
+    check_limit("max_bytes", max_bytes)?;
+    if directory.is_some() && max_bytes.is_some() {
+        return Err(PyValueError::new_err("max_bytes only applies to the in-memory cache"));
+    }
+    let store = match (enabled, directory) {
+        (false, _) => None,
+        (true, Some(dir)) => Some(Store::Directory(PathBuf::from(dir))),
+        (true, None) => Some(Store::Memory {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            bytes: 0,
+            max_bytes: max_bytes.unwrap_or(DEFAULT_MAX_BYTES),
+        }),
+    };
+    *STORE.lock().unwrap() = store;
+    Ok(())
+}
+
+/// 清空内存中的响应缓存，返回删除的条目数；目录缓存需自行删除文件
+#[pyfunction]
+pub fn clear_response_cache() -> usize {
+    let mut store = STORE.lock().unwrap();
+    match store.as_mut() {
+        Some(Store::Memory { entries, order, bytes, .. }) => {
+            let count = entries.len();
+            entries.clear();
+            order.clear();
+            *bytes = 0;
+            count
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn entry(status: u16, pairs: &[(&str, &str)]) -> Entry {
+        Entry { status, headers: headers(pairs), body: Vec::new(), stored_at: now(), initial_age: 0.0, vary: Vec::new() }
+    }
+
+    fn date(base: SystemTime, secs_ago: u64) -> String {
+        httpdate::fmt_http_date(base - std::time::Duration::from_secs(secs_ago))
+    }
+
+    #[test]
+    fn lifetime_prefers_max_age() {
+        let e = entry(200, &[("cache-control", "public, max-age=60"), ("expires", &date(SystemTime::now(), 0))]);
+        assert_eq!(e.lifetime(), 60.0);
+        assert_eq!(entry(200, &[("cache-control", "no-cache, max-age=60")]).lifetime(), 0.0);
+    }
+
+    #[test]
+    fn lifetime_from_expires() {
+        let now = SystemTime::now();
+        let e = entry(200, &[
+            ("date", &httpdate::fmt_http_date(now)),
+            ("expires", &httpdate::fmt_http_date(now + std::time::Duration::from_secs(120))),
+        ]);
+        assert_eq!(e.lifetime(), 120.0);
+        // 无效的 Expires 视为已过期
+        assert_eq!(entry(200, &[("expires", "0")]).lifetime(), 0.0);
+    }
+
+    #[test]
+    fn lifetime_heuristic() {
+        let base = SystemTime::now();
+        let d = date(base, 0);
+        let e = entry(200, &[("date", &d), ("last-modified", &date(base, 1000))]);
+        assert_eq!(e.lifetime(), 100.0);
+        let e = entry(200, &[("date", &d), ("last-modified", &date(base, 100 * 24 * 3600))]);
+        assert_eq!(e.lifetime(), MAX_HEURISTIC_SECS);
+        // 302 不允许启发式缓存
+        assert_eq!(entry(302, &[("date", &d), ("last-modified", &date(base, 1000))]).lifetime(), 0.0);
+    }
+
+    #[test]
+    fn matches_vary_headers() {
+        let mut e = entry(200, &[]);
+        e.vary = vec![("accept-encoding".to_string(), Some("gzip".to_string())), ("accept-language".to_string(), None)];
+        assert!(e.matches(&headers(&[("Accept-Encoding", "gzip")])));
+        assert!(!e.matches(&headers(&[("Accept-Encoding", "br")])));
+        assert!(!e.matches(&headers(&[])));
+        assert!(!e.matches(&headers(&[("Accept-Encoding", "gzip"), ("Accept-Language", "en")])));
+    }
+
+    #[tokio::test]
+    async fn store_follows_response_headers() {
+        *STORE.lock().unwrap() = Some(Store::Memory { entries: HashMap::new(), order: VecDeque::new(), bytes: 0, max_bytes: DEFAULT_MAX_BYTES });
+        let pending = |key: &str| Pending {
+            key: key.to_string(),
+            url: format!("https://example.com/{}", key),
+            request_headers: headers(&[("accept-encoding", "gzip")]),
+            stale: None,
+        };
+
+        pending("fresh").store(200, headers(&[("cache-control", "max-age=60"), ("vary", "Accept-Encoding")]), b"ok").await;
+        let stored = get("fresh").await.expect("max-age response is stored");
+        assert_eq!(stored.body, b"ok");
+        assert_eq!(stored.vary, vec![("accept-encoding".to_string(), Some("gzip".to_string()))]);
+
+        pending("no-store").store(200, headers(&[("cache-control", "no-store, max-age=60")]), b"").await;
+        pending("vary-star").store(200, headers(&[("cache-control", "max-age=60"), ("vary", "*")]), b"").await;
+        pending("redirect").store(302, headers(&[("etag", "\"a\"")]), b"").await;
+        pending("stale").store(200, headers(&[]), b"").await;
+        for key in ["no-store", "vary-star", "redirect", "stale"] {
+            assert!(get(key).await.is_none(), "{} should not be stored", key);
+        }
+
+        // 可以重新验证的响应即使不新鲜也保存
+        pending("etag").store(200, headers(&[("etag", "\"a\""), ("cache-control", "no-cache")]), b"").await;
+        assert!(get("etag").await.is_some());
+
+        // no-store 删除已有条目
+        pending("fresh").store(200, headers(&[("cache-control", "no-store")]), b"").await;
+        assert!(get("fresh").await.is_none());
+        *STORE.lock().unwrap() = None;
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use crate::request::RequestItem;
 use crate::request::executor::execute_single_request;
+use crate::request::ramp::{Ramp, RampUp};
+use crate::request::timeouts::with_deadline;
 use futures::future::join_all;
 use pyo3::{pyclass, pymethods};
 use reqwest::Client;
@@ -44,11 +46,25 @@ pub async fn execute_with_select_all(
     requests: Vec<RequestItem>,
     total_duration: Duration,
     base_client: Option<Client>,
+    ramp_up: Option<RampUp>,
 ) -> Vec<HashMap<String, String>> {
+    // 爬坡期间请求按名额依次发出，等待时间计入 total_timeout
+    let ramp = ramp_up.map(|plan| Ramp::start(&plan, requests.len()));
+    let permits = ramp.as_ref().map(Ramp::permits);
     let futures = requests.into_iter().map(|req| {
         let client = base_client.clone();
+        let permits = permits.clone();
         async move {
-            match tokio::time::timeout(total_duration, execute_single_request(req, client)).await {
+            let run = async move {
+                let _permit = match permits {
+                    Some(permits) => permits.acquire_owned().await.ok(),
+                    None => None,
+                };
+                execute_single_request(req, client).await
+            };
+            // 重试等待 Retry-After 时不超过 total_timeout 的剩余时间
+            let run = with_deadline(Instant::now() + total_duration, run);
+            match tokio::time::timeout(total_duration, run).await {
                 Ok(result) => result,
                 Err(_) => {
                     let mut timeout_result = HashMap::new();
@@ -70,7 +86,8 @@ pub async fn execute_with_join_all(
     let mut results = Vec::with_capacity(requests.len());
 
     for req in requests {
-        match tokio::time::timeout(total_duration, execute_single_request(req, base_client.clone())).await {
+        let run = with_deadline(Instant::now() + total_duration, execute_single_request(req, base_client.clone()));
+        match tokio::time::timeout(total_duration, run).await {
             Ok(result) => results.push(result),
             Err(_) => {
                 let mut timeout_result = HashMap::new();
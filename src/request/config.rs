@@ -1,11 +1,230 @@
 use pyo3::{pyfunction, PyAny, PyResult, Python};
-use crate::{ProxyConfig, GLOBAL_PROXY};
+use pyo3::exceptions::PyValueError;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use reqwest::header::HeaderValue;
+use crate::network::{CaBundle, ClientIdentity, HttpVersion, SslVerifyArg, TlsVersion};
+use crate::network::tls_version::check_tls_range;
+use crate::network::pinning::parse_pin;
+use crate::network::dns::{parse_resolve, IpVersion};
+use crate::network::local_address::check_local_address;
+use crate::request::limiter;
+use crate::request::timeouts::check_positive;
+use crate::request::limits::check_limit;
+use crate::request::oauth2::OAuth2ClientCredentials;
+use crate::{ProxyConfig, GLOBAL_CLIENT_CONFIG, GLOBAL_PROXY};
+
+/// 全局客户端配置，请求未单独指定时使用
+#[derive(Clone, Default)]
+pub struct GlobalClientConfig {
+    pub connect_timeout: Option<f64>,
+    pub read_timeout: Option<f64>,
+    pub allow_redirects: Option<bool>,
+    pub max_redirects: Option<usize>,
+    pub default_scheme: Option<String>,
+    pub bearer_token: Option<String>,
+    pub dns_timeout: Option<f64>,
+    pub oauth2: Option<OAuth2ClientCredentials>,
+    pub identity: Option<ClientIdentity>,
+    pub ca_bundle: Option<CaBundle>,
+    /// 主机名（小写）到 SPKI 指纹列表的映射
+    pub cert_pins: Option<HashMap<String, Vec<String>>>,
+    pub ssl_verify: Option<bool>,
+    pub tls_min_version: Option<TlsVersion>,
+    pub tls_max_version: Option<TlsVersion>,
+    /// 内容寻址存储目录
+    pub body_store: Option<String>,
+    /// 主机名（小写）到静态地址的映射
+    pub resolve: Option<BTreeMap<String, SocketAddr>>,
+    /// DNS 缓存有效期（秒），0 表示关闭
+    pub dns_cache_ttl: Option<f64>,
+    /// 缓存的客户端（连接池）数量上限，超出时淘汰最久未使用的
+    pub max_cached_clients: Option<usize>,
+    /// 请求未指定 timeout 时的默认值
+    pub timeout: Option<f64>,
+    pub user_agent: Option<String>,
+    pub http_version: Option<HttpVersion>,
+    /// Accept-Encoding 中声明的压缩格式，空列表表示只接受不压缩的响应
+    pub compression: Option<Vec<String>>,
+    /// 每个主机保留的空闲连接数和空闲连接的保留时间（秒）
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout: Option<f64>,
+    /// 地址族偏好：v4、v6 或 auto
+    pub ip_version: Option<String>,
+    /// 出站连接绑定的本地 IP 或网卡名
+    pub local_address: Option<String>,
+    /// 响应头数量和总字节数上限
+    pub max_response_headers: Option<usize>,
+    pub max_header_bytes: Option<usize>,
+    /// 响应体字节数上限
+    pub max_response_bytes: Option<usize>,
+    /// 超过该字节数的响应体写入临时文件
+    pub memory_threshold: Option<usize>,
+    /// 空闲连接保活探测间隔（秒）
+    pub keepalive_interval: Option<f64>,
+}
 
 #[pyfunction]
 pub fn set_global_proxy<'py>(py: Python<'py>, proxy: ProxyConfig) -> PyResult<&'py PyAny> {
     pyo3_asyncio::tokio::future_into_py(py, async move {
-        let mut global = GLOBAL_PROXY.lock().await;
-        *global = Some(proxy);
+        *GLOBAL_PROXY.write().unwrap() = Some(proxy);
+        Ok(())
+    })
+}
+
+/// 执行器能够解压的格式
+pub(crate) const SUPPORTED_COMPRESSION: [&str; 4] = ["gzip", "deflate", "br", "zstd"];
+
+/// 校验并规范化 compression 列表
+fn parse_compression(names: &[String]) -> PyResult<Vec<String>> {
+    names.iter().map(|name| {
+        let name = name.trim().to_lowercase();
+        if SUPPORTED_COMPRESSION.contains(&name.as_str()) {
+            Ok(name)
+        } else {
+            Err(PyValueError::new_err(format!(
+                "Invalid compression: '{}'. Valid values: {}", name, SUPPORTED_COMPRESSION.join(", ")
+            )))
+        }
+    }).collect()
+}
+
+/// 校验请求的 accept_encoding 和 decompress：关闭解压时可以声明任意编码，否则只能是支持解压的格式
+pub(crate) fn check_encoding(accept_encoding: Option<&[String]>, decompress: Option<bool>, compress_result: Option<&str>) -> PyResult<()> {
+    if decompress == Some(false) && compress_result.is_some_and(|c| !c.eq_ignore_ascii_case("received")) {
+        return Err(PyValueError::new_err("decompress=False cannot be combined with compress_result='gzip'"));
+    }
+    match accept_encoding {
+        Some(names) if decompress != Some(false) => parse_compression(names).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// 配置全局客户端，只更新传入的参数
+#[pyfunction]
+#[pyo3(signature = (connect_timeout=None, read_timeout=None, allow_redirects=None, max_redirects=None, default_scheme=None, bearer_token=None, dns_timeout=None, oauth2=None, identity=None, ca_bundle=None, cert_pins=None, max_in_flight=None, max_rps=None, ssl_verify=None, tls_min_version=None, tls_max_version=None, body_store=None, resolve=None, dns_cache_ttl=None, ip_version=None, local_address=None, max_response_headers=None, max_header_bytes=None, keepalive_interval=None, max_cached_clients=None, timeout=None, user_agent=None, http_version=None, proxy=None, compression=None, pool_max_idle_per_host=None, pool_idle_timeout=None, max_connections_per_host=None, scheduler=None, max_response_bytes=None, memory_threshold=None))]
+pub fn configure_global_client<'py>(
+    py: Python<'py>,
+    connect_timeout: Option<f64>,
+    read_timeout: Option<f64>,
+    allow_redirects: Option<bool>,
+    max_redirects: Option<usize>,
+    default_scheme: Option<String>,
+    bearer_token: Option<String>,
+    dns_timeout: Option<f64>,
+    oauth2: Option<OAuth2ClientCredentials>,
+    identity: Option<ClientIdentity>,
+    ca_bundle: Option<CaBundle>,
+    cert_pins: Option<HashMap<String, Vec<String>>>,
+    max_in_flight: Option<usize>,
+    max_rps: Option<f64>,
+    ssl_verify: Option<SslVerifyArg>,
+    tls_min_version: Option<TlsVersion>,
+    tls_max_version: Option<TlsVersion>,
+    body_store: Option<String>,
+    resolve: Option<HashMap<String, String>>,
+    dns_cache_ttl: Option<f64>,
+    ip_version: Option<String>,
+    local_address: Option<String>,
+    max_response_headers: Option<usize>,
+    max_header_bytes: Option<usize>,
+    keepalive_interval: Option<f64>,
+    max_cached_clients: Option<usize>,
+    timeout: Option<f64>,
+    user_agent: Option<String>,
+    http_version: Option<HttpVersion>,
+    proxy: Option<ProxyConfig>,
+    compression: Option<Vec<String>>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<f64>,
+    max_connections_per_host: Option<usize>,
+    scheduler: Option<String>,
+    max_response_bytes: Option<usize>,
+    memory_threshold: Option<usize>,
+) -> PyResult<&'py PyAny> {
+    if let Some(scheme) = &default_scheme {
+        if scheme != "http" && scheme != "https" {
+            return Err(PyValueError::new_err(format!("Invalid default_scheme: '{}'. Valid values: http, https", scheme)));
+        }
+    }
+
+    let cert_pins = match cert_pins {
+        Some(pins) => {
+            for pin in pins.values().flatten() {
+                parse_pin(pin).map_err(PyValueError::new_err)?;
+            }
+            Some(pins.into_iter().map(|(host, pins)| (host.to_lowercase(), pins)).collect())
+        }
+        None => None,
+    };
+    check_positive("connect_timeout", connect_timeout)?;
+    check_positive("read_timeout", read_timeout)?;
+    check_positive("dns_timeout", dns_timeout)?;
+    check_positive("keepalive_interval", keepalive_interval)?;
+    if dns_cache_ttl.is_some_and(|ttl| !ttl.is_finite() || ttl < 0.0) {
+        return Err(PyValueError::new_err("dns_cache_ttl must be >= 0 (0 disables the cache)"));
+    }
+    let ssl_verify = ssl_verify.map(|v| v.get());
+    let resolve = resolve.as_ref().map(parse_resolve).transpose()?;
+    check_tls_range(tls_min_version, tls_max_version)?;
+    if let Some(version) = &ip_version {
+        IpVersion::parse(version)?;
+    }
+    if let Some(local) = &local_address {
+        check_local_address(local)?;
+    }
+    check_limit("max_response_headers", max_response_headers)?;
+    check_limit("max_header_bytes", max_header_bytes)?;
+    check_limit("max_response_bytes", max_response_bytes)?;
+    check_limit("memory_threshold", memory_threshold)?;
+    check_limit("max_cached_clients", max_cached_clients)?;
+    check_positive("timeout", timeout)?;
+    check_positive("pool_idle_timeout", pool_idle_timeout)?;
+    if let Some(agent) = &user_agent {
+        HeaderValue::from_str(agent).map_err(|_| PyValueError::new_err(format!("Invalid user_agent: {:?}", agent)))?;
+    }
+    let compression = compression.map(|names| parse_compression(&names)).transpose()?;
+    if let Some(name) = &scheduler {
+        if !limiter::SCHEDULERS.contains(&name.as_str()) {
+            return Err(PyValueError::new_err(format!("Invalid scheduler: '{}'. Valid values: {}", name, limiter::SCHEDULERS.join(", "))));
+        }
+    }
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let mut global = GLOBAL_CLIENT_CONFIG.write().unwrap();
+        if connect_timeout.is_some() { global.connect_timeout = connect_timeout; }
+        if read_timeout.is_some() { global.read_timeout = read_timeout; }
+        if allow_redirects.is_some() { global.allow_redirects = allow_redirects; }
+        if max_redirects.is_some() { global.max_redirects = max_redirects; }
+        if default_scheme.is_some() { global.default_scheme = default_scheme; }
+        if bearer_token.is_some() { global.bearer_token = bearer_token; }
+        if dns_timeout.is_some() { global.dns_timeout = dns_timeout; }
+        if oauth2.is_some() { global.oauth2 = oauth2; }
+        if identity.is_some() { global.identity = identity; }
+        if ca_bundle.is_some() { global.ca_bundle = ca_bundle; }
+        if cert_pins.is_some() { global.cert_pins = cert_pins; }
+        if ssl_verify.is_some() { global.ssl_verify = ssl_verify; }
+        if tls_min_version.is_some() { global.tls_min_version = tls_min_version; }
+        if tls_max_version.is_some() { global.tls_max_version = tls_max_version; }
+        if body_store.is_some() { global.body_store = body_store; }
+        if resolve.is_some() { global.resolve = resolve; }
+        if dns_cache_ttl.is_some() { global.dns_cache_ttl = dns_cache_ttl; }
+        if ip_version.is_some() { global.ip_version = ip_version; }
+        if local_address.is_some() { global.local_address = local_address; }
+        if max_response_headers.is_some() { global.max_response_headers = max_response_headers; }
+        if max_header_bytes.is_some() { global.max_header_bytes = max_header_bytes; }
+        if max_response_bytes.is_some() { global.max_response_bytes = max_response_bytes; }
+        if memory_threshold.is_some() { global.memory_threshold = memory_threshold; }
+        if keepalive_interval.is_some() { global.keepalive_interval = keepalive_interval; }
+        if max_cached_clients.is_some() { global.max_cached_clients = max_cached_clients; }
+        if timeout.is_some() { global.timeout = timeout; }
+        if user_agent.is_some() { global.user_agent = user_agent; }
+        if http_version.is_some() { global.http_version = http_version; }
+        if compression.is_some() { global.compression = compression; }
+        if pool_max_idle_per_host.is_some() { global.pool_max_idle_per_host = pool_max_idle_per_host; }
+        if pool_idle_timeout.is_some() { global.pool_idle_timeout = pool_idle_timeout; }
+        if proxy.is_some() { *GLOBAL_PROXY.write().unwrap() = proxy; }
+        limiter::configure(max_in_flight, max_rps, max_connections_per_host, scheduler.as_deref());
         Ok(())
     })
 }
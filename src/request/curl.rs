@@ -0,0 +1,544 @@
+use std::collections::HashMap;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use url::form_urlencoded;
+use crate::network::{HttpVersion, ProxyConfig};
+use crate::request::{PreparedRequest, RequestItem};
+use crate::request::serializer::{has_serializer, mime_of};
+use crate::utils::json_to_py;
+use crate::utils::decode::utf8_chunks;
+
+// 不影响请求内容、解析时忽略的选项
+const IGNORED_FLAGS: &[&str] = &[
+    "-s", "--silent", "-S", "--show-error", "-v", "--verbose", "-i", "--include", "--compressed",
+    "-f", "--fail", "-g", "--globoff", "-N", "--no-buffer", "-#", "--progress-bar", "--path-as-is",
+];
+const IGNORED_OPTIONS: &[&str] = &["-o", "--output", "-w", "--write-out", "--retry", "--stderr", "-c", "--cookie-jar"];
+
+/// 按 shell 规则拆分命令：支持 '...'、"..."、$'...'、反斜杠转义和续行
+fn split_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                // 行尾的反斜杠是续行
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => { chars.next(); }
+                Some(escaped) => word.get_or_insert_with(String::new).push(escaped),
+                None => return Err("trailing backslash".to_string()),
+            },
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => w.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => w.push(c),
+                            Some('\n') => {}
+                            Some(c) => { w.push('\\'); w.push(c); }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => w.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let w = word.get_or_insert_with(String::new);
+                ansi_c_quoted(&mut chars, w)?;
+            }
+            c if c.is_whitespace() => {
+                if let Some(w) = word.take() {
+                    words.push(w);
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// $'...'：浏览器复制含特殊字符的请求体时使用
+fn ansi_c_quoted(chars: &mut std::iter::Peekable<std::str::Chars>, w: &mut String) -> Result<(), String> {
+    let take_hex = |chars: &mut std::iter::Peekable<std::str::Chars>, max: usize| {
+        let mut digits = String::new();
+        while digits.len() < max && chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            digits.push(chars.next().unwrap());
+        }
+        u32::from_str_radix(&digits, 16).ok()
+    };
+    // \xHH 可能组成多字节 UTF-8 字符，先收集字节
+    let mut bytes = Vec::new();
+    loop {
+        match chars.next() {
+            Some('\'') => break,
+            Some('\\') => match chars.next() {
+                Some('n') => bytes.push(b'\n'),
+                Some('t') => bytes.push(b'\t'),
+                Some('r') => bytes.push(b'\r'),
+                Some('0') => bytes.push(0),
+                Some('x') => bytes.push(take_hex(chars, 2).ok_or("invalid \\x escape")? as u8),
+                Some(u @ ('u' | 'U')) => {
+                    let code = take_hex(chars, if u == 'u' { 4 } else { 8 }).and_then(char::from_u32)
+                        .ok_or("invalid \\u escape")?;
+                    bytes.extend_from_slice(code.to_string().as_bytes());
+                }
+                Some(c) => bytes.extend_from_slice(c.to_string().as_bytes()),
+                None => return Err("unterminated $' quote".to_string()),
+            },
+            Some(c) => bytes.extend_from_slice(c.to_string().as_bytes()),
+            None => return Err("unterminated $' quote".to_string()),
+        }
+    }
+    w.push_str(&String::from_utf8_lossy(&bytes));
+    Ok(())
+}
+
+/// 从命令中解析出的请求
+#[derive(Default)]
+struct Parsed {
+    url: Option<String>,
+    method: Option<String>,
+    headers: Vec<(String, String)>,
+    data: Vec<String>,
+    json: bool,
+    get: bool,
+    head: bool,
+    user: Option<String>,
+    proxy: Option<String>,
+    proxy_user: Option<String>,
+    insecure: bool,
+    location: bool,
+    max_redirects: Option<usize>,
+    timeout: Option<f64>,
+    connect_timeout: Option<f64>,
+    http_version: Option<HttpVersion>,
+    unix_socket: Option<String>,
+    resolve: HashMap<String, String>,
+    ip_version: Option<String>,
+}
+
+fn takes_value(option: &str) -> bool {
+    matches!(option,
+        "-X" | "--request" | "-H" | "--header" | "-d" | "--data" | "--data-raw" | "--data-binary"
+        | "--data-ascii" | "--data-urlencode" | "--json" | "-u" | "--user" | "-x" | "--proxy"
+        | "-U" | "--proxy-user" | "-m" | "--max-time" | "--connect-timeout" | "-A" | "--user-agent"
+        | "-e" | "--referer" | "-b" | "--cookie" | "--url" | "--max-redirs" | "--unix-socket"
+        | "--resolve" | "-F" | "--form" | "-T" | "--upload-file"
+    ) || IGNORED_OPTIONS.contains(&option)
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value for {}: '{}'", option, value))
+}
+
+/// -d @file 读取文件内容；-d 和 --data-ascii 去掉换行，与 curl 一致
+fn read_data(option: &str, value: &str) -> Result<String, String> {
+    let Some(path) = value.strip_prefix('@').filter(|_| option != "--data-raw") else {
+        return Ok(value.to_string());
+    };
+    let content = std::fs::read_to_string(path).map_err(|e| format!("cannot read {} file '{}': {}", option, path, e))?;
+    Ok(match option {
+        "-d" | "--data" | "--data-ascii" => content.replace(['\r', '\n'], ""),
+        _ => content,
+    })
+}
+
+/// --data-urlencode 的 "content"、"=content"、"name=content" 形式
+fn urlencode_data(value: &str) -> String {
+    let encode = |s: &str| form_urlencoded::byte_serialize(s.as_bytes()).collect::<String>();
+    match value.split_once('=') {
+        Some(("", content)) => encode(content),
+        Some((name, content)) => format!("{}={}", name, encode(content)),
+        None => encode(value),
+    }
+}
+
+impl Parsed {
+    fn apply(&mut self, option: &str, value: Option<String>) -> Result<(), String> {
+        let value = value.unwrap_or_default();
+        match option {
+            "-X" | "--request" => self.method = Some(value.to_uppercase()),
+            "-H" | "--header" => {
+                // "Name;" 表示空值，"Name:" 在 curl 中是删除请求头
+                if let Some(name) = value.strip_suffix(';').filter(|n| !n.contains(':')) {
+                    self.headers.push((name.trim().to_string(), String::new()));
+                } else {
+                    let (name, v) = value.split_once(':').ok_or_else(|| format!("invalid header: '{}'", value))?;
+                    if !v.trim().is_empty() {
+                        self.headers.push((name.trim().to_string(), v.trim().to_string()));
+                    }
+                }
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => self.data.push(read_data(option, &value)?),
+            "--data-urlencode" => self.data.push(urlencode_data(&value)),
+            "--json" => {
+                self.data.push(read_data(option, &value)?);
+                self.json = true;
+            }
+            "-u" | "--user" => self.user = Some(value),
+            "-x" | "--proxy" => self.proxy = Some(value),
+            "-U" | "--proxy-user" => self.proxy_user = Some(value),
+            "-m" | "--max-time" => self.timeout = Some(parse_number(option, &value)?),
+            "--connect-timeout" => self.connect_timeout = Some(parse_number(option, &value)?),
+            "--max-redirs" => self.max_redirects = Some(parse_number(option, &value)?),
+            "-A" | "--user-agent" => self.headers.push(("User-Agent".to_string(), value)),
+            "-e" | "--referer" => self.headers.push(("Referer".to_string(), value)),
+            "-b" | "--cookie" => {
+                if !value.contains('=') {
+                    return Err(format!("reading cookies from a file is not supported: '{}'", value));
+                }
+                self.headers.push(("Cookie".to_string(), value));
+            }
+            "--url" => self.set_url(value)?,
+            "--unix-socket" => self.unix_socket = Some(value),
+            "--resolve" => {
+                // host:port:addr，端口由 URL 决定
+                let mut parts = value.splitn(3, ':');
+                let (Some(host), Some(_), Some(addr)) = (parts.next(), parts.next(), parts.next()) else {
+                    return Err(format!("invalid value for --resolve: '{}'", value));
+                };
+                self.resolve.insert(host.to_string(), addr.trim_matches(['[', ']']).to_string());
+            }
+            "-k" | "--insecure" => self.insecure = true,
+            "-L" | "--location" => self.location = true,
+            "-G" | "--get" => self.get = true,
+            "-I" | "--head" => self.head = true,
+            "--http1.0" | "--http1.1" => self.http_version = Some(HttpVersion::Http1Only),
+            "--http2" => self.http_version = Some(HttpVersion::Http2),
+            "--http2-prior-knowledge" => self.http_version = Some(HttpVersion::Http2PriorKnowledge),
+            "-4" | "--ipv4" => self.ip_version = Some("v4".to_string()),
+            "-6" | "--ipv6" => self.ip_version = Some("v6".to_string()),
+            "-F" | "--form" | "-T" | "--upload-file" => return Err(format!("{} is not supported", option)),
+            _ if IGNORED_FLAGS.contains(&option) || IGNORED_OPTIONS.contains(&option) => {}
+            _ => return Err(format!("unsupported option: {}", option)),
+        }
+        Ok(())
+    }
+
+    fn set_url(&mut self, url: String) -> Result<(), String> {
+        if self.url.is_some() {
+            return Err("only one URL is supported".to_string());
+        }
+        self.url = Some(url);
+        Ok(())
+    }
+
+    fn parse(command: &str) -> Result<Self, String> {
+        let words = split_words(command)?;
+        let mut words = words.into_iter();
+        let program = words.next().unwrap_or_default();
+        let name = program.rsplit(['/', '\\']).next().unwrap_or_default();
+        if name != "curl" && name != "curl.exe" {
+            return Err("command must start with curl".to_string());
+        }
+        let mut parsed = Parsed::default();
+        while let Some(word) = words.next() {
+            if let Some(long) = word.strip_prefix("--").filter(|l| !l.is_empty()) {
+                let option = format!("--{}", long);
+                let value = if takes_value(&option) {
+                    Some(words.next().ok_or_else(|| format!("{} requires a value", option))?)
+                } else {
+                    None
+                };
+                parsed.apply(&option, value)?;
+            } else if word.len() > 1 && word.starts_with('-') {
+                // 短选项可以合并（-sSL），取值可以紧跟（-XPOST）
+                let cluster: Vec<char> = word[1..].chars().collect();
+                for (i, c) in cluster.iter().enumerate() {
+                    let option = format!("-{}", c);
+                    if takes_value(&option) {
+                        let rest: String = cluster[i + 1..].iter().collect();
+                        let value = if rest.is_empty() {
+                            words.next().ok_or_else(|| format!("{} requires a value", option))?
+                        } else {
+                            rest
+                        };
+                        parsed.apply(&option, Some(value))?;
+                        break;
+                    }
+                    parsed.apply(&option, None)?;
+                }
+            } else {
+                parsed.set_url(word)?;
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+/// 解析 curl 命令，生成 RequestItem
+///
+/// 请求体需要是 JSON 对象，或已注册序列化函数的表单（application/x-www-form-urlencoded）。
+pub(crate) fn from_curl(py: Python, command: &str) -> PyResult<RequestItem> {
+    let invalid = |message: String| PyValueError::new_err(format!("Invalid curl command: {}", message));
+    let mut parsed = Parsed::parse(command).map_err(invalid)?;
+    let mut url = parsed.url.take().ok_or_else(|| invalid("missing URL".to_string()))?;
+
+    let data = (!parsed.data.is_empty()).then(|| parsed.data.join("&"));
+    let method = match &parsed.method {
+        Some(method) => method.clone(),
+        None if parsed.head => "HEAD".to_string(),
+        None if data.is_some() && !parsed.get => "POST".to_string(),
+        None => "GET".to_string(),
+    };
+    if parsed.json {
+        for (name, value) in [("Content-Type", "application/json"), ("Accept", "application/json")] {
+            if parsed.header(name).is_none() {
+                parsed.headers.push((name.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    let mut params = None;
+    if let Some(data) = data {
+        if parsed.get {
+            // -G 把数据作为查询参数
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, separator, data);
+        } else {
+            let content_type = parsed.header("content-type").unwrap_or("application/x-www-form-urlencoded").to_string();
+            let mime = mime_of(&content_type);
+            let dict = if mime.ends_with("json") {
+                let value: serde_json::Value = serde_json::from_str(&data)
+                    .map_err(|e| invalid(format!("request body is not valid JSON: {}", e)))?;
+                if !value.is_object() {
+                    return Err(invalid("only JSON object request bodies are supported".to_string()));
+                }
+                json_to_py(py, &value)?.extract::<Py<PyDict>>(py)?
+            } else if mime == "application/x-www-form-urlencoded" && has_serializer(&mime) {
+                let dict = PyDict::new(py);
+                for (k, v) in form_urlencoded::parse(data.as_bytes()) {
+                    dict.set_item(k.as_ref(), v.as_ref())?;
+                }
+                dict.into()
+            } else {
+                return Err(invalid(format!(
+                    "cannot convert a '{}' request body to params; register_serializer('{}', ...) for form bodies",
+                    mime, mime,
+                )));
+            };
+            if parsed.header("content-type").is_none() {
+                parsed.headers.push(("Content-Type".to_string(), content_type));
+            }
+            params = Some(dict);
+        }
+    }
+
+    let headers = PyDict::new(py);
+    for (name, value) in &parsed.headers {
+        headers.set_item(name, value)?;
+    }
+    let auth = parsed.user.map(|user| match user.split_once(':') {
+        Some((name, password)) => (name.to_string(), password.to_string()),
+        None => (user, String::new()),
+    });
+    let proxy = parsed.proxy.map(|proxy| {
+        // 没有协议时 curl 按 HTTP 代理处理
+        let all = if proxy.contains("://") { proxy } else { format!("http://{}", proxy) };
+        let (username, password) = match parsed.proxy_user.as_deref().map(|u| u.split_once(':').unwrap_or((u, ""))) {
+            Some((u, p)) => (Some(u.to_string()), Some(p.to_string())),
+            None => (None, None),
+        };
+        ProxyConfig { all: Some(all), username, password, ..Default::default() }
+    });
+
+    Ok(RequestItem {
+        url,
+        method: Some(method),
+        params,
+        headers: (!headers.is_empty()).then(|| headers.into()),
+        timeout: parsed.timeout,
+        connect_timeout: parsed.connect_timeout,
+        proxy,
+        http_version: parsed.http_version,
+        ssl_verify: parsed.insecure.then_some(false),
+        allow_redirects: parsed.location.then_some(true),
+        max_redirects: parsed.max_redirects,
+        auth,
+        unix_socket: parsed.unix_socket,
+        resolve: (!parsed.resolve.is_empty()).then_some(parsed.resolve),
+        ip_version: parsed.ip_version,
+        ..Default::default()
+    })
+}
+
+/// 按 shell 规则引用参数；含控制字符或非 UTF-8 字节时使用 $'...'
+fn quote(bytes: &[u8]) -> String {
+    let plain = |b: &u8| b.is_ascii_alphanumeric() || b"-_./:=@,+%".contains(b);
+    if !bytes.is_empty() && bytes.iter().all(plain) {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.chars().any(|c| c.is_control()) => format!("'{}'", text.replace('\'', r"'\''")),
+        _ => {
+            let mut quoted = String::from("$'");
+            for (valid, invalid) in utf8_chunks(bytes) {
+                for c in valid.chars() {
+                    match c {
+                        '\n' => quoted.push_str("\\n"),
+                        '\r' => quoted.push_str("\\r"),
+                        '\t' => quoted.push_str("\\t"),
+                        '\\' | '\'' => { quoted.push('\\'); quoted.push(c); }
+                        c if c.is_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+                        c => quoted.push(c),
+                    }
+                }
+                for b in invalid {
+                    quoted.push_str(&format!("\\x{:02x}", b));
+                }
+            }
+            quoted.push('\'');
+            quoted
+        }
+    }
+}
+
+/// 生成等价的 curl 命令，包含默认请求头和主机配置补全后的内容
+///
+/// oauth2 的令牌在发送时获取，不包含在命令中。
+pub(crate) fn to_curl(prepared: &PreparedRequest) -> String {
+    let req = &prepared.request;
+    let mut args = vec!["curl".to_string()];
+    let mut push = |option: &str, value: Option<&str>| {
+        args.push(option.to_string());
+        if let Some(value) = value {
+            args.push(quote(value.as_bytes()));
+        }
+    };
+    let implied = if prepared.body.is_some() { "POST" } else { "GET" };
+    if prepared.method == "HEAD" {
+        push("-I", None);
+    } else if prepared.method != implied {
+        push("-X", Some(&prepared.method));
+    }
+    for (name, value) in &prepared.headers {
+        push("-H", Some(&format!("{}: {}", name, value)));
+    }
+    if let Some(token) = &req.bearer_token {
+        if !prepared.headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("authorization")) {
+            push("-H", Some(&format!("Authorization: Bearer {}", token)));
+        }
+    }
+    if let Some((username, password)) = &req.auth {
+        push("-u", Some(&format!("{}:{}", username, password)));
+    }
+    if let Some(proxy) = &req.proxy {
+        let scheme_proxy = if prepared.url.starts_with("https:") { &proxy.https } else { &proxy.http };
+        if let Some(url) = proxy.all.as_ref().or(scheme_proxy.as_ref()) {
+            push("-x", Some(url));
+            if let Some(username) = &proxy.username {
+                push("-U", Some(&format!("{}:{}", username, proxy.password.as_deref().unwrap_or_default())));
+            }
+        }
+    }
+    match req.http_version {
+        Some(HttpVersion::Http1Only) => push("--http1.1", None),
+        Some(HttpVersion::Http2) => push("--http2", None),
+        Some(HttpVersion::Http2PriorKnowledge) => push("--http2-prior-knowledge", None),
+        _ => {}
+    }
+    if req.ssl_verify == Some(false) {
+        push("-k", None);
+    }
+    // 客户端默认跟随重定向，curl 默认不跟随
+    if req.allow_redirects != Some(false) {
+        push("-L", None);
+        if let Some(max) = req.max_redirects {
+            push("--max-redirs", Some(&max.to_string()));
+        }
+    }
+    if let Some(timeout) = req.timeout {
+        push("-m", Some(&timeout.to_string()));
+    }
+    if let Some(timeout) = req.connect_timeout {
+        push("--connect-timeout", Some(&timeout.to_string()));
+    }
+    if let Some(path) = &req.unix_socket {
+        push("--unix-socket", Some(path));
+    }
+    // 客户端自动解压响应
+    push("--compressed", None);
+    if let Some(body) = &prepared.body {
+        // --data-raw 不把开头的 @ 当作文件名
+        args.push("--data-raw".to_string());
+        args.push(quote(body));
+    }
+    args.push(quote(prepared.url.as_bytes()));
+    args.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_shell_quoting() {
+        let words = split_words(r#"curl 'a b' "c \"d\" $HOME" e\ f $'g\nh\x41' \
+            'it'\''s'"#).unwrap();
+        assert_eq!(words, ["curl", "a b", r#"c "d" $HOME"#, "e f", "g\nhA", "it's"]);
+        assert!(split_words("curl 'open").is_err());
+        assert!(split_words("curl \"open").is_err());
+        assert!(split_words("curl $'open").is_err());
+    }
+
+    #[test]
+    fn quote_round_trips() {
+        for arg in ["plain-arg", "", "a b", "it's", "line\nbreak\t", "caf\u{e9} \"x\""] {
+            let words = split_words(&format!("curl {}", quote(arg.as_bytes()))).unwrap();
+            assert_eq!(words[1], arg);
+        }
+        assert_eq!(quote(b"https://example.com/a?b=1"), "'https://example.com/a?b=1'");
+        assert_eq!(quote(b"\xff\n"), r"$'\xff\n'");
+    }
+
+    #[test]
+    fn parses_common_options() {
+        let parsed = Parsed::parse(
+            "curl -XPOST https://example.com/api -H 'Content-Type: application/json' -H 'X-Empty;' \
+             -d '{\"a\":1}' --data-binary 'b=2' -u user:secret -sSL --compressed",
+        ).unwrap();
+        assert_eq!(parsed.url.as_deref(), Some("https://example.com/api"));
+        assert_eq!(parsed.method.as_deref(), Some("POST"));
+        assert_eq!(parsed.header("content-type"), Some("application/json"));
+        assert_eq!(parsed.header("X-Empty"), Some(""));
+        assert_eq!(parsed.data, ["{\"a\":1}", "b=2"]);
+        assert_eq!(parsed.user.as_deref(), Some("user:secret"));
+        assert!(parsed.location);
+
+        let parsed = Parsed::parse("curl --request put --url http://h/ --data-urlencode 'q=a b'").unwrap();
+        assert_eq!(parsed.method.as_deref(), Some("PUT"));
+        assert_eq!(parsed.data, ["q=a+b"]);
+    }
+
+    #[test]
+    fn rejects_unsupported_input() {
+        let error = |command: &str| Parsed::parse(command).err().unwrap_or_default();
+        assert_eq!(error("curl --frobnicate http://h/"), "unsupported option: --frobnicate");
+        assert_eq!(error("curl -Z http://h/"), "unsupported option: -Z");
+        assert_eq!(error("curl -F a=b http://h/"), "-F is not supported");
+        assert_eq!(error("curl http://h/ -H"), "-H requires a value");
+        assert_eq!(error("curl http://a/ http://b/"), "only one URL is supported");
+        assert_eq!(error("wget http://h/"), "command must start with curl");
+        assert_eq!(error("curl -b cookies.txt http://h/"), "reading cookies from a file is not supported: 'cookies.txt'");
+    }
+}
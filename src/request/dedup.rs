@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, HashMap};
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+use crate::network::{CaBundle, ClientIdentity, ProxyConfig};
+use crate::request::{PreparedRequest, RequestItem};
+use crate::utils::{normalize_url, py_to_json};
+
+/// 去重键：发送的请求，以及其余所有影响响应或结果形态的参数（tag 除外）
+///
+/// 只有除 tag 外完全相同的请求才会合并。
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+    proxy: Option<ProxyConfig>,
+    identity: Option<ClientIdentity>,
+    ca_bundle: Option<CaBundle>,
+    /// 其余参数的 JSON
+    options: String,
+}
+
+fn sorted(map: &Option<HashMap<String, String>>) -> Option<BTreeMap<&String, &String>> {
+    map.as_ref().map(|m| m.iter().collect())
+}
+
+/// 请求中除 method / url / params / headers / tag 外的参数
+///
+/// 解构不使用 ..，RequestItem 新增字段时必须在这里决定是否参与去重。
+fn options(py: Python, req: &RequestItem) -> Option<String> {
+    let RequestItem {
+        url: _, method: _, params: _, headers: _, tag: _,
+        // 在 Key 中单独比较
+        proxy: _, identity: _, ca_bundle: _,
+        timeout, http_version, ssl_verify, connect_timeout, read_timeout, allow_redirects, max_redirects,
+        normalize, auth, bearer_token, decode_errors, dns_timeout, oauth2, compress_result,
+        tls_min_version, tls_max_version, impersonate, fingerprint, assert_json, assert_contains, assert_header,
+        unix_socket, body_store, resolve, ip_version, local_address, isolated, max_response_headers, max_header_bytes,
+        keepalive_interval, cache, transform, xml, parse_xml, protobuf, grpc_web, max_response_bytes, accept_encoding,
+        decompress, compress_body, encoding, raw_body, memory_threshold, conditional, retries, retry_policy, hedge_after,
+    } = req;
+    let assert_json = match assert_json {
+        Some(expected) => Some(py_to_json(py, expected.as_ref(py)).ok()?),
+        None => None,
+    };
+    let fields: Vec<(&str, Value)> = vec![
+        ("timeout", json!(timeout)),
+        ("http_version", json!(http_version.as_ref().map(|v| format!("{:?}", v)))),
+        ("ssl_verify", json!(ssl_verify)),
+        ("connect_timeout", json!(connect_timeout)),
+        ("read_timeout", json!(read_timeout)),
+        ("allow_redirects", json!(allow_redirects)),
+        ("max_redirects", json!(max_redirects)),
+        ("normalize", json!(normalize)),
+        ("auth", json!(auth)),
+        ("bearer_token", json!(bearer_token)),
+        ("decode_errors", json!(decode_errors)),
+        ("dns_timeout", json!(dns_timeout)),
+        ("oauth2", json!(oauth2.as_ref().map(|o| o.fingerprint()))),
+        ("compress_result", json!(compress_result)),
+        ("tls_min_version", json!(tls_min_version.as_ref().map(|v| format!("{:?}", v)))),
+        ("tls_max_version", json!(tls_max_version.as_ref().map(|v| format!("{:?}", v)))),
+        ("impersonate", json!(impersonate)),
+        ("fingerprint", json!(fingerprint)),
+        ("assert_json", json!(assert_json)),
+        ("assert_contains", json!(assert_contains)),
+        ("assert_header", json!(sorted(assert_header))),
+        ("unix_socket", json!(unix_socket)),
+        ("body_store", json!(body_store)),
+        ("resolve", json!(sorted(resolve))),
+        ("ip_version", json!(ip_version)),
+        ("local_address", json!(local_address)),
+        ("isolated", json!(isolated)),
+        ("max_response_headers", json!(max_response_headers)),
+        ("max_header_bytes", json!(max_header_bytes)),
+        ("keepalive_interval", json!(keepalive_interval)),
+        ("cache", json!(cache)),
+        ("transform", json!(transform)),
+        ("xml", json!(xml)),
+        ("parse_xml", json!(parse_xml)),
+        ("protobuf", json!(protobuf.as_ref().map(|p| p.as_ref(py).as_bytes().to_vec()))),
+        ("grpc_web", json!(grpc_web)),
+        ("max_response_bytes", json!(max_response_bytes)),
+        ("accept_encoding", json!(accept_encoding)),
+        ("decompress", json!(decompress)),
+        ("compress_body", json!(compress_body)),
+        ("encoding", json!(encoding)),
+        ("raw_body", json!(raw_body)),
+        ("memory_threshold", json!(memory_threshold)),
+        ("conditional", json!(conditional)),
+        ("retries", json!(retries)),
+        ("retry_policy", json!(retry_policy.as_ref().map(|v| format!("{:?}", v)))),
+        ("hedge_after", json!(hedge_after)),
+    ];
+    let options: Map<String, Value> = fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+    Some(Value::Object(options).to_string())
+}
+
+/// 批次中相同 GET / HEAD 请求的合并计划：每组只发送第一个请求，其余共享它的结果
+pub(crate) struct Plan {
+    /// 每个原始请求对应的实际发送请求的序号
+    slots: Vec<usize>,
+    /// 是否为组内第一个（实际发送的）请求
+    leaders: Vec<bool>,
+    tags: Vec<Option<String>>,
+}
+
+fn key(py: Python, req: &RequestItem, default_scheme: &str) -> Option<Key> {
+    let url = normalize_url(&req.url, default_scheme).ok()?;
+    // 无法构造的请求照常执行，由执行阶段返回错误
+    let prepared = PreparedRequest::from_item(py, req, url).ok()?;
+    if prepared.method != "GET" && prepared.method != "HEAD" {
+        return None;
+    }
+    let mut headers: Vec<(String, String)> = prepared.headers.iter()
+        .map(|(k, v)| (k.to_ascii_lowercase(), v.clone()))
+        .collect();
+    headers.sort();
+    Some(Key {
+        method: prepared.method,
+        url: prepared.url,
+        headers,
+        body: prepared.body,
+        proxy: req.proxy.clone(),
+        identity: req.identity.clone(),
+        ca_bundle: req.ca_bundle.clone(),
+        options: options(py, req)?,
+    })
+}
+
+impl Plan {
+    pub(crate) fn new(py: Python, requests: &[RequestItem], default_scheme: &str) -> Self {
+        let mut seen: HashMap<Key, usize> = HashMap::new();
+        let mut plan = Plan { slots: Vec::with_capacity(requests.len()), leaders: Vec::with_capacity(requests.len()), tags: Vec::new() };
+        let mut sent = 0;
+        for req in requests {
+            let existing = key(py, req, default_scheme).and_then(|key| match seen.get(&key) {
+                Some(slot) => Some(*slot),
+                None => {
+                    seen.insert(key, sent);
+                    None
+                }
+            });
+            match existing {
+                Some(slot) => {
+                    plan.slots.push(slot);
+                    plan.leaders.push(false);
+                }
+                None => {
+                    plan.slots.push(sent);
+                    plan.leaders.push(true);
+                    sent += 1;
+                }
+            }
+            plan.tags.push(req.tag.clone());
+        }
+        plan
+    }
+
+    /// 实际需要发送的请求
+    pub(crate) fn unique(&self, requests: Vec<RequestItem>) -> Vec<RequestItem> {
+        requests.into_iter().zip(&self.leaders).filter(|(_, leader)| **leader).map(|(req, _)| req).collect()
+    }
+
+    /// 按原始顺序展开结果，共享结果的请求在 meta 中标记 deduplicated 并使用自己的 tag
+    pub(crate) fn expand(&self, results: Vec<HashMap<String, String>>) -> Vec<HashMap<String, String>> {
+        self.slots.iter().zip(&self.leaders).zip(&self.tags).map(|((slot, leader), tag)| {
+            let mut result = results.get(*slot).cloned().unwrap_or_default();
+            if *leader {
+                return result;
+            }
+            if let Some(Value::Object(mut meta)) = result.get("meta").and_then(|m| serde_json::from_str(m).ok()) {
+                meta.insert("deduplicated".to_string(), Value::Bool(true));
+                match tag {
+                    Some(tag) => meta.insert("tag".to_string(), Value::String(tag.clone())),
+                    None => meta.remove("tag"),
+                };
+                result.insert("meta".to_string(), Value::Object(meta).to_string());
+            }
+            result
+        }).collect()
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use reqwest::header::{HeaderName, HeaderValue};
+
+type Headers = Arc<Vec<(String, String)>>;
+
+// 全局默认请求头，请求之间共享，不复制到每个 RequestItem
+static DEFAULT_HEADERS: Lazy<RwLock<Headers>> = Lazy::new(|| RwLock::new(Arc::new(Vec::new())));
+
+tokio::task_local! {
+    // fetch_requests(default_headers=...) 在本批请求内生效
+    static BATCH_HEADERS: Headers;
+}
+
+/// 设置所有请求的默认请求头，请求中的同名请求头优先；None 或空 dict 清除
+#[pyfunction]
+#[pyo3(signature = (headers=None))]
+pub fn set_default_headers(headers: Option<HashMap<String, String>>) -> PyResult<()> {
+    let headers = parse_headers(headers.unwrap_or_default())?;
+    *DEFAULT_HEADERS.write().unwrap() = Arc::new(headers);
+    Ok(())
+}
+
+/// 校验请求头名称和取值
+pub(crate) fn parse_headers(headers: HashMap<String, String>) -> PyResult<Vec<(String, String)>> {
+    headers.into_iter().map(|(name, value)| {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(PyValueError::new_err(format!("Invalid header name: {:?}", name)));
+        }
+        if HeaderValue::from_str(&value).is_err() {
+            return Err(PyValueError::new_err(format!("Invalid value for header '{}'", name)));
+        }
+        Ok((name, value))
+    }).collect()
+}
+
+/// 在 fut 执行期间使用批量默认请求头，优先于全局默认值
+pub(crate) async fn scope<F: Future>(headers: Option<Vec<(String, String)>>, fut: F) -> F::Output {
+    match headers {
+        Some(headers) => BATCH_HEADERS.scope(Arc::new(headers), fut).await,
+        None => fut.await,
+    }
+}
+
+/// 补全请求中没有的默认请求头
+///
+/// 请求自带认证（auth、bearer_token、oauth2）时不添加默认的 Authorization。
+pub(crate) fn merge(headers: &mut Vec<(String, String)>, has_auth: bool) {
+    let batch = BATCH_HEADERS.try_with(|h| h.clone()).ok();
+    let global = DEFAULT_HEADERS.read().unwrap().clone();
+    for (name, value) in batch.iter().flat_map(|h| h.iter()).chain(global.iter()) {
+        if has_auth && name.eq_ignore_ascii_case("authorization") {
+            continue;
+        }
+        if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name)) {
+            headers.push((name.clone(), value.clone()));
+        }
+    }
+}
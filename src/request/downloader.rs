@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use futures::future::try_join_all;
+use hyper::body::Bytes;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyConnectionError, PyException, PyIOError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use crate::network::client::get_or_create_client;
+use crate::network::ProxyConfig;
+use crate::request::executor::client_options;
+use crate::request::timeouts::{check_positive, NO_TOTAL_TIMEOUT};
+use crate::request::{RequestItem, RetryPolicy};
+use crate::testing::clock;
+use crate::utils::{normalize_url, Checksum, Hasher};
+use crate::GLOBAL_CLIENT_CONFIG;
+
+create_exception!(rusty_req, ChecksumMismatch, PyException, "下载内容的摘要与期望值不一致");
+
+const DEFAULT_SEGMENTS: usize = 4;
+// 小于该大小的分段不值得单独建立连接
+const MIN_SEGMENT_BYTES: u64 = 1024 * 1024;
+
+/// 下载失败的原因；文件错误和资源变化不重试
+enum DownloadError {
+    Timeout(String),
+    Connection(String),
+    Io(std::io::Error),
+    /// If-Range 不匹配，服务端返回了整个新文件，已下载的部分不可用
+    Changed,
+}
+
+impl From<std::io::Error> for DownloadError {
+    fn from(e: std::io::Error) -> Self {
+        DownloadError::Io(e)
+    }
+}
+
+impl From<DownloadError> for PyErr {
+    fn from(e: DownloadError) -> Self {
+        match e {
+            DownloadError::Timeout(msg) => PyTimeoutError::new_err(msg),
+            DownloadError::Connection(msg) => PyConnectionError::new_err(msg),
+            DownloadError::Io(e) => PyIOError::new_err(e.to_string()),
+            DownloadError::Changed => PyConnectionError::new_err("The file changed on the server during the download"),
+        }
+    }
+}
+
+/// 一次下载共用的客户端和请求选项
+struct Source {
+    client: Client,
+    url: String,
+    headers: Vec<(String, String)>,
+    bearer_token: Option<String>,
+    timeout: Duration,
+    read_timeout: Option<Duration>,
+    /// 探测到的 ETag 或 Last-Modified，分段请求带上 If-Range
+    if_range: Option<String>,
+}
+
+/// HEAD 探测的结果
+struct Probe {
+    /// 支持 Range 时的文件大小
+    size: Option<u64>,
+    /// 可用于 If-Range 的校验值：强 ETag，否则 Last-Modified
+    validator: Option<String>,
+}
+
+/// 一段下载：offset 为下一个要写入的字节，end 为最后一个字节（含）
+struct Segment {
+    offset: AtomicU64,
+    end: u64,
+}
+
+impl Segment {
+    fn new(offset: u64, end: u64) -> Self {
+        Segment { offset: AtomicU64::new(offset), end }
+    }
+
+    fn remaining(&self) -> u64 {
+        (self.end + 1).saturating_sub(self.offset.load(Ordering::Relaxed))
+    }
+}
+
+impl Source {
+    /// timeout 只限制等待响应头的时间，读取响应体由 read_timeout 限制
+    fn request(&self, method: reqwest::Method) -> RequestBuilder {
+        let mut builder = self.client.request(method, &self.url).timeout(NO_TOTAL_TIMEOUT);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(token) = &self.bearer_token {
+            builder = builder.bearer_auth(token);
+        }
+        builder
+    }
+
+    async fn send(&self, builder: RequestBuilder) -> Result<Response, DownloadError> {
+        tokio::time::timeout(self.timeout, builder.send()).await
+            .map_err(|_| DownloadError::Timeout(format!("No response within {:.2} seconds", self.timeout.as_secs_f64())))?
+            .map_err(|e| DownloadError::Connection(format!("Request error: {}", e)))
+    }
+
+    async fn chunk(&self, res: &mut Response) -> Result<Option<Bytes>, DownloadError> {
+        let next = match self.read_timeout {
+            Some(t) => tokio::time::timeout(t, res.chunk()).await
+                .map_err(|_| DownloadError::Timeout(format!("No data received within {:.2} seconds", t.as_secs_f64())))?,
+            None => res.chunk().await,
+        };
+        next.map_err(|e| DownloadError::Connection(format!("Failed to read response body: {}", e)))
+    }
+
+    /// HEAD 探测大小和校验值；不支持 Range 或 HEAD 时 size 为 None
+    async fn probe(&self) -> Result<Probe, DownloadError> {
+        let res = self.send(self.request(reqwest::Method::HEAD)).await?;
+        if !res.status().is_success() {
+            return Ok(Probe { size: None, validator: None });
+        }
+        let header = |name: &str| res.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let ranges = header("accept-ranges").is_some_and(|v| v.split(',').any(|u| u.trim().eq_ignore_ascii_case("bytes")));
+        let size = header("content-length").and_then(|v| v.parse::<u64>().ok());
+        // If-Range 不接受弱 ETag
+        let validator = header("etag").filter(|etag| !etag.starts_with("W/")).or_else(|| header("last-modified"));
+        Ok(Probe { size: size.filter(|size| ranges && *size > 0), validator })
+    }
+
+    /// 从 offset 起下载到 end（含）并写入文件的对应位置，offset 随写入推进；
+    /// end 为 None 时不发送 Range，从头下载整个响应体，同时计算 hasher
+    async fn write(&self, path: &Path, offset: &AtomicU64, end: Option<u64>, mut hasher: Option<&mut Hasher>) -> Result<(), DownloadError> {
+        let mut builder = self.request(reqwest::Method::GET);
+        if let Some(end) = end {
+            builder = builder.header("Range", format!("bytes={}-{}", offset.load(Ordering::Relaxed), end));
+            if let Some(validator) = &self.if_range {
+                builder = builder.header("If-Range", validator);
+            }
+        }
+        let mut res = self.send(builder).await?;
+        let status = res.status();
+        if end.is_some() && self.if_range.is_some() && status == StatusCode::OK {
+            return Err(DownloadError::Changed);
+        }
+        if !status.is_success() || (end.is_some() && status != StatusCode::PARTIAL_CONTENT) {
+            return Err(DownloadError::Connection(format!("Download failed with HTTP status {}", status.as_u16())));
+        }
+        let mut file = tokio::fs::OpenOptions::new().write(true).create(true).truncate(end.is_none()).open(path).await?;
+        if end.is_none() {
+            offset.store(0, Ordering::Relaxed);
+            if let Some(hasher) = hasher.as_deref_mut() {
+                hasher.reset();
+            }
+        }
+        let mut position = offset.load(Ordering::Relaxed);
+        file.seek(SeekFrom::Start(position)).await?;
+        while end.map_or(true, |end| position <= end) {
+            let Some(chunk) = self.chunk(&mut res).await? else { break };
+            // 忽略超出请求范围的数据
+            let take = end.map_or(chunk.len(), |end| chunk.len().min((end + 1 - position) as usize));
+            file.write_all(&chunk[..take]).await?;
+            if let Some(hasher) = hasher.as_deref_mut() {
+                hasher.update(&chunk[..take]);
+            }
+            position += take as u64;
+            offset.store(position, Ordering::Relaxed);
+        }
+        file.flush().await?;
+        match end {
+            Some(end) if position <= end => Err(DownloadError::Connection(
+                format!("Connection closed with {} bytes of the segment missing", end + 1 - position)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// 下载一段，失败时按策略从已写入的位置重试；返回重试次数
+    async fn fetch(&self, path: &Path, offset: &AtomicU64, end: Option<u64>, policy: &RetryPolicy, mut hasher: Option<&mut Hasher>) -> Result<u32, DownloadError> {
+        let mut attempt = 1;
+        loop {
+            match self.write(path, offset, end, hasher.as_deref_mut()).await {
+                Ok(()) => return Ok(attempt - 1),
+                // 文件错误和资源变化重试也无法恢复
+                Err(e @ (DownloadError::Io(_) | DownloadError::Changed)) => return Err(e),
+                Err(e) if attempt >= policy.max_attempts => return Err(e),
+                Err(_) => {
+                    clock::sleep(policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// 续传记录 <path>.part.json：校验值、大小和每段进度
+fn state_path(part: &Path) -> PathBuf {
+    let mut path = part.to_path_buf().into_os_string();
+    path.push(".json");
+    PathBuf::from(path)
+}
+
+/// 读取与当前文件匹配的续传进度；校验值或大小变化、记录缺失时返回 None
+async fn load_state(part: &Path, size: u64, validator: Option<&str>) -> Option<Vec<Segment>> {
+    let validator = validator?;
+    let state: Value = serde_json::from_slice(&tokio::fs::read(state_path(part)).await.ok()?).ok()?;
+    let len = tokio::fs::metadata(part).await.ok()?.len();
+    if state["size"].as_u64() != Some(size) || state["validator"].as_str() != Some(validator) || len != size {
+        return None;
+    }
+    state["segments"].as_array()?.iter()
+        .map(|s| Some(Segment::new(s.get(0)?.as_u64()?, s.get(1)?.as_u64()?)))
+        .collect()
+}
+
+async fn save_state(part: &Path, size: u64, validator: &str, segments: &[Segment]) -> std::io::Result<()> {
+    let state = json!({
+        "size": size,
+        "validator": validator,
+        "segments": segments.iter().map(|s| json!([s.offset.load(Ordering::Relaxed), s.end])).collect::<Vec<_>>(),
+    });
+    tokio::fs::write(state_path(part), state.to_string()).await
+}
+
+/// 把 size 字节分成不超过 segments 段，每段不小于 MIN_SEGMENT_BYTES；返回闭区间
+fn split(size: u64, segments: usize) -> Vec<(u64, u64)> {
+    let count = (segments as u64).min(size.div_ceil(MIN_SEGMENT_BYTES)).max(1);
+    let len = size.div_ceil(count);
+    (0..count)
+        .map(|i| (i * len, ((i + 1) * len).min(size)))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| (start, end - 1))
+        .collect()
+}
+
+/// 按顺序读取文件计算摘要
+async fn hash_file(path: &Path, mut hasher: Hasher) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(hasher.hex());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+/// 下载结果
+struct Done {
+    size: u64,
+    segments: usize,
+    retries: u32,
+    /// 续传时已有的字节数
+    resumed: u64,
+    /// 指定 checksum 时计算出的摘要
+    digest: Option<String>,
+}
+
+/// 下载到 part；resume 时从上次的进度继续，失败时保存进度
+///
+/// 单连接下载时边写边计算摘要；分段下载的数据乱序到达，完成后再按顺序读取文件计算。
+async fn run(source: &mut Source, part: &Path, segments: usize, policy: &RetryPolicy, resume: bool, checksum: Option<&Checksum>) -> Result<Done, DownloadError> {
+    let probe = source.probe().await?;
+    let Some(size) = probe.size else {
+        let offset = AtomicU64::new(0);
+        let mut hasher = checksum.map(Checksum::hasher);
+        let retries = source.fetch(part, &offset, None, policy, hasher.as_mut()).await?;
+        return Ok(Done { size: offset.into_inner(), segments: 1, retries, resumed: 0, digest: hasher.map(Hasher::hex) });
+    };
+    source.if_range = probe.validator.clone();
+    let source = &*source;
+
+    let loaded = match resume {
+        true => load_state(part, size, probe.validator.as_deref()).await,
+        false => None,
+    };
+    let segments = match loaded {
+        Some(segments) => segments,
+        None => {
+            // 预先分配完整大小，各段并发写入各自的位置
+            tokio::fs::File::create(part).await?.set_len(size).await?;
+            split(size, segments).into_iter().map(|(start, end)| Segment::new(start, end)).collect()
+        }
+    };
+    let resumed = size - segments.iter().map(Segment::remaining).sum::<u64>();
+    // 没有校验值时无法确认续传的是同一个文件，不保存进度
+    let checkpoint = probe.validator.as_deref().filter(|_| resume);
+    if let Some(validator) = checkpoint {
+        save_state(part, size, validator, &segments).await?;
+    }
+
+    let fetched = try_join_all(segments.iter().filter(|s| s.remaining() > 0).map(|segment| {
+        source.fetch(part, &segment.offset, Some(segment.end), policy, None)
+    })).await;
+    match fetched {
+        Ok(retries) => {
+            let _ = tokio::fs::remove_file(state_path(part)).await;
+            let digest = match checksum {
+                Some(checksum) => Some(hash_file(part, checksum.hasher()).await?),
+                None => None,
+            };
+            Ok(Done { size, segments: segments.len(), retries: retries.into_iter().sum(), resumed, digest })
+        }
+        Err(e) => {
+            if let (Some(validator), false) = (checkpoint, matches!(e, DownloadError::Changed)) {
+                save_state(part, size, validator, &segments).await?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 下载文件到 path：服务端支持 Range 时分段并发下载，否则单连接下载
+///
+/// 先写入 <path>.part，完成后重命名；每段独立按 retry 重试，从该段已写入的位置继续。
+/// resume 时失败保留 .part 和进度记录，下次调用用 If-Range 确认文件未变后从断点继续。
+/// checksum 不匹配时删除下载的文件并抛出 ChecksumMismatch。
+#[pyfunction]
+#[pyo3(signature = (url, path, segments=None, headers=None, timeout=None, read_timeout=None, retry=None, resume=None, checksum=None, proxy=None, ssl_verify=None, bearer_token=None))]
+pub fn download<'py>(
+    py: Python<'py>,
+    url: String,
+    path: PathBuf,
+    segments: Option<usize>,
+    headers: Option<HashMap<String, String>>,
+    timeout: Option<f64>,
+    read_timeout: Option<f64>,
+    retry: Option<RetryPolicy>,
+    resume: Option<bool>,
+    checksum: Option<String>,
+    proxy: Option<ProxyConfig>,
+    ssl_verify: Option<bool>,
+    bearer_token: Option<String>,
+) -> PyResult<&'py PyAny> {
+    if segments == Some(0) {
+        return Err(PyValueError::new_err("segments must be at least 1"));
+    }
+    check_positive("timeout", timeout)?;
+    check_positive("read_timeout", read_timeout)?;
+    let checksum = checksum.as_deref().map(Checksum::parse).transpose()?;
+    let global_config = GLOBAL_CLIENT_CONFIG.read().unwrap().clone();
+    let url = normalize_url(&url, global_config.default_scheme.as_deref().unwrap_or("http"))
+        .map_err(PyValueError::new_err)?;
+    let req = RequestItem { url: url.clone(), proxy, ssl_verify, ..Default::default() };
+    let policy = retry.unwrap_or_default();
+    let segments = segments.unwrap_or(DEFAULT_SEGMENTS);
+    let resume = resume.unwrap_or(false);
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        // 关闭自动解压，Range 针对的是线上的原始字节
+        let options = client_options(&req, &global_config, false).await;
+        let client = get_or_create_client(&url, &options).await
+            .map_err(|e| PyConnectionError::new_err(format!("Failed to build reqwest client: {}", e)))?;
+        let mut source = Source {
+            client,
+            url,
+            headers: headers.unwrap_or_default().into_iter().collect(),
+            bearer_token: bearer_token.or(global_config.bearer_token.clone()),
+            timeout: Duration::from_secs_f64(timeout.or(global_config.timeout).unwrap_or(30.0)),
+            read_timeout: read_timeout.map(Duration::from_secs_f64),
+            if_range: None,
+        };
+
+        let started = Instant::now();
+        let mut part = path.clone().into_os_string();
+        part.push(".part");
+        let part = PathBuf::from(part);
+        let done = match run(&mut source, &part, segments, &policy, resume, checksum.as_ref()).await {
+            Ok(done) => done,
+            Err(e) => {
+                if !resume || matches!(e, DownloadError::Changed) {
+                    let _ = tokio::fs::remove_file(&part).await;
+                    let _ = tokio::fs::remove_file(state_path(&part)).await;
+                }
+                return Err(e.into());
+            }
+        };
+        if let (Some(checksum), Some(digest)) = (&checksum, &done.digest) {
+            if *digest != checksum.expected {
+                let _ = tokio::fs::remove_file(&part).await;
+                return Err(ChecksumMismatch::new_err(format!(
+                    "{} mismatch for {}: expected {}, got {}", checksum.algorithm, path.display(), checksum.expected, digest
+                )));
+            }
+        }
+        tokio::fs::rename(&part, &path).await.map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        Python::with_gil(|py| -> PyResult<PyObject> {
+            let summary = PyDict::new(py);
+            summary.set_item("path", path.to_string_lossy())?;
+            summary.set_item("size", done.size)?;
+            summary.set_item("segments", done.segments)?;
+            summary.set_item("retries", done.retries)?;
+            summary.set_item("resumed_bytes", done.resumed)?;
+            if let (Some(checksum), Some(digest)) = (&checksum, &done.digest) {
+                summary.set_item("checksum", format!("{}:{}", checksum.algorithm, digest))?;
+            }
+            summary.set_item("elapsed", started.elapsed().as_secs_f64())?;
+            Ok(summary.into_py(py))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    // 代替 create_reqwest_client 的 30 秒总超时，测试不必真的等待 30 秒
+    const CLIENT_TIMEOUT: Duration = Duration::from_millis(300);
+
+    /// 本地服务：先发送响应头和一半响应体，暂停 pause 后再发送剩余部分
+    async fn slow_server(body: &'static [u8], pause: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+            socket.write_all(head.as_bytes()).await.unwrap();
+            let (first, rest) = body.split_at(body.len() / 2);
+            socket.write_all(first).await.unwrap();
+            tokio::time::sleep(pause).await;
+            socket.write_all(rest).await.unwrap();
+        });
+        format!("http://{}/file", addr)
+    }
+
+    fn source(url: String, read_timeout: Option<Duration>) -> Source {
+        let client = Client::builder().timeout(CLIENT_TIMEOUT).build().unwrap();
+        Source { client, url, headers: Vec::new(), bearer_token: None, timeout: CLIENT_TIMEOUT, read_timeout, if_range: None }
+    }
+
+    #[tokio::test]
+    async fn body_slower_than_client_timeout() {
+        let url = slow_server(b"0123456789", CLIENT_TIMEOUT * 3).await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        let offset = AtomicU64::new(0);
+        let result = source(url, None).write(&path, &offset, None, None).await;
+        assert!(result.is_ok());
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+    }
+
+    #[tokio::test]
+    async fn read_timeout_limits_idle_time() {
+        let url = slow_server(b"0123456789", CLIENT_TIMEOUT * 3).await;
+        let dir = tempfile::tempdir().unwrap();
+        let offset = AtomicU64::new(0);
+        let result = source(url, Some(CLIENT_TIMEOUT)).write(&dir.path().join("file"), &offset, None, None).await;
+        assert!(matches!(result, Err(DownloadError::Timeout(_))));
+        assert_eq!(offset.load(Ordering::Relaxed), 5);
+    }
+}
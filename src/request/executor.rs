@@ -1,186 +1,709 @@
 use std::collections::HashMap;
-use std::time::{Duration, SystemTime};
+use std::net::IpAddr;
+use std::time::{Duration, Instant, SystemTime};
+use base64::Engine;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
-use reqwest::{Client, Proxy};
-use crate::request::{execute_with_join_all, execute_with_select_all, RequestItem};
-use crate::network::{HttpVersion};
+use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use reqwest::Client;
+use crate::request::{execute_with_join_all, execute_with_select_all, retry, RequestItem, RetryPolicy};
+use crate::request::projection::project_result;
+use crate::request::prepared::PreparedRequest;
+use crate::request::body::read_body;
+use crate::request::limiter;
+use crate::request::host_profile;
+use crate::request::hooks::{self, Before};
+use crate::request::middleware;
+use crate::request::fair::Group;
+use crate::request::replay;
+use crate::request::store;
+use crate::request::dedup;
+use crate::request::spill;
+use crate::request::limits::check_headers;
+use crate::request::tagging::TagGenerator;
+use crate::request::cache::{self, CacheMode, Lookup};
+use crate::request::validators::{self, Conditional};
+use crate::request::hedge;
+use crate::request::ramp::RampUp;
+use crate::request::default_headers;
+use crate::request::transform;
+use crate::request::xml;
+use crate::request::protobuf;
+use crate::request::raw_body;
+use crate::request::isolation::{on_isolated_runtime, run_isolated};
+use crate::request::timeouts::check_batch;
+use crate::request::assertions::Assertions;
+use crate::request::watchdog::{self, Phase};
+use crate::request::oauth2::OAuth2ClientCredentials;
+use crate::network::{CaBundle, ClientIdentity, HttpVersion, SslVerifyArg, TlsVersion};
+use crate::network::impersonate::Impersonate;
+use crate::network::client::{get_or_create_client, ClientOptions};
+use crate::network::dns::{parse_resolve, IpVersion, system_nameservers, track_dns, DnsError};
+use crate::network::pinning::find_pin_mismatch;
+use crate::network::socket_error::{errno_name, find_os_error};
+use crate::network::tls_info::tls_summary;
+use crate::network::pool_stats;
+use crate::network::redirect::track_redirects;
+use crate::testing::cassette::{self, Playback};
+use crate::testing::faults::{self, Injected};
+use crate::testing::mock::{self, Mocked};
 use serde_json::Value;
 use url::Url;
-use crate::{ConcurrencyMode, ProxyConfig, DEFAULT_USER_AGENT, GLOBAL_CLIENT, GLOBAL_PROXY};
+use crate::{ConcurrencyMode, GlobalClientConfig, ProxyConfig, GLOBAL_CLIENT, GLOBAL_CLIENT_CONFIG, GLOBAL_PROXY};
 use crate::debug::debug_log;
-use crate::utils::{format_datetime, py_to_json};
-
-pub(crate) async fn create_reqwest_client(
-    request_url: &str,
-    proxy_config: &Option<ProxyConfig>,
-    http_version: &HttpVersion,
-    ssl_verify: bool,
-) -> Result<Client, Box<dyn std::error::Error>> {
-    let mut builder = Client::builder()
-        .timeout(Duration::from_secs(30))
-        .gzip(true)
-        .brotli(true)
-        .deflate(true)
-        .user_agent(&*DEFAULT_USER_AGENT);  // 复用同一个静态变量
-
-    builder = http_version.apply_to_builder(builder);
-
-    if !ssl_verify {
-        builder = builder
-            .danger_accept_invalid_certs(true)
-            .danger_accept_invalid_hostnames(true);
-    }
+use crate::audit::{audit_enabled, audit_log, AuditRecord};
+use crate::stats::{self, Outcome};
+use crate::telemetry::{self, RequestSpan};
+use crate::utils::{compile_patterns, content_fingerprint, decompress, format_datetime, DecompressError, py_to_json, normalize_text, normalize_url, parse_host_rewrites, parse_server_timing, resolve_charset, rewrite_host, DecodePolicy, Decoded, NormalizeStep, ResultCompression};
+
+// 未指定 Accept-Encoding 时声明支持的压缩格式
+const DEFAULT_ACCEPT_ENCODING: &str = "gzip, deflate, br, zstd";
 
-    // 检查是否信任环境变量，默认为 true
-    let trust_env = proxy_config
-        .as_ref()
-        .and_then(|config| config.trust_env)
-        .unwrap_or(true);
+/// 请求未发出即失败时的结果
+pub(crate) fn early_error_result(req: &RequestItem, exc_type: &str, message: String) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    result.insert("response".to_string(), String::new());
+    result.insert("http_status".to_string(), "0".to_string());
+    let mut exc = serde_json::Map::new();
+    exc.insert("type".to_string(), Value::String(exc_type.to_string()));
+    exc.insert("message".to_string(), Value::String(message));
+    result.insert("exception".to_string(), Value::Object(exc).to_string());
 
-    // 如果不信任环境变量，禁用自动代理检测
-    if !trust_env {
-        builder = builder.no_proxy();
+    let mut meta = serde_json::Map::new();
+    meta.insert("request_time".to_string(), Value::String("".to_string()));
+    meta.insert("process_time".to_string(), Value::String("0.0000".to_string()));
+    if let Some(tag) = req.tag.clone() { meta.insert("tag".to_string(), Value::String(tag)); }
+    result.insert("meta".to_string(), Value::Object(meta).to_string());
+    result
+}
+
+pub async fn execute_single_request(req: RequestItem, _base_client: Option<Client>) -> HashMap<String, String> {
+    // 字段可能在提交后通过属性修改，执行前再次校验，不静默忽略无效值
+    if let Err(e) = req.validate(None) {
+        return early_error_result(&req, "InvalidRequest", e.to_string());
     }
+    // 补全裸主机地址（如 example.com:8443）并校验 URL
+    let default_scheme = GLOBAL_CLIENT_CONFIG.read().unwrap().default_scheme.clone();
+    let url = match normalize_url(&req.url, default_scheme.as_deref().unwrap_or("http")) {
+        Ok(u) => u,
+        Err(e) => return early_error_result(&req, "InvalidUrl", e),
+    };
 
-    if let Some(config) = proxy_config { // 解包 Option<ProxyConfig>
-        if let Some(all_proxy) = &config.all {
-            let proxy_url = match (&config.username, &config.password) {
-                (Some(user), Some(pass)) => {
-                    let mut url_parsed = Url::parse(all_proxy)?;
-                    let _ = url_parsed.set_username(user);
-                    let _ = url_parsed.set_password(Some(pass));
-                    url_parsed.to_string()
-                }
-                (Some(user), None) => {
-                    let mut url_parsed = Url::parse(all_proxy)?;
-                    let _ = url_parsed.set_username(user);
-                    url_parsed.to_string()
-                }
-                _ => all_proxy.clone(),
-            };
-            builder = builder.proxy(Proxy::all(&proxy_url)?);
-        } else {
-            // 如果没有 all_proxy，则根据 scheme 判断
-            let parsed = Url::parse(request_url)?; // 使用请求的 url 来判断 scheme
-            match parsed.scheme() {
-                "http" => {
-                    if let Some(http_proxy) = &config.http {
-                        builder = builder.proxy(Proxy::http(http_proxy)?);
-                    }
-                }
-                "https" => {
-                    if let Some(https_proxy) = &config.https {
-                        builder = builder.proxy(Proxy::https(https_proxy)?);
-                    }
-                }
-                _ => {}
+    let prepared = match Python::with_gil(|py| PreparedRequest::from_item(py, &req, url)) {
+        Ok(p) => p,
+        Err(e) => return early_error_result(&req, "SerializeError", format!("Failed to serialize params: {}", e)),
+    };
+    execute_prepared(prepared).await
+}
+
+/// 发送已解析的请求
+/// 合并请求参数和全局配置得到客户端选项
+pub(crate) async fn client_options(req: &RequestItem, global_config: &GlobalClientConfig, decompress: bool) -> ClientOptions {
+    // 获取代理配置，优先使用请求中的，否则使用全局的
+    let proxy_config = if req.proxy.is_some() {
+        req.proxy.clone()
+    } else {
+        GLOBAL_PROXY.read().unwrap().clone()
+    };
+
+    ClientOptions {
+        proxy_config,
+        http_version: req.http_version.clone().or(global_config.http_version.clone()).unwrap_or(HttpVersion::Auto),
+        // 获取 ssl_verify 布尔值，请求和全局都未指定时默认 true
+        ssl_verify: req.ssl_verify.or(global_config.ssl_verify).unwrap_or(true),
+        connect_timeout: req.connect_timeout.or(global_config.connect_timeout).map(Duration::from_secs_f64),
+        allow_redirects: req.allow_redirects.or(global_config.allow_redirects).unwrap_or(true),
+        max_redirects: req.max_redirects.or(global_config.max_redirects).unwrap_or(10),
+        dns_timeout: req.dns_timeout.or(global_config.dns_timeout).map(Duration::from_secs_f64),
+        identity: req.identity.clone().or(global_config.identity.clone()),
+        ca_bundle: req.ca_bundle.clone().or(global_config.ca_bundle.clone()),
+        decompress,
+        tls_min_version: req.tls_min_version.or(global_config.tls_min_version),
+        tls_max_version: req.tls_max_version.or(global_config.tls_max_version),
+        unix_socket: req.unix_socket.clone(),
+        // 请求中的映射覆盖全局同名主机
+        resolve: {
+            let mut merged = global_config.resolve.clone().unwrap_or_default();
+            if let Some(overrides) = &req.resolve {
+                merged.extend(parse_resolve(overrides).unwrap_or_default());
             }
-        }
+            (!merged.is_empty()).then_some(merged)
+        },
+        ip_version: req.ip_version.as_deref().or(global_config.ip_version.as_deref())
+            .and_then(|v| IpVersion::parse(v).ok())
+            .unwrap_or_default(),
+        local_address: req.local_address.clone().or(global_config.local_address.clone()),
+        // 隔离线程池使用独立的连接池
+        isolated: on_isolated_runtime(),
+        keepalive_interval: req.keepalive_interval.or(global_config.keepalive_interval).map(Duration::from_secs_f64),
+        user_agent: global_config.user_agent.clone(),
+        pool_max_idle_per_host: global_config.pool_max_idle_per_host,
+        pool_idle_timeout: global_config.pool_idle_timeout.map(Duration::from_secs_f64),
+        cert_pins: global_config.cert_pins.as_ref().map(|pins| pins.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+        impersonate: req.impersonate.as_deref().and_then(|name| Impersonate::parse(name).ok()),
     }
+}
 
-    Ok(builder.build()?)
+pub async fn execute_prepared(prepared: PreparedRequest) -> HashMap<String, String> {
+    // 请求或主机配置了重试策略时按策略重试
+    let host_policy = host_profile::lookup(&prepared.url).and_then(|p| p.retry.clone());
+    match retry::for_request(&prepared.request, host_policy) {
+        Some(policy) => policy.execute(prepared).await,
+        None => execute_once(prepared).await,
+    }
 }
 
-pub async fn execute_single_request(req: RequestItem, _base_client: Option<Client>) -> HashMap<String, String> {
+/// 发送一次，不重试
+pub(crate) async fn execute_once(prepared: PreparedRequest) -> HashMap<String, String> {
+    // on_request 钩子可以修改请求，或直接给出结果
+    let req = prepared.request.clone();
+    let result = match hooks::before(prepared) {
+        Ok(Before::Send(prepared)) => middleware::run(*prepared).await,
+        Ok(Before::Respond(result)) => result,
+        Err(message) => early_error_result(&req, "HookError", message),
+    };
+    hooks::after(result)
+}
+
+pub(crate) async fn dispatch(prepared: PreparedRequest) -> HashMap<String, String> {
+    // 启用磁带时优先回放记录的结果，发送的请求结束后记录
+    let pending = match cassette::lookup(&prepared) {
+        Playback::Off => None,
+        Playback::Hit(result) => return result,
+        Playback::Miss(message) => return early_error_result(&prepared.request, "CassetteMiss", message),
+        Playback::Record(pending) => Some(pending),
+    };
+    let result = hedge::run(prepared, send_isolated).await;
+    if let Some(pending) = pending {
+        cassette::record(pending, &result);
+    }
+    result
+}
+
+async fn send_isolated(prepared: PreparedRequest) -> HashMap<String, String> {
+    // isolated 请求在独立线程池中执行，不与其他请求争抢工作线程
+    if prepared.request.isolated == Some(true) && !on_isolated_runtime() {
+        let req = prepared.request.clone();
+        // 所属批次不会随任务传到隔离线程池，需要重新设置
+        let group = Group::current();
+        let owner = raw_body::current();
+        return match run_isolated(group.scope(raw_body::scope(owner, send_prepared(prepared)))).await {
+            Some(result) => result,
+            None => early_error_result(&req, "IsolatedRuntimeError", "Isolated task was cancelled".to_string()),
+        };
+    }
+    send_prepared(prepared).await
+}
+
+async fn send_prepared(prepared: PreparedRequest) -> HashMap<String, String> {
+    let req = &prepared.request;
+    let url = &prepared.url;
     let mut result = HashMap::new();
     result.insert("response".to_string(), String::new());
 
     let start = SystemTime::now();
-    let http_version = req.http_version.clone().unwrap_or(HttpVersion::Auto);
+    // 记录请求本身，供 replay 重新发送
+    let request_record = replay::record(&prepared);
+    // 进程级并发/QPS 和单主机并发限制，permit 持有到请求结束
+    let host = Url::parse(url).ok()
+        .and_then(|u| Some(format!("{}:{}", u.host_str()?.to_lowercase(), u.port_or_known_default()?)));
+    let rate_limit = host_profile::lookup(url).and_then(|p| p.rate_limit);
+    let _permits = limiter::acquire(host.as_deref(), Group::current(), rate_limit).await;
+    // 慢请求看门狗，结束时自动移除登记
+    let tracked = watchdog::track(&prepared.method, url, req.tag.clone());
+    // 链路追踪：请求头中的 traceparent 作为父 span，并替换为本次请求的 span
+    let span = telemetry::start(&prepared.method, url, &prepared.headers);
+    // 所有结果（包括未发出即失败的）都经过 finish 记录
+    let finish = Finish { prepared: &prepared, host, start, request_record, span };
+    // decompress=False 与 compress_result="received" 相同，原样返回收到的字节
+    let compression = req.compress_result.as_deref().and_then(|c| ResultCompression::parse(c).ok())
+        .or((req.decompress == Some(false)).then_some(ResultCompression::Received));
 
-    // 获取代理配置，优先使用请求中的，否则使用全局的
-    let proxy_config = if req.proxy.is_some() {
-        req.proxy.clone()
-    } else {
-        GLOBAL_PROXY.lock().await.clone()
-    };
+    // 其余选项优先使用请求中的，否则使用全局配置
+    let global_config = GLOBAL_CLIENT_CONFIG.read().unwrap().clone();
+    let read_timeout = req.read_timeout.or(global_config.read_timeout).map(Duration::from_secs_f64);
 
-    // 获取 ssl_verify 布尔值，如果为 None 则默认 true
-    let ssl_verify_bool = req.ssl_verify.unwrap_or(true);
+    // 由执行器自行解压，以便在 meta.encoding 中记录传输大小和压缩比
+    let options = client_options(req, &global_config, false).await;
 
-    // 总是创建一个新的客户端，确保 ssl_verify 和 proxy 配置生效
-    let client = match create_reqwest_client(&req.url, &proxy_config, &http_version, ssl_verify_bool).await {
+    // 相同主机和选项的请求复用缓存的客户端，确保 ssl_verify 和 proxy 配置生效的同时保留连接池
+    let client = match get_or_create_client(url, &options).await {
         Ok(c) => c,
-        Err(e) => {
-            result.insert("http_status".to_string(), "0".to_string());
-            let mut exc = serde_json::Map::new();
-            exc.insert("type".to_string(), Value::String("ClientBuildError".to_string()));
-            exc.insert("message".to_string(), Value::String(format!("Failed to build reqwest client: {}", e)));
-            result.insert("exception".to_string(), Value::Object(exc).to_string());
+        Err(e) => return finish.record(early_error_result(req, "ClientBuildError", format!("Failed to build reqwest client: {}", e)), serde_json::Map::new(), 0),
+    };
+
+    // 客户端创建成功后，继续原有的请求逻辑
+    let method = prepared.method.parse::<reqwest::Method>().unwrap_or(reqwest::Method::GET);
 
-            let mut meta = serde_json::Map::new();
-            meta.insert("request_time".to_string(), Value::String("".to_string()));
-            meta.insert("process_time".to_string(), Value::String("0.0000".to_string()));
-            if let Some(tag) = req.tag.clone() { meta.insert("tag".to_string(), Value::String(tag)); }
-            result.insert("meta".to_string(), Value::Object(meta).to_string());
-            return result;
+    let mut request_builder = client.request(method.clone(), url);
+    // 模拟浏览器时先按浏览器顺序写入请求头，用户指定的同名请求头优先
+    let has_header = |name: &str| prepared.headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name));
+    let impersonate = req.impersonate.as_deref().and_then(|name| Impersonate::parse(name).ok());
+    if let Some(profile) = impersonate {
+        for (name, value) in profile.headers() {
+            if !has_header(name) {
+                request_builder = request_builder.header(*name, *value);
+            }
+        }
+    }
+    let traceparent = finish.span.as_ref().and_then(|s| s.traceparent()).map(|tp| ("traceparent", tp));
+    request_builder = prepared.apply(request_builder, traceparent.as_slice());
+    // 缓存指令通过 Cache-Control 传给中间缓存，用户指定的请求头优先
+    if let Some(mode) = req.cache.as_deref().and_then(|m| CacheMode::parse(m).ok()) {
+        if !has_header("cache-control") {
+            request_builder = request_builder.header(reqwest::header::CACHE_CONTROL, mode.cache_control());
+        }
+    }
+    // 关闭自动解压后 reqwest 不再发送 Accept-Encoding，需要手动声明
+    let requested_encoding = prepared.headers.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("accept-encoding"))
+        .map(|(_, v)| v.clone())
+        .or_else(|| impersonate.and_then(|profile| profile.headers().iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("accept-encoding"))
+            .map(|(_, value)| value.to_string())));
+    let requested_encoding = match requested_encoding {
+        Some(value) => value,
+        None => {
+            let value = match req.accept_encoding.as_ref().or(global_config.compression.as_ref()) {
+                Some(names) if names.is_empty() => "identity".to_string(),
+                Some(names) => names.iter().map(|n| n.trim().to_lowercase()).collect::<Vec<_>>().join(", "),
+                None => DEFAULT_ACCEPT_ENCODING.to_string(),
+            };
+            request_builder = request_builder.header(reqwest::header::ACCEPT_ENCODING, &value);
+            value
         }
     };
 
-    // 客户端创建成功后，继续原有的请求逻辑
-    let method = req.method.clone().unwrap_or_else(|| "GET".to_string()).to_uppercase();
-    let method = method.parse::<reqwest::Method>().unwrap_or(reqwest::Method::GET);
+    // 响应缓存按认证信息区分，命中时不访问网络，包括获取 OAuth2 token
+    let bearer_token = req.bearer_token.as_ref().or(global_config.bearer_token.as_ref());
+    let mut credentials: Vec<String> = match (&req.auth, bearer_token, req.oauth2.as_ref().or(global_config.oauth2.as_ref())) {
+        (Some((user, password)), _, _) => vec![format!("basic {}:{}", user, password)],
+        (None, Some(token), _) => vec![format!("bearer {}", token)],
+        (None, None, Some(oauth2)) => vec![format!("oauth2 {} {}", oauth2.token_url, oauth2.client_id)],
+        (None, None, None) => Vec::new(),
+    };
+    // headers 中自带的 Authorization / Cookie 同样区分缓存，避免不同用户共享私有响应（RFC 9111 3.5）
+    credentials.extend(prepared.headers.iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case("authorization") || k.eq_ignore_ascii_case("cookie"))
+        .map(|(k, v)| format!("{}: {}", k.to_ascii_lowercase(), v)));
+    let credentials = (!credentials.is_empty()).then(|| credentials.join("\n"));
+    // 条件请求：带上该 URL 上次响应的校验值，用户指定的请求头优先
+    let conditional = req.conditional.as_deref().and_then(|m| Conditional::parse(m).ok());
+    let conditions: Vec<(&str, String)> = match conditional {
+        Some(_) => validators::conditions(url).into_iter().filter(|(name, _)| !has_header(name)).collect(),
+        None => Vec::new(),
+    };
+    for (name, value) in &conditions {
+        request_builder = request_builder.header(*name, value);
+    }
+    let mut lookup_headers = vec![("accept-encoding", requested_encoding.as_str())];
+    lookup_headers.extend(conditions.iter().map(|(name, value)| (*name, value.as_str())));
+    let cache_mode = req.cache.as_deref().and_then(|m| CacheMode::parse(m).ok());
+    let mut cache_state = None;
+    let mut cache_hit = None;
+    let mut cache_pending = None;
+    match cache::lookup(&prepared, cache_mode, credentials.as_deref(), &lookup_headers).await {
+        Lookup::Off => {}
+        Lookup::Hit(res) => {
+            cache_state = Some("hit");
+            cache_hit = Some(res);
+        }
+        Lookup::Unavailable(res) => {
+            cache_state = Some("miss");
+            cache_hit = Some(res);
+        }
+        Lookup::Miss(pending) => {
+            cache_state = Some("miss");
+            cache_pending = Some(pending);
+        }
+        Lookup::Revalidate(pending, conditions) => {
+            cache_state = Some("miss");
+            cache_pending = Some(pending);
+            for (name, value) in conditions {
+                request_builder = request_builder.header(name, value);
+            }
+        }
+    }
 
-    let mut request_builder = client.request(method.clone(), &req.url);
-    let timeout = Duration::from_secs_f64(req.timeout.unwrap_or(30.0).max(3.0));
+    // 使用模拟传输时不访问网络，包括获取 OAuth2 token
+    let mocked = match cache_hit {
+        Some(res) => Some(res),
+        None => match mock::respond(&prepared) {
+            Some(Mocked::Error(exc_type, message)) => {
+                return finish.record(early_error_result(req, &exc_type, message), serde_json::Map::new(), 0);
+            }
+            Some(Mocked::Response(res)) => Some(res),
+            None => None,
+        },
+    };
+
+    // Basic 认证优先，其次是请求或全局的 Bearer Token，最后是 OAuth2 获取的 token
+    let oauth2 = req.oauth2.as_ref().or(global_config.oauth2.as_ref())
+        .filter(|_| req.auth.is_none() && bearer_token.is_none() && mocked.is_none());
+    let auth_scheme = if req.auth.is_some() {
+        Some("Basic")
+    } else if let Some(token) = bearer_token {
+        request_builder = request_builder.bearer_auth(token);
+        Some("Bearer")
+    } else if let Some(oauth2) = oauth2 {
+        match oauth2.access_token(&client).await {
+            Ok(token) => request_builder = request_builder.bearer_auth(token),
+            Err(e) => return finish.record(early_error_result(req, "OAuth2Error", e), serde_json::Map::new(), 0),
+        }
+        Some("Bearer")
+    } else {
+        None
+    };
+    let timeout = Duration::from_secs_f64(req.timeout.or(global_config.timeout).unwrap_or(30.0).max(3.0));
     request_builder = request_builder.timeout(timeout);
 
-    // headers
-    let mut headers_to_add = Vec::new();
-    if let Some(py_headers) = &req.headers {
-        Python::with_gil(|py| {
-            if let Ok(dict) = py_headers.as_ref(py).downcast::<PyDict>() {
-                for (k, v) in dict.iter() {
-                    if let (Ok(k_str), Ok(v_str)) = (k.extract::<String>(), v.extract::<String>()) {
-                        if let (Ok(h_name), Ok(h_val)) = (
-                            reqwest::header::HeaderName::from_bytes(k_str.as_bytes()),
-                            reqwest::header::HeaderValue::from_str(&v_str),
-                        ) { headers_to_add.push((h_name, h_val)); }
-                    }
-                }
+    let tag = req.tag.clone().unwrap_or_else(|| "no-tag".to_string());
+
+    // 测试用的故障注入，在真正发送之前生效
+    match faults::apply(url, timeout).await {
+        Some(Injected::Timeout(limit)) => {
+            let message = format!("Request timeout after {:.2} seconds", limit.as_secs_f64());
+            return finish.record(early_error_result(req, "Timeout", message), serde_json::Map::new(), 0);
+        }
+        Some(Injected::Error(exc_type, message)) => {
+            return finish.record(early_error_result(req, &exc_type, message), serde_json::Map::new(), 0);
+        }
+        None => {}
+    }
+
+    // 模拟传输、缓存命中和走代理的请求不占用到目标主机的连接，不计入连接池统计
+    let pooled = mocked.is_none() && options.proxy_config.is_none();
+    let send_started = Instant::now();
+    let ((send_result, redirect_chain), dns_trace) = track_dns(watchdog::scope(
+        tracked.as_ref(),
+        track_redirects(tokio::time::timeout(timeout, async move {
+            match mocked {
+                Some(res) => Ok(res),
+                None => request_builder.send().await,
             }
-        });
+        })),
+    )).await;
+    // 重新验证得到 304 时，用更新后的缓存响应代替
+    let mut send_result = send_result;
+    let not_modified = match &send_result {
+        Ok(Ok(res)) if res.status() == reqwest::StatusCode::NOT_MODIFIED => match cache_pending.take() {
+            Some(pending) => pending.refresh(res).await,
+            None => None,
+        },
+        _ => None,
+    };
+    if let Some(res) = not_modified {
+        send_result = Ok(Ok(res));
+        cache_state = Some("revalidated");
     }
-    for (name, value) in headers_to_add { request_builder = request_builder.header(name, value); }
-
-    if let Some(params_dict) = &req.params {
-        request_builder = Python::with_gil(|py| {
-            let mut inner_request_builder = request_builder;
-            if let Ok(dict) = params_dict.as_ref(py).downcast::<PyDict>() {
-                if let Ok(json) = py_to_json(py, dict) {
-                    match method {
-                        reqwest::Method::GET | reqwest::Method::DELETE => {
-                            if let Some(obj) = json.as_object() {
-                                let query_pairs: Vec<(String, String)> = obj.iter()
-                                    .map(|(k, v)| (k.clone(), v.to_string().trim_matches('"').to_string()))
-                                    .collect();
-                                let query_refs: Vec<(&str, &str)> = query_pairs.iter().map(|(k,v)| (k.as_str(), v.as_str())).collect();
-                                inner_request_builder = inner_request_builder.query(&query_refs);
-                            }
-                        }
-                        _ => { inner_request_builder = inner_request_builder.json(&json); }
-                    }
-                }
+    let mut final_url = None;
+    let mut bytes_received = 0;
+    // 执行过程中追加到 meta 的字段
+    let mut extra_meta = serde_json::Map::new();
+    if let Some(state) = cache_state {
+        extra_meta.insert("cache".to_string(), Value::String(state.to_string()));
+    }
+    // 连接池统计：新建连接都会经过解析器（包括静态解析和 DNS 缓存），没有解析最终主机即复用了连接，连接占用到请求结束
+    let _connection = match &send_result {
+        Ok(Ok(res)) if pooled => match (res.url().host(), res.url().port_or_known_default()) {
+            (Some(url::Host::Domain(name)), Some(port)) => {
+                let name = name.to_ascii_lowercase();
+                Some(pool_stats::record(&format!("{}:{}", name, port), dns_trace.hosts.contains(&name)))
             }
-            inner_request_builder
-        });
+            _ => None,
+        },
+        _ => None,
+    };
+
+    // 分阶段耗时；reqwest 0.11 不暴露 TCP/TLS 建连的边界，不提供 connect_time / tls_time，新连接的 ttfb 包含建连时间
+    if let Some(dns_time) = dns_trace.elapsed {
+        extra_meta.insert("dns_time".to_string(), Value::String(format!("{:.4}", dns_time.as_secs_f64())));
+    }
+    if send_result.as_ref().is_ok_and(|r| r.is_ok()) {
+        extra_meta.insert("ttfb".to_string(), Value::String(format!("{:.4}", send_started.elapsed().as_secs_f64())));
     }
 
-    let tag = req.tag.clone().unwrap_or_else(|| "no-tag".to_string());
+    let header_error = match &send_result {
+        Ok(Ok(res)) => check_headers(
+            res.headers(),
+            req.max_response_headers.or(global_config.max_response_headers),
+            req.max_header_bytes.or(global_config.max_header_bytes),
+        ),
+        _ => None,
+    };
 
-    match tokio::time::timeout(timeout, request_builder.send()).await {
+    match send_result {
+        // 响应头超出限制时不读取响应体
+        Ok(Ok(res)) if header_error.is_some() => {
+            result.insert("http_status".to_string(), res.status().as_u16().to_string());
+            let mut exc = serde_json::Map::new();
+            exc.insert("type".to_string(), Value::String("HeaderLimitError".to_string()));
+            exc.insert("message".to_string(), Value::String(header_error.unwrap_or_default()));
+            result.insert("exception".to_string(), Value::Object(exc).to_string());
+            result.insert("response".to_string(), serde_json::json!({"headers":{}, "content":""}).to_string());
+        }
         Ok(Ok(res)) => {
             let status = res.status();
+            final_url = Some(res.url().to_string());
+            // token 被拒绝时丢弃缓存，后续请求重新获取
+            if status == reqwest::StatusCode::UNAUTHORIZED {
+                if let Some(oauth2) = oauth2 {
+                    oauth2.invalidate_token().await;
+                }
+            }
             result.insert("http_status".to_string(), status.as_u16().to_string());
+            cache::invalidate(&prepared.method, url, credentials.as_deref(), status.as_u16()).await;
+            let cache_headers: Vec<(String, String)> = match &cache_pending {
+                Some(_) => res.headers().iter()
+                    .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+                    .collect(),
+                None => Vec::new(),
+            };
 
             // 生成 headers_map
             let headers_map: serde_json::Map<String, Value> = res.headers().iter()
                 .map(|(k, v)| (k.to_string(), Value::String(v.to_str().unwrap_or("").to_string())))
                 .collect();
 
-            // 读取响应
-            let text = res.text().await.unwrap_or_else(|e| format!("Failed to read response text: {}", e));
+            // 服务端证书信息，便于监控证书过期
+            if let Some(tls) = tls_summary(&res) {
+                extra_meta.insert("tls".to_string(), tls);
+            }
+
+            // 服务端耗时，便于与客户端耗时对照
+            let server_timing = parse_server_timing(
+                res.headers().get_all("server-timing").iter().filter_map(|v| v.to_str().ok())
+            );
+            if !server_timing.is_empty() {
+                extra_meta.insert("server_timing".to_string(), Value::Array(server_timing));
+            }
+
+            let content_encoding = res.headers().get(reqwest::header::CONTENT_ENCODING)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string());
+
+            // 读取响应，设置了 read_timeout 时限制响应体读取时长，超时保留已收到的部分
+            if let Some(tracked) = &tracked {
+                tracked.set_phase(Phase::ReadingBody);
+            }
+            let download_started = Instant::now();
+            let max_response_bytes = req.max_response_bytes.or(global_config.max_response_bytes);
+            let memory_threshold = req.memory_threshold.or(global_config.memory_threshold);
+            let mut body = read_body(res, read_timeout, max_response_bytes, memory_threshold).await;
+            let mut too_large = max_response_bytes.filter(|_| body.too_large);
+            bytes_received = body.len();
+            extra_meta.insert("download_time".to_string(), Value::String(format!("{:.4}", download_started.elapsed().as_secs_f64())));
+            let read_timed_out = body.timed_out.then(|| read_timeout.unwrap_or(timeout));
+            if body.timed_out {
+                extra_meta.insert("partial_body".to_string(), Value::Bool(true));
+            }
+            // 按 decode_errors 策略解码，默认替换无效字节；protobuf / gRPC 响应默认保留原始字节
+            let binary = protobuf::is_binary(headers_map.get("content-type").and_then(Value::as_str));
+            let decode_policy = req.decode_errors.as_deref()
+                .and_then(|p| DecodePolicy::parse(p).ok())
+                .unwrap_or(if binary { DecodePolicy::Bytes } else { DecodePolicy::Replace });
+            let mut decode_error = None;
+            let body_complete = body.error.is_none() && !body.timed_out && too_large.is_none();
+            // 按线上收到的原始字节缓存，命中时和网络响应走同样的解压流程
+            if let Some(pending) = cache_pending.take().filter(|_| body_complete && body.spilled.is_none()) {
+                pending.store(status.as_u16(), cache_headers, &body.bytes).await;
+            }
+
+            // 协商结果：请求的编码、实际收到的编码和压缩比
+            let received_encoding = content_encoding.as_deref()
+                .map(|e| e.trim().to_lowercase())
+                .filter(|e| !e.is_empty())
+                .unwrap_or_else(|| "identity".to_string());
+            // max_response_bytes 同时限制解压后的大小，防止高压缩比的响应耗尽内存
+            if received_encoding != "identity" && compression != Some(ResultCompression::Received) && body.error.is_none() && too_large.is_none() {
+                let decoded = match body.spilled.take() {
+                    // 写入临时文件的响应体流式解压到新的临时文件
+                    Some(spilled) => spill::decompress(spilled, &received_encoding, max_response_bytes).await
+                        .map(|decoded| body.spilled = Some(decoded)),
+                    None => decompress(&body.bytes, &received_encoding, body.timed_out, max_response_bytes)
+                        .map(|decoded| body.bytes = decoded),
+                };
+                match decoded {
+                    Ok(()) => {}
+                    Err(DecompressError::TooLarge) => {
+                        body.bytes = Vec::new();
+                        too_large = max_response_bytes;
+                    }
+                    Err(DecompressError::Invalid(e)) => decode_error = Some(e),
+                }
+            }
+            let mut encoding = serde_json::json!({
+                "requested": requested_encoding,
+                "received": received_encoding,
+                "wire_bytes": bytes_received,
+            });
+            if decode_error.is_none() && compression != Some(ResultCompression::Received) {
+                encoding["decoded_bytes"] = Value::from(body.len());
+                if bytes_received > 0 {
+                    encoding["ratio"] = Value::from((body.len() as f64 / bytes_received as f64 * 100.0).round() / 100.0);
+                }
+            }
+            extra_meta.insert("encoding".to_string(), encoding);
+
+            // 超出 memory_threshold 的响应体留在临时文件中，content 为空，meta.body_file 返回路径
+            let spilled = body.spilled.take();
+            if let Some(spilled) = &spilled {
+                let content_encoding = match compression {
+                    Some(ResultCompression::Received) => received_encoding.as_str(),
+                    _ => "identity",
+                };
+                extra_meta.insert("body_file".to_string(), spilled.reference(content_encoding));
+            }
+
+            // gRPC-Web 响应：content 为第一个消息，状态和 trailer 记录在 meta.grpc
+            let mut grpc_error = None;
+            if req.grpc_web == Some(true) && body_complete && decode_error.is_none() && compression.is_none() && spilled.is_none() {
+                match protobuf::unframe(&body.bytes) {
+                    Ok(unframed) => {
+                        let summary = unframed.summary(&headers_map);
+                        match unframed.status(&headers_map) {
+                            (Some(code), message) if code != 0 => grpc_error = Some((code, message)),
+                            _ => {}
+                        }
+                        extra_meta.insert("grpc".to_string(), summary);
+                        body.bytes = unframed.messages.into_iter().next().unwrap_or_default();
+                    }
+                    Err(e) => decode_error = Some(e),
+                }
+            }
+
+            let raw = req.raw_body == Some(true) && body.error.is_none() && decode_error.is_none() && too_large.is_none() && spilled.is_none();
+            let mut text = match (body.error, compression) {
+                (_, _) if too_large.is_some() || spilled.is_some() => String::new(),
+                (Some(e), _) => format!("Failed to read response text: {}", e),
+                // 解压失败时不返回压缩数据
+                (None, _) if decode_error.is_some() => String::new(),
+                // raw_body 时不解码，响应体在下面暂存
+                (None, _) if raw => String::new(),
+                // 要求压缩返回时跳过解码，content 为 base64 编码的压缩数据
+                (None, Some(compression)) => {
+                    let (content, codec) = compression.encode(&body.bytes, content_encoding.as_deref());
+                    extra_meta.insert("content_encoding".to_string(), Value::String("base64".to_string()));
+                    extra_meta.insert("compression".to_string(), Value::String(codec));
+                    content
+                }
+                (None, None) => {
+                    extra_meta.insert("decode_policy".to_string(), Value::String(decode_policy.as_str().to_string()));
+                    // decode_errors="error" 时不猜测编码，保持对无效 UTF-8 报错
+                    let charset = resolve_charset(
+                        &body.bytes,
+                        headers_map.get("content-type").and_then(Value::as_str),
+                        req.encoding.as_deref(),
+                        decode_policy == DecodePolicy::Replace,
+                    );
+                    if decode_policy != DecodePolicy::Bytes {
+                        extra_meta.insert("charset".to_string(), serde_json::json!({"name": charset.encoding.name(), "source": charset.source}));
+                    }
+                    match decode_policy.decode(&body.bytes, charset.encoding) {
+                        Decoded::Text { text, replaced } => {
+                            extra_meta.insert("replaced_chars".to_string(), Value::from(replaced));
+                            // 按需对响应文本做规范化处理
+                            match &req.normalize {
+                                Some(names) => normalize_text(text, &NormalizeStep::parse_all(names).unwrap_or_default()),
+                                None => text,
+                            }
+                        }
+                        Decoded::Base64(encoded) => {
+                            extra_meta.insert("content_encoding".to_string(), Value::String("base64".to_string()));
+                            encoded
+                        }
+                        Decoded::Invalid(message) => {
+                            decode_error = Some(message);
+                            String::new()
+                        }
+                    }
+                }
+            };
+
+            // 条件请求：2xx 保存校验值，304 标记 not_modified，"body" 模式返回上次保存的内容
+            if let Some(mode) = conditional {
+                let decoded = body_complete && decode_error.is_none() && !raw && compression.is_none() && spilled.is_none();
+                if status == reqwest::StatusCode::NOT_MODIFIED {
+                    extra_meta.insert("not_modified".to_string(), Value::Bool(true));
+                    if let Some(previous) = validators::previous_body(url).filter(|_| mode == Conditional::Body) {
+                        text = previous;
+                    }
+                } else if status.is_success() && body_complete {
+                    let keep = (mode == Conditional::Body && decoded).then(|| text.clone());
+                    validators::update(url, &headers_map, keep);
+                }
+            }
+
+            // 内容指纹，用于跨次运行检测内容变化
+            if let Some(patterns) = &req.fingerprint {
+                if decode_error.is_none() && spilled.is_none() {
+                    let ignore = compile_patterns(patterns).unwrap_or_default();
+                    extra_meta.insert("fingerprint".to_string(), Value::String(content_fingerprint(&text, &ignore)));
+                }
+            }
+
+            // 监控断言，结果记录在 meta 中
+            let assertions = Assertions {
+                json: req.assert_json.as_ref()
+                    .and_then(|expected| Python::with_gil(|py| py_to_json(py, expected.as_ref(py)).ok())),
+                contains: req.assert_contains.as_deref(),
+                headers: req.assert_header.as_ref(),
+            };
+            if let Some(outcome) = assertions.evaluate(&headers_map, &text).filter(|_| spilled.is_none()) {
+                extra_meta.insert("assertions".to_string(), outcome);
+            }
+
+            // XML 响应转为 JSON，之后的 transform 可以用 JSONPath 处理
+            let mut xml_error = None;
+            if req.parse_xml == Some(true) && decode_error.is_none() && compression.is_none() && too_large.is_none() && spilled.is_none() {
+                match xml::to_json(&text) {
+                    Ok(parsed) => {
+                        extra_meta.insert("parsed_xml".to_string(), Value::Bool(true));
+                        text = parsed.to_string();
+                    }
+                    Err(e) => xml_error = Some(e),
+                }
+            }
+
+            // Rust 侧后处理，失败时保留原始 content
+            let mut transform_error = None;
+            let transformable = decode_error.is_none() && compression.is_none() && xml_error.is_none() && too_large.is_none() && spilled.is_none();
+            if let Some(name) = req.transform.as_deref().filter(|_| transformable) {
+                match transform::run(name, text.clone()).await {
+                    Ok(output) => {
+                        extra_meta.insert("transform".to_string(), Value::String(name.to_string()));
+                        text = output;
+                    }
+                    Err(e) => transform_error = Some(e),
+                }
+            }
+
+            // 写入内容寻址存储，content 置空，meta.body_ref 返回引用
+            if let Some(dir) = req.body_store.as_ref().or(global_config.body_store.as_ref()).filter(|_| body_complete && spilled.is_none()) {
+                match store::put(dir, &body.bytes).await {
+                    Ok(reference) => {
+                        extra_meta.insert("body_ref".to_string(), reference);
+                        text = String::new();
+                    }
+                    Err(e) => {
+                        extra_meta.insert("body_store_error".to_string(), Value::String(e.to_string()));
+                    }
+                }
+            }
+
+            // raw_body：响应体不经过 String，转换结果时直接复制进 bytes；不在 fetch 调用中时退回 base64
+            if raw {
+                extra_meta.insert("body_bytes".to_string(), Value::from(body.bytes.len()));
+                match raw_body::stash(std::mem::take(&mut body.bytes)) {
+                    Ok(id) => {
+                        result.insert(raw_body::BODY_KEY.to_string(), id);
+                    }
+                    Err(bytes) => {
+                        extra_meta.insert("content_encoding".to_string(), Value::String("base64".to_string()));
+                        text = base64::engine::general_purpose::STANDARD.encode(bytes);
+                    }
+                }
+            }
 
             // response 对象
             let response = serde_json::json!({
@@ -195,21 +718,58 @@ pub async fn execute_single_request(req: RequestItem, _base_client: Option<Clien
             debug_log(
                 &method.to_string(),
                 &tag,
-                &req.url,
+                url,
                 status,
                 &headers_map,
                 &response,
-                proxy_config.as_ref().and_then(|p| p.all.as_deref()),
-                proxy_config.as_ref().and_then(|p| {
+                options.proxy_config.as_ref().and_then(|p| p.all.as_deref()),
+                options.proxy_config.as_ref().and_then(|p| {
                     if p.username.is_some() {
                         Some("with authentication")
                     } else {
                         None
                     }
                 }).map(|s| s),
+                auth_scheme,
             );
 
-            if !status.is_success() {
+            // 禁用重定向时，3xx 响应作为正常结果返回
+            let redirect_captured = status.is_redirection() && !options.allow_redirects;
+            // 条件请求的 304 是预期结果，不作为错误
+            let not_modified = conditional.is_some() && status == reqwest::StatusCode::NOT_MODIFIED;
+            if let Some(limit) = too_large {
+                let mut exc = serde_json::Map::new();
+                exc.insert("type".to_string(), Value::String("ResponseTooLarge".to_string()));
+                exc.insert("message".to_string(), Value::String(format!("Response body exceeds max_response_bytes ({} bytes)", limit)));
+                exc.insert("limit".to_string(), Value::from(limit));
+                result.insert("exception".to_string(), Value::Object(exc).to_string());
+            } else if let Some(message) = decode_error {
+                let mut exc = serde_json::Map::new();
+                exc.insert("type".to_string(), Value::String("DecodeError".to_string()));
+                exc.insert("message".to_string(), Value::String(message));
+                result.insert("exception".to_string(), Value::Object(exc).to_string());
+            } else if let Some((code, message)) = grpc_error {
+                let mut exc = serde_json::Map::new();
+                exc.insert("type".to_string(), Value::String("GrpcError".to_string()));
+                exc.insert("message".to_string(), Value::String(message.unwrap_or_else(|| format!("gRPC status {}", code))));
+                exc.insert("code".to_string(), Value::from(code));
+                result.insert("exception".to_string(), Value::Object(exc).to_string());
+            } else if let Some(message) = xml_error {
+                let mut exc = serde_json::Map::new();
+                exc.insert("type".to_string(), Value::String("XmlParseError".to_string()));
+                exc.insert("message".to_string(), Value::String(message));
+                result.insert("exception".to_string(), Value::Object(exc).to_string());
+            } else if let Some(message) = transform_error {
+                let mut exc = serde_json::Map::new();
+                exc.insert("type".to_string(), Value::String("TransformError".to_string()));
+                exc.insert("message".to_string(), Value::String(message));
+                result.insert("exception".to_string(), Value::Object(exc).to_string());
+            } else if let Some(limit) = read_timed_out {
+                let mut exc = serde_json::Map::new();
+                exc.insert("type".to_string(), Value::String("ReadTimeout".to_string()));
+                exc.insert("message".to_string(), Value::String(format!("Read timeout after {:.2} seconds", limit.as_secs_f64())));
+                result.insert("exception".to_string(), Value::Object(exc).to_string());
+            } else if !status.is_success() && !redirect_captured && !not_modified {
                 let mut exc = serde_json::Map::new();
                 exc.insert("type".to_string(), Value::String("HttpStatusError".to_string()));
                 exc.insert("message".to_string(), Value::String(format!("HTTP status error: {}", status.as_u16())));
@@ -221,8 +781,44 @@ pub async fn execute_single_request(req: RequestItem, _base_client: Option<Clien
         Ok(Err(e)) => {
             result.insert("http_status".to_string(), "0".to_string());
             let mut exc = serde_json::Map::new();
-            exc.insert("type".to_string(), Value::String("HttpError".to_string()));
-            exc.insert("message".to_string(), Value::String(format!("Request error: {}", e)));
+            if let Some(mismatch) = find_pin_mismatch(&e) {
+                // 证书指纹不匹配时握手已中止，请求没有发出
+                exc.insert("type".to_string(), Value::String("PinningError".to_string()));
+                exc.insert("message".to_string(), Value::String(mismatch.to_string()));
+            } else if let Some(dns) = DnsError::find(&e) {
+                // DNS 解析失败与连接错误分开报告
+                let exc_type = if dns.timed_out { "DnsTimeout" } else { "DnsError" };
+                exc.insert("type".to_string(), Value::String(exc_type.to_string()));
+                exc.insert("message".to_string(), Value::String(dns.to_string()));
+                exc.insert("host".to_string(), Value::String(dns.host.clone()));
+                exc.insert("nameservers".to_string(), Value::from(system_nameservers()));
+            } else {
+                let exc_type = if e.is_connect() && e.is_timeout() {
+                    "ConnectTimeout"
+                } else if e.is_redirect() {
+                    "TooManyRedirects"
+                } else {
+                    "HttpError"
+                };
+                exc.insert("type".to_string(), Value::String(exc_type.to_string()));
+                exc.insert("message".to_string(), Value::String(format!("Request error: {}", e)));
+                // 操作系统错误码和目标地址，便于区分拒绝连接、不可达和超时
+                if let Some(os_error) = find_os_error(&e) {
+                    let errno = os_error.raw_os_error().unwrap_or_default();
+                    exc.insert("errno".to_string(), Value::from(errno));
+                    exc.insert("code".to_string(), errno_name(errno).map(Value::from).unwrap_or(Value::Null));
+                }
+                let mut addrs: Vec<IpAddr> = dns_trace.addrs.iter().map(|a| a.ip()).collect();
+                if addrs.is_empty() {
+                    // 静态解析不经过解析器
+                    addrs.extend(Url::parse(url).ok()
+                        .and_then(|u| options.resolve.as_ref()?.get(&u.host_str()?.to_lowercase()).map(|a| a.ip())));
+                }
+                if let Some(ip) = addrs.first() {
+                    exc.insert("ip".to_string(), Value::String(ip.to_string()));
+                    exc.insert("addresses".to_string(), addrs.iter().map(|a| Value::String(a.to_string())).collect());
+                }
+            }
             result.insert("exception".to_string(), Value::Object(exc).to_string());
             result.insert("response".to_string(), serde_json::json!({"headers":{}, "content":""}).to_string());
         }
@@ -236,18 +832,73 @@ pub async fn execute_single_request(req: RequestItem, _base_client: Option<Clien
         }
     }
 
-    // meta 信息
-    let end = SystemTime::now();
-    let process_time = end.duration_since(start).unwrap_or(Duration::from_secs(0)).as_secs_f64();
-    let start_str = format_datetime(start);
-    let end_str = format_datetime(end);
-    let mut meta = serde_json::Map::new();
-    meta.insert("request_time".to_string(), Value::String(format!("{} -> {}", start_str, end_str)));
-    meta.insert("process_time".to_string(), Value::String(format!("{:.4}", process_time)));
-    if let Some(tag) = req.tag.clone() { meta.insert("tag".to_string(), Value::String(tag)); }
-    result.insert("meta".to_string(), Value::Object(meta).to_string());
+    if let Some(final_url) = final_url { extra_meta.insert("final_url".to_string(), Value::String(final_url)); }
+    if !redirect_chain.is_empty() {
+        extra_meta.insert("redirect_chain".to_string(), Value::Array(redirect_chain.iter().map(|hop| hop.to_json()).collect()));
+    }
+    finish.record(result, extra_meta, bytes_received)
+}
 
-    result
+/// 请求结束时的公共记录：生成 meta、结束 span、计入统计和审计日志
+///
+/// 未发出即失败的结果（客户端创建失败、模拟错误、OAuth2 错误、注入的故障）同样经过这里。
+struct Finish<'a> {
+    prepared: &'a PreparedRequest,
+    host: Option<String>,
+    start: SystemTime,
+    request_record: Value,
+    span: Option<RequestSpan>,
+}
+
+impl Finish<'_> {
+    fn record(self, mut result: HashMap<String, String>, mut extra_meta: serde_json::Map<String, Value>, bytes_received: usize) -> HashMap<String, String> {
+        let req = &self.prepared.request;
+        let end = SystemTime::now();
+        let process_time = end.duration_since(self.start).unwrap_or(Duration::from_secs(0)).as_secs_f64();
+        let mut meta = serde_json::Map::new();
+        meta.insert("request_time".to_string(), Value::String(format!("{} -> {}", format_datetime(self.start), format_datetime(end))));
+        meta.insert("process_time".to_string(), Value::String(format!("{:.4}", process_time)));
+        if let Some(tag) = req.tag.clone() { meta.insert("tag".to_string(), Value::String(tag)); }
+        meta.insert("request".to_string(), self.request_record);
+        // 没有拿到响应时断言视为失败
+        let has_assertions = req.assert_json.is_some() || req.assert_contains.is_some() || req.assert_header.is_some();
+        if has_assertions && !extra_meta.contains_key("assertions") {
+            extra_meta.insert("assertions".to_string(), serde_json::json!({
+                "passed": false,
+                "failures": ["no response"],
+            }));
+        }
+        meta.extend(extra_meta);
+        result.insert("meta".to_string(), Value::Object(meta).to_string());
+
+        if let Some(span) = self.span {
+            span.finish(&result);
+        }
+        let exception = result.get("exception").and_then(|e| serde_json::from_str::<Value>(e).ok());
+        let error = exception.as_ref().and_then(|e| e.get("type")).and_then(|t| t.as_str());
+        let bytes_sent = self.prepared.body.as_ref().map_or(0, |b| b.len());
+        stats::record(Outcome {
+            tag: req.tag.as_deref(),
+            host: self.host.as_deref(),
+            error,
+            bytes_sent,
+            bytes_received,
+            duration: process_time,
+        });
+        if audit_enabled() {
+            audit_log(AuditRecord {
+                method: &self.prepared.method,
+                url: &self.prepared.url,
+                status: result.get("http_status").and_then(|s| s.parse().ok()).unwrap_or(0),
+                bytes_sent,
+                bytes_received,
+                duration: process_time,
+                tag: req.tag.as_deref(),
+                error,
+            });
+        }
+        result
+    }
 }
 
 #[pyfunction]
@@ -261,23 +912,88 @@ pub fn fetch_single<'py>(
     tag: Option<String>,
     proxy: Option<ProxyConfig>,
     http_version: Option<HttpVersion>,
-    ssl_verify: Option<bool>,
+    ssl_verify: Option<SslVerifyArg>,
+    connect_timeout: Option<f64>,
+    read_timeout: Option<f64>,
+    allow_redirects: Option<bool>,
+    max_redirects: Option<usize>,
+    normalize: Option<Vec<String>>,
+    auth: Option<(String, String)>,
+    bearer_token: Option<String>,
+    decode_errors: Option<String>,
+    dns_timeout: Option<f64>,
+    oauth2: Option<OAuth2ClientCredentials>,
+    identity: Option<ClientIdentity>,
+    ca_bundle: Option<CaBundle>,
+    compress_result: Option<String>,
+    tls_min_version: Option<TlsVersion>,
+    tls_max_version: Option<TlsVersion>,
+    impersonate: Option<String>,
+    fingerprint: Option<Vec<String>>,
+    assert_json: Option<Py<PyDict>>,
+    assert_contains: Option<String>,
+    assert_header: Option<HashMap<String, String>>,
+    unix_socket: Option<String>,
+    body_store: Option<String>,
+    resolve: Option<HashMap<String, String>>,
+    ip_version: Option<String>,
+    local_address: Option<String>,
+    isolated: Option<bool>,
+    max_response_headers: Option<usize>,
+    max_header_bytes: Option<usize>,
+    keepalive_interval: Option<f64>,
+    cache: Option<String>,
+    transform: Option<String>,
+    xml: Option<String>,
+    parse_xml: Option<bool>,
+    protobuf: Option<Py<PyBytes>>,
+    grpc_web: Option<bool>,
+    max_response_bytes: Option<usize>,
+    accept_encoding: Option<Vec<String>>,
+    decompress: Option<bool>,
+    compress_body: Option<String>,
+    encoding: Option<String>,
+    raw_body: Option<bool>,
+    memory_threshold: Option<usize>,
+    conditional: Option<String>,
+    retries: Option<u32>,
+    retry_policy: Option<RetryPolicy>,
+    hedge_after: Option<f64>,
 ) -> PyResult<&'py PyAny> {
+    let req = RequestItem {
+        url, method, params, timeout, tag, headers, proxy, http_version,
+        ssl_verify: ssl_verify.map(|v| v.get()),
+        connect_timeout, read_timeout, allow_redirects, max_redirects, normalize, auth,
+        bearer_token, decode_errors, dns_timeout, oauth2, identity,
+        ca_bundle, compress_result, tls_min_version, tls_max_version, impersonate,
+        fingerprint, assert_json, assert_contains, assert_header, unix_socket, body_store,
+        resolve, ip_version, local_address, isolated, max_response_headers, max_header_bytes,
+        keepalive_interval, cache, transform, xml, parse_xml, protobuf, grpc_web, max_response_bytes,
+        accept_encoding, decompress, compress_body, encoding, raw_body, memory_threshold, conditional,
+        retries, retry_policy, hedge_after,
+    };
+    // 提交时校验各字段和超时层级，包括全局配置中的超时
+    req.validate(Some(&GLOBAL_CLIENT_CONFIG.read().unwrap()))?;
+
     // 这里直接调用 execute_single_request 异步包装
     pyo3_asyncio::tokio::future_into_py(py, async move {
-        let req = RequestItem { url, method, params, timeout, tag, headers, proxy, http_version, ssl_verify };
-        let result = execute_single_request(req, None).await;
-        Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-            let dict = PyDict::new(py);
-            dict.set_item("response", &result["response"])?;
-            dict.set_item("http_status", result.get("http_status").unwrap_or(&"0".to_string()))?;
-            dict.set_item("meta", result.get("meta").unwrap_or(&"{}".to_string()))?;
-            dict.set_item("exception", result.get("exception").unwrap_or(&"{}".to_string()))?;
-            Ok(dict.into_py(py))
-        })
+        let owner = raw_body::Owner::default();
+        let result = raw_body::scope(Some(owner.clone()), execute_single_request(req, None)).await;
+        Python::with_gil(|py| single_result_to_py(py, &result))
     })
 }
 
+/// fetch_single 风格的返回值
+pub(crate) fn single_result_to_py(py: Python, result: &HashMap<String, String>) -> PyResult<Py<PyAny>> {
+    let dict = PyDict::new(py);
+    dict.set_item("response", result.get("response").unwrap_or(&String::new()))?;
+    dict.set_item("http_status", result.get("http_status").unwrap_or(&"0".to_string()))?;
+    dict.set_item("meta", result.get("meta").unwrap_or(&"{}".to_string()))?;
+    dict.set_item("exception", result.get("exception").unwrap_or(&"{}".to_string()))?;
+    raw_body::attach(py, result, dict)?;
+    Ok(dict.into_py(py))
+}
+
 /// 异步批量请求函数
 #[pyfunction]
 pub fn fetch_requests<'py>(
@@ -285,47 +1001,127 @@ pub fn fetch_requests<'py>(
     requests: Vec<RequestItem>,
     total_timeout: Option<f64>,
     mode: Option<ConcurrencyMode>,
+    fields: Option<Vec<String>>,
+    host_rewrites: Option<HashMap<String, String>>,
+    isolated: Option<bool>,
+    tag_generator: Option<&PyAny>,
+    ramp_up: Option<RampUp>,
+    default_headers: Option<HashMap<String, String>>,
+    priority: Option<f64>,
+    middleware: Option<Vec<PyObject>>,
+    deduplicate: Option<bool>,
 ) -> PyResult<&'py PyAny> {
+    check_batch(&requests, total_timeout, &GLOBAL_CLIENT_CONFIG.read().unwrap())?;
+    if priority.is_some_and(|p| !p.is_finite() || p <= 0.0) {
+        return Err(PyValueError::new_err("priority must be a positive number"));
+    }
+    let default_headers = default_headers.map(default_headers::parse_headers).transpose()?;
+    let middleware = middleware.map(|m| middleware::parse_chain(py, m)).transpose()?;
+    let mut requests = requests;
+    // 整批放到隔离线程池，单个请求的 isolated 设置被覆盖
+    if let Some(isolated) = isolated {
+        for req in requests.iter_mut() {
+            req.isolated = Some(isolated);
+        }
+    }
+    // 批量替换主机，如把生产环境的请求集指向测试环境
+    if let Some(rewrites) = host_rewrites {
+        let rewrites = parse_host_rewrites(rewrites)?;
+        let default_scheme = GLOBAL_CLIENT_CONFIG.read().unwrap().default_scheme.clone();
+        for req in requests.iter_mut() {
+            // 无效的地址保持原样，执行时返回 InvalidUrl
+            if let Ok(url) = normalize_url(&req.url, default_scheme.as_deref().unwrap_or("http")) {
+                req.url = rewrite_host(&url, &rewrites).unwrap_or(url);
+            }
+        }
+    }
+    // 为没有 tag 的请求生成 tag，结果和日志不再出现 "no-tag"
+    if let Some(generator) = tag_generator {
+        let default_scheme = GLOBAL_CLIENT_CONFIG.read().unwrap().default_scheme.clone();
+        TagGenerator::extract(generator)?.apply(py, &mut requests, default_scheme.as_deref().unwrap_or("http"))?;
+    }
+    // 相同的 GET / HEAD 请求只发送一次，结果在返回前展开
+    let dedup = match deduplicate {
+        Some(true) => {
+            let default_scheme = GLOBAL_CLIENT_CONFIG.read().unwrap().default_scheme.clone();
+            let plan = dedup::Plan::new(py, &requests, default_scheme.as_deref().unwrap_or("http"));
+            requests = plan.unique(requests);
+            Some(plan)
+        }
+        _ => None,
+    };
+
     pyo3_asyncio::tokio::future_into_py(py, async move {
         let total_duration = Duration::from_secs_f64(total_timeout.unwrap_or(30.0));
         let mode = mode.unwrap_or(ConcurrencyMode::SelectAll);
-        let base_client = Some(GLOBAL_CLIENT.lock().await.clone());
+        let base_client = Some(GLOBAL_CLIENT.clone());
 
-        let final_results = match mode {
-            ConcurrencyMode::SelectAll => {
-                execute_with_select_all(requests, total_duration, base_client).await
-            }
-            ConcurrencyMode::JoinAll => {
-                execute_with_join_all(requests, total_duration, base_client).await
+        let run = async move {
+            match mode {
+                ConcurrencyMode::SelectAll => {
+                    execute_with_select_all(requests, total_duration, base_client, ramp_up).await
+                }
+                ConcurrencyMode::JoinAll => {
+                    execute_with_join_all(requests, total_duration, base_client).await
+                }
             }
         };
+        // 每次调用是一个批次，scheduler="fair" 时按 priority 分享全局并发名额
+        let group = Group::new(priority.unwrap_or(1.0));
+        let run = middleware::scope(middleware, run);
+        let owner = raw_body::Owner::default();
+        let run = raw_body::scope(Some(owner.clone()), run);
+        let final_results = group.scope(default_headers::scope(default_headers, run)).await;
+        let final_results = match dedup {
+            Some(plan) => plan.expand(final_results),
+            None => final_results,
+        };
 
         Python::with_gil(|py| -> PyResult<PyObject> {
             let py_list = PyList::empty(py);
-            for res in final_results {
-                let dict = PyDict::new(py);
-                dict.set_item("response", &res["response"])?;
 
-                if let Some(http_status_str) = res.get("http_status") {
-                    if let Ok(http_status_int) = http_status_str.parse::<u16>() {
-                        dict.set_item("http_status", http_status_int)?;
-                    } else {
-                        dict.set_item("http_status", http_status_str)?;
+            // 指定了 fields 时只转换需要的字段
+            if let Some(fields) = &fields {
+                for res in &final_results {
+                    let dict = project_result(py, res, fields)?;
+                    if fields.iter().any(|f| f == "body") {
+                        raw_body::attach(py, res, dict)?;
                     }
+                    py_list.append(dict)?;
                 }
+                return Ok(py_list.into_py(py));
+            }
 
-                let meta_json_str = res.get("meta").map(|s| s.as_str()).unwrap_or("{}");
-                let meta_pyobj = py.import("json")?.call_method1("loads", (meta_json_str,))?;
-                dict.set_item("meta", meta_pyobj)?;
-
-                if let Some(exc_str) = res.get("exception") {
-                    let exc_obj = py.import("json")?.call_method1("loads", (exc_str,))?;
-                    dict.set_item("exception", exc_obj)?;
-                }
-
+            for res in final_results {
+                let dict = batch_result_to_py(py, &res)?;
+                raw_body::attach(py, &res, dict.as_ref(py).downcast()?)?;
                 py_list.append(dict)?;
             }
             Ok(py_list.into_py(py))
         })
     })
 }
+
+/// fetch_requests 风格的返回值：meta 和 exception 解析为 dict
+pub(crate) fn batch_result_to_py(py: Python, res: &HashMap<String, String>) -> PyResult<Py<PyAny>> {
+    let dict = PyDict::new(py);
+    dict.set_item("response", res.get("response").map(|s| s.as_str()).unwrap_or(""))?;
+
+    if let Some(http_status_str) = res.get("http_status") {
+        if let Ok(http_status_int) = http_status_str.parse::<u16>() {
+            dict.set_item("http_status", http_status_int)?;
+        } else {
+            dict.set_item("http_status", http_status_str)?;
+        }
+    }
+
+    let meta_json_str = res.get("meta").map(|s| s.as_str()).unwrap_or("{}");
+    let meta_pyobj = py.import("json")?.call_method1("loads", (meta_json_str,))?;
+    dict.set_item("meta", meta_pyobj)?;
+
+    if let Some(exc_str) = res.get("exception") {
+        let exc_obj = py.import("json")?.call_method1("loads", (exc_str,))?;
+        dict.set_item("exception", exc_obj)?;
+    }
+    Ok(dict.into_py(py))
+}
@@ -0,0 +1,136 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+// 0 号分组：不属于任何 fetch_requests 批次的请求
+const DEFAULT_GROUP: Group = Group { id: 0, weight: 1.0 };
+static NEXT_GROUP_ID: AtomicU64 = AtomicU64::new(1);
+
+tokio::task_local! {
+    // 当前请求所属的批次
+    static BATCH_GROUP: Group;
+}
+
+/// 共享全局并发名额的一个批次，weight 即 fetch_requests 的 priority
+#[derive(Clone, Copy)]
+pub(crate) struct Group {
+    id: u64,
+    weight: f64,
+}
+
+impl Group {
+    /// 为新的批次分配分组
+    pub(crate) fn new(weight: f64) -> Self {
+        Group { id: NEXT_GROUP_ID.fetch_add(1, Ordering::Relaxed), weight }
+    }
+
+    /// 当前任务所属的分组，批次外的请求归入默认分组
+    pub(crate) fn current() -> Self {
+        BATCH_GROUP.try_with(|g| *g).unwrap_or(DEFAULT_GROUP)
+    }
+
+    /// 在 fut 执行期间属于该分组
+    pub(crate) async fn scope<F: Future>(self, fut: F) -> F::Output {
+        BATCH_GROUP.scope(self, fut).await
+    }
+}
+
+#[derive(Default)]
+struct GroupState {
+    weight: f64,
+    active: usize,
+    waiters: VecDeque<oneshot::Sender<FairPermit>>,
+}
+
+struct State {
+    capacity: usize,
+    in_use: usize,
+    groups: HashMap<u64, GroupState>,
+}
+
+/// 按权重公平分配的并发名额
+///
+/// 有名额空出时交给「占用数 / 权重」最小的等待批次，而不是最早等待的请求，
+/// 大批量的后台抓取不会饿死同时运行的其他批次。
+pub(crate) struct FairQueue {
+    state: Mutex<State>,
+}
+
+/// 持有期间占用一个名额，drop 时交给下一个等待者
+pub(crate) struct FairPermit {
+    queue: Arc<FairQueue>,
+    group: u64,
+    armed: bool,
+}
+
+impl FairQueue {
+    pub(crate) fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(FairQueue { state: Mutex::new(State { capacity, in_use: 0, groups: HashMap::new() }) })
+    }
+
+    pub(crate) async fn acquire(self: &Arc<Self>, group: Group) -> FairPermit {
+        let receiver = {
+            let mut guard = self.state.lock().unwrap();
+            let state = &mut *guard;
+            let waiting = state.groups.values().any(|g| !g.waiters.is_empty());
+            let entry = state.groups.entry(group.id).or_default();
+            entry.weight = group.weight;
+            // 没有其他等待者时直接占用空闲名额
+            if !waiting && state.in_use < state.capacity {
+                entry.active += 1;
+                state.in_use += 1;
+                return FairPermit { queue: self.clone(), group: group.id, armed: true };
+            }
+            let (sender, receiver) = oneshot::channel();
+            entry.waiters.push_back(sender);
+            receiver
+        };
+        // 名额在送达前被取消时随 channel 一起 drop，自动归还
+        receiver.await.expect("fair queue dropped a waiter")
+    }
+
+    fn release(self: &Arc<Self>, group: u64) {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        state.in_use -= 1;
+        if let Some(entry) = state.groups.get_mut(&group) {
+            entry.active -= 1;
+        }
+        self.dispatch(state);
+    }
+
+    fn dispatch(self: &Arc<Self>, state: &mut State) {
+        while state.in_use < state.capacity {
+            // 占用比例最小的批次优先，相同时先创建的批次优先
+            let next = state.groups.iter()
+                .filter(|(_, g)| !g.waiters.is_empty())
+                .min_by(|(a_id, a), (b_id, b)| {
+                    let share = |g: &GroupState| g.active as f64 / g.weight;
+                    share(a).total_cmp(&share(b)).then(a_id.cmp(b_id))
+                })
+                .map(|(id, _)| *id);
+            let Some(id) = next else { break };
+            let entry = state.groups.get_mut(&id).unwrap();
+            let sender = entry.waiters.pop_front().unwrap();
+            entry.active += 1;
+            state.in_use += 1;
+            if let Err(mut permit) = sender.send(FairPermit { queue: self.clone(), group: id, armed: true }) {
+                // 等待者已取消，撤销占用
+                permit.armed = false;
+                entry.active -= 1;
+                state.in_use -= 1;
+            }
+        }
+        state.groups.retain(|_, g| g.active > 0 || !g.waiters.is_empty());
+    }
+}
+
+impl Drop for FairPermit {
+    fn drop(&mut self) {
+        if self.armed {
+            self.queue.release(self.group);
+        }
+    }
+}
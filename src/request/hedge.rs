@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use serde_json::Value;
+use crate::request::PreparedRequest;
+use crate::testing::clock;
+
+// 重复发送没有副作用的方法
+const IDEMPOTENT: &[&str] = &["GET", "HEAD", "OPTIONS"];
+
+/// 没有收到响应的结果（连接失败、超时等）
+fn failed(result: &HashMap<String, String>) -> bool {
+    result.get("http_status").map_or(true, |s| s == "0")
+}
+
+fn mark(result: &mut HashMap<String, String>, hedge_won: bool) {
+    if let Some(Value::Object(mut meta)) = result.get("meta").and_then(|m| serde_json::from_str(m).ok()) {
+        meta.insert("hedged".to_string(), Value::Bool(true));
+        meta.insert("hedge_won".to_string(), Value::Bool(hedge_won));
+        result.insert("meta".to_string(), Value::Object(meta).to_string());
+    }
+}
+
+/// 对冲请求：超过 hedge_after 仍未完成时再发送一份，取先完成的结果并取消另一份
+///
+/// 先完成的一份没有收到响应时继续等待另一份。
+pub(crate) async fn run<F, Fut>(prepared: PreparedRequest, send: F) -> HashMap<String, String>
+where
+    F: Fn(PreparedRequest) -> Fut,
+    Fut: Future<Output = HashMap<String, String>>,
+{
+    let delay = prepared.request.hedge_after.filter(|_| IDEMPOTENT.contains(&prepared.method.as_str()));
+    let Some(delay) = delay else { return send(prepared).await };
+
+    let primary = send(prepared.clone());
+    tokio::pin!(primary);
+    tokio::select! {
+        result = &mut primary => return result,
+        _ = clock::sleep(Duration::from_secs_f64(delay)) => {}
+    }
+
+    let hedge = send(prepared);
+    tokio::pin!(hedge);
+    // 返回时丢弃仍在进行的一份，连接随之关闭
+    let (mut result, hedge_won) = tokio::select! {
+        result = &mut primary => match failed(&result) {
+            true => (hedge.await, true),
+            false => (result, false),
+        },
+        result = &mut hedge => match failed(&result) {
+            true => (primary.await, false),
+            false => (result, true),
+        },
+    };
+    mark(&mut result, hedge_won);
+    result
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+use crate::request::PreparedRequest;
+use crate::request::executor::batch_result_to_py;
+use crate::utils::py_to_json;
+
+#[derive(Default)]
+struct Hooks {
+    on_request: Option<PyObject>,
+    on_response: Option<PyObject>,
+}
+
+// 未设置钩子时不获取 GIL
+static HOOKS_SET: AtomicBool = AtomicBool::new(false);
+static HOOKS: Lazy<RwLock<Hooks>> = Lazy::new(|| RwLock::new(Hooks::default()));
+
+/// 设置在每个请求前后调用的 Python 函数，替换之前的设置；都为 None 时清除
+///
+/// on_request(prepared) 可以修改 PreparedRequest 的 method、url、headers、body，
+/// 返回 dict 时不发送请求，直接作为结果；on_response(result) 返回 dict 时替换结果。
+#[pyfunction]
+#[pyo3(signature = (on_request=None, on_response=None))]
+pub fn set_hooks(py: Python, on_request: Option<PyObject>, on_response: Option<PyObject>) -> PyResult<()> {
+    for (name, hook) in [("on_request", &on_request), ("on_response", &on_response)] {
+        if hook.as_ref().is_some_and(|h| !h.as_ref(py).is_callable()) {
+            return Err(PyTypeError::new_err(format!("{} must be callable", name)));
+        }
+    }
+    HOOKS_SET.store(on_request.is_some() || on_response.is_some(), Ordering::Release);
+    *HOOKS.write().unwrap() = Hooks { on_request, on_response };
+    Ok(())
+}
+
+/// on_request 的处理结果
+pub(crate) enum Before {
+    Send(Box<PreparedRequest>),
+    Respond(HashMap<String, String>),
+}
+
+/// 调用 on_request；钩子抛出异常或返回值类型不对时返回错误信息
+pub(crate) fn before(prepared: PreparedRequest) -> Result<Before, String> {
+    if !HOOKS_SET.load(Ordering::Acquire) {
+        return Ok(Before::Send(Box::new(prepared)));
+    }
+    let hook = Python::with_gil(|py| HOOKS.read().unwrap().on_request.as_ref().map(|h| h.clone_ref(py)));
+    match hook {
+        Some(hook) => call_before(&hook, "on_request", prepared),
+        None => Ok(Before::Send(Box::new(prepared))),
+    }
+}
+
+/// 调用 on_response；钩子失败时保留原结果并把异常记为 HookError
+pub(crate) fn after(result: HashMap<String, String>) -> HashMap<String, String> {
+    if !HOOKS_SET.load(Ordering::Acquire) {
+        return result;
+    }
+    let hook = Python::with_gil(|py| HOOKS.read().unwrap().on_response.as_ref().map(|h| h.clone_ref(py)));
+    match hook {
+        Some(hook) => call_after(&hook, "on_response", None, result, "HookError"),
+        None => result,
+    }
+}
+
+/// 以 PreparedRequest 调用 Python 函数：返回 None 时发送（可能已修改的）请求，返回 dict 时作为结果
+pub(crate) fn call_before(hook: &PyObject, name: &str, prepared: PreparedRequest) -> Result<Before, String> {
+    Python::with_gil(|py| {
+        let cell = Py::new(py, prepared).map_err(|e| e.to_string())?;
+        let returned = hook.call1(py, (cell.clone_ref(py),)).map_err(|e| format!("{} failed: {}", name, e))?;
+        let returned = returned.as_ref(py);
+        if returned.is_none() {
+            return Ok(Before::Send(Box::new(cell.borrow(py).clone())));
+        }
+        match returned.downcast::<PyDict>() {
+            Ok(dict) => result_from_py(py, dict).map(Before::Respond).map_err(|e| format!("{} returned an invalid result: {}", name, e)),
+            Err(_) => Err(format!("{} must return None or a dict, got {}", name, returned.get_type().name().unwrap_or("?"))),
+        }
+    })
+}
+
+/// 以结果（prepared 不为 None 时为 (prepared, result)）调用 Python 函数，返回 dict 时替换结果
+///
+/// 调用失败时保留原结果，异常记为 exc_type。
+pub(crate) fn call_after(
+    hook: &PyObject,
+    name: &str,
+    prepared: Option<&PreparedRequest>,
+    mut result: HashMap<String, String>,
+    exc_type: &str,
+) -> HashMap<String, String> {
+    let replaced = Python::with_gil(|py| -> Result<Option<HashMap<String, String>>, String> {
+        let arg = batch_result_to_py(py, &result).map_err(|e| e.to_string())?;
+        let returned = match prepared {
+            Some(prepared) => hook.call1(py, (prepared.clone(), arg)),
+            None => hook.call1(py, (arg,)),
+        }.map_err(|e| format!("{} failed: {}", name, e))?;
+        let returned = returned.as_ref(py);
+        if returned.is_none() {
+            return Ok(None);
+        }
+        match returned.downcast::<PyDict>() {
+            Ok(dict) => result_from_py(py, dict).map(Some).map_err(|e| format!("{} returned an invalid result: {}", name, e)),
+            Err(_) => Err(format!("{} must return None or a dict, got {}", name, returned.get_type().name().unwrap_or("?"))),
+        }
+    });
+    match replaced {
+        Ok(Some(replaced)) => replaced,
+        Ok(None) => result,
+        Err(message) => {
+            let exc = serde_json::json!({"type": exc_type, "message": message});
+            result.insert("exception".to_string(), exc.to_string());
+            result
+        }
+    }
+}
+
+/// 把 fetch_requests 风格的结果 dict 转回内部结果；字符串原样保留，其余序列化为 JSON
+fn result_from_py(py: Python, dict: &PyDict) -> PyResult<HashMap<String, String>> {
+    let mut result = HashMap::new();
+    for (key, default) in [("response", ""), ("http_status", "0"), ("meta", "{}"), ("exception", "{}")] {
+        let value = match dict.get_item(key)? {
+            None => default.to_string(),
+            Some(v) if v.is_none() => default.to_string(),
+            Some(v) => match v.extract::<String>() {
+                Ok(s) => s,
+                Err(_) => match py_to_json(py, v)? {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                },
+            },
+        };
+        result.insert(key.to_string(), value);
+    }
+    Ok(result)
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use url::Url;
+use crate::request::RequestItem;
+use crate::request::default_headers::parse_headers;
+use crate::request::retry::RetryPolicy;
+use crate::request::timeouts::check_positive;
+
+/// 某个上游主机的默认设置，只补全请求中未设置的选项
+#[derive(Default)]
+pub(crate) struct HostProfile {
+    timeout: Option<f64>,
+    connect_timeout: Option<f64>,
+    read_timeout: Option<f64>,
+    pub(crate) retry: Option<RetryPolicy>,
+    /// 每秒最多发出的请求数，按 host:port 计算
+    pub(crate) rate_limit: Option<f64>,
+    headers: Vec<(String, String)>,
+}
+
+// 主机名（小写）或 "*.example.com" 到配置的映射
+static PROFILES: Lazy<RwLock<HashMap<String, Arc<HostProfile>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 为主机设置配置，替换该主机已有的配置
+///
+/// host 为主机名或 "*.example.com"（匹配所有子域名）；精确匹配优先，其次是最长的通配后缀。
+#[pyfunction]
+#[pyo3(signature = (host, timeout=None, connect_timeout=None, read_timeout=None, retry=None, rate_limit=None, headers=None))]
+pub fn set_host_profile(
+    host: &str,
+    timeout: Option<f64>,
+    connect_timeout: Option<f64>,
+    read_timeout: Option<f64>,
+    retry: Option<RetryPolicy>,
+    rate_limit: Option<f64>,
+    headers: Option<HashMap<String, String>>,
+) -> PyResult<()> {
+    let host = check_host(host)?;
+    check_positive("timeout", timeout)?;
+    check_positive("connect_timeout", connect_timeout)?;
+    check_positive("read_timeout", read_timeout)?;
+    if rate_limit.is_some_and(|r| !r.is_finite() || r <= 0.0) {
+        return Err(PyValueError::new_err("rate_limit must be a positive number of requests per second"));
+    }
+    let headers = parse_headers(headers.unwrap_or_default())?;
+    let profile = HostProfile { timeout, connect_timeout, read_timeout, retry, rate_limit, headers };
+    PROFILES.write().unwrap().insert(host, Arc::new(profile));
+    Ok(())
+}
+
+/// 删除主机的配置，返回之前是否存在
+#[pyfunction]
+pub fn remove_host_profile(host: &str) -> bool {
+    PROFILES.write().unwrap().remove(&host.to_ascii_lowercase()).is_some()
+}
+
+fn check_host(host: &str) -> PyResult<String> {
+    let host = host.to_ascii_lowercase();
+    let name = host.strip_prefix("*.").unwrap_or(&host);
+    if name.is_empty() || name.contains(['*', '/', ':']) || Url::parse(&format!("http://{}/", name)).is_err() {
+        return Err(PyValueError::new_err(format!("Invalid host: '{}', expected a host name such as 'api.example.com' or '*.example.com'", host)));
+    }
+    Ok(host)
+}
+
+/// 查找 URL 对应的配置
+pub(crate) fn lookup(url: &str) -> Option<Arc<HostProfile>> {
+    let profiles = PROFILES.read().unwrap();
+    if profiles.is_empty() {
+        return None;
+    }
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    if let Some(profile) = profiles.get(&host) {
+        return Some(profile.clone());
+    }
+    // a.b.example.com 依次尝试 *.b.example.com、*.example.com、*.com
+    host.match_indices('.')
+        .find_map(|(i, _)| profiles.get(&format!("*{}", &host[i..])))
+        .cloned()
+}
+
+impl HostProfile {
+    /// 补全请求中未设置的超时
+    pub(crate) fn fill(&self, req: &mut RequestItem) {
+        req.timeout = req.timeout.or(self.timeout);
+        req.connect_timeout = req.connect_timeout.or(self.connect_timeout);
+        req.read_timeout = req.read_timeout.or(self.read_timeout);
+    }
+
+    /// 补全请求中没有的请求头，优先于批量和全局默认请求头
+    pub(crate) fn merge_headers(&self, headers: &mut Vec<(String, String)>, has_auth: bool) {
+        for (name, value) in &self.headers {
+            if has_auth && name.eq_ignore_ascii_case("authorization") {
+                continue;
+            }
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name)) {
+                headers.push((name.clone(), value.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use once_cell::sync::OnceCell;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use tokio::runtime::{Builder, Runtime};
+use tokio::task::AbortHandle;
+
+const THREAD_NAME: &str = "rusty-req-isolated";
+const DEFAULT_WORKER_THREADS: usize = 2;
+
+static WORKER_THREADS: AtomicUsize = AtomicUsize::new(DEFAULT_WORKER_THREADS);
+// 第一次执行 isolated 请求时创建，之后不再变化
+static ISOLATED_RUNTIME: OnceCell<Runtime> = OnceCell::new();
+
+/// 设置隔离线程池的线程数，必须在第一个 isolated 请求之前调用
+#[pyfunction]
+pub fn configure_isolated_pool(worker_threads: usize) -> PyResult<()> {
+    if worker_threads == 0 {
+        return Err(PyValueError::new_err("worker_threads must be at least 1"));
+    }
+    if ISOLATED_RUNTIME.get().is_some() {
+        return Err(PyRuntimeError::new_err("the isolated pool is already running; configure it before the first isolated request"));
+    }
+    WORKER_THREADS.store(worker_threads, Ordering::Relaxed);
+    Ok(())
+}
+
+fn runtime() -> &'static Runtime {
+    ISOLATED_RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .worker_threads(WORKER_THREADS.load(Ordering::Relaxed))
+            .thread_name(THREAD_NAME)
+            .enable_all()
+            .build()
+            .expect("Failed to create isolated runtime")
+    })
+}
+
+/// 隔离线程池的线程数
+pub(crate) fn worker_threads() -> usize {
+    WORKER_THREADS.load(Ordering::Relaxed)
+}
+
+/// 当前是否运行在隔离线程池中
+pub(crate) fn on_isolated_runtime() -> bool {
+    std::thread::current().name() == Some(THREAD_NAME)
+}
+
+// 调用方放弃等待（如 total_timeout）时取消隔离池中的任务
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// 在隔离线程池中执行 future 并等待结果；任务被取消时返回 None
+pub(crate) async fn run_isolated<F>(fut: F) -> Option<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let handle = runtime().spawn(fut);
+    let _guard = AbortOnDrop(handle.abort_handle());
+    match handle.await {
+        Ok(output) => Some(output),
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => None,
+    }
+}
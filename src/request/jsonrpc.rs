@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use futures::future::join_all;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::{json, Value};
+use crate::request::{PreparedRequest, RequestItem};
+use crate::request::default_headers::parse_headers;
+use crate::request::executor::{batch_result_to_py, early_error_result, execute_prepared};
+use crate::request::timeouts::check_positive;
+use crate::utils::{normalize_url, py_to_json};
+use crate::GLOBAL_CLIENT_CONFIG;
+
+// 未指定 id 的调用使用进程内递增的 id
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 一个 JSON-RPC 2.0 调用
+#[pyclass]
+#[derive(Clone)]
+pub struct JsonRpcRequest {
+    #[pyo3(get, set)]
+    pub url: String,
+    #[pyo3(get, set)]
+    pub method: String,
+    /// 位置参数（list）或命名参数（dict）
+    #[pyo3(get)]
+    pub params: Option<PyObject>,
+    /// int 或 str；None 时自动生成
+    #[pyo3(get)]
+    pub id: Option<PyObject>,
+    /// 通知不带 id，服务端不返回结果
+    #[pyo3(get, set)]
+    pub notification: bool,
+    #[pyo3(get, set)]
+    pub headers: Option<HashMap<String, String>>,
+    #[pyo3(get, set)]
+    pub timeout: Option<f64>,
+    #[pyo3(get, set)]
+    pub tag: Option<String>,
+}
+
+#[pymethods]
+impl JsonRpcRequest {
+    #[new]
+    #[pyo3(signature = (url, method, params=None, id=None, notification=false, headers=None, timeout=None, tag=None))]
+    fn new(
+        py: Python,
+        url: String,
+        method: String,
+        params: Option<PyObject>,
+        id: Option<PyObject>,
+        notification: bool,
+        headers: Option<HashMap<String, String>>,
+        timeout: Option<f64>,
+        tag: Option<String>,
+    ) -> PyResult<Self> {
+        if let Some(params) = &params {
+            if !matches!(py_to_json(py, params.as_ref(py))?, Value::Array(_) | Value::Object(_)) {
+                return Err(PyValueError::new_err("params must be a list or a dict"));
+            }
+        }
+        if let Some(id) = &id {
+            if notification {
+                return Err(PyValueError::new_err("a notification cannot have an id"));
+            }
+            if !matches!(py_to_json(py, id.as_ref(py))?, Value::Number(_) | Value::String(_)) {
+                return Err(PyValueError::new_err("id must be an int or a str"));
+            }
+        }
+        if let Some(headers) = &headers {
+            parse_headers(headers.clone())?;
+        }
+        check_positive("timeout", timeout)?;
+        Ok(JsonRpcRequest { url, method, params, id, notification, headers, timeout, tag })
+    }
+}
+
+/// 一个 HTTP 请求中的调用
+struct Call {
+    index: usize,
+    id: Option<Value>,
+    envelope: Value,
+}
+
+/// 共用同一个 HTTP 请求的调用需要相同的地址、请求头和超时
+type GroupKey = (String, Vec<(String, String)>, Option<u64>);
+
+fn group_key(req: &JsonRpcRequest) -> GroupKey {
+    let mut headers: Vec<(String, String)> = req.headers.clone().unwrap_or_default().into_iter()
+        .map(|(k, v)| (k.to_ascii_lowercase(), v))
+        .collect();
+    headers.sort();
+    (req.url.clone(), headers, req.timeout.map(f64::to_bits))
+}
+
+/// 把批量响应中 id 对应的结果拆成单个调用的结果
+fn split_result(http: &HashMap<String, String>, calls: &[Call]) -> Vec<(usize, HashMap<String, String>)> {
+    let response: Value = http.get("response").and_then(|r| serde_json::from_str(r).ok()).unwrap_or(Value::Null);
+    let meta: Value = http.get("meta").and_then(|m| serde_json::from_str(m).ok()).unwrap_or(json!({}));
+    let http_failed = http.get("exception").is_some_and(|e| e != "{}" && !e.is_empty());
+    let content = response["content"].as_str().unwrap_or_default();
+    let parsed: Option<Value> = serde_json::from_str(content).ok();
+    // 单个调用的响应是对象，批量调用的响应是数组
+    let replies: Vec<Value> = match parsed {
+        Some(Value::Array(items)) => items,
+        Some(item @ Value::Object(_)) => vec![item],
+        _ => Vec::new(),
+    };
+    let by_id: HashMap<String, &Value> = replies.iter()
+        .filter_map(|r| Some((r.get("id")?.to_string(), r)))
+        .collect();
+
+    calls.iter().map(|call| {
+        let mut result = http.clone();
+        let mut meta = meta.clone();
+        meta["jsonrpc"] = json!({"id": call.id, "method": call.envelope["method"], "batch_size": calls.len()});
+        result.insert("meta".to_string(), meta.to_string());
+        let Some(id) = &call.id else {
+            // 通知没有响应
+            result.insert("response".to_string(), json!({"headers": response["headers"], "content": ""}).to_string());
+            return (call.index, result);
+        };
+        if http_failed {
+            return (call.index, result);
+        }
+        let exception = match by_id.get(&id.to_string()) {
+            Some(reply) => {
+                result.insert("response".to_string(), json!({"headers": response["headers"], "content": reply.to_string()}).to_string());
+                reply.get("error").map(|error| json!({
+                    "type": "JsonRpcError",
+                    "message": error["message"].as_str().unwrap_or("JSON-RPC error"),
+                    "code": error["code"],
+                    "data": error.get("data"),
+                }))
+            }
+            None if replies.is_empty() => Some(json!({"type": "JsonRpcError", "message": "Response is not a JSON-RPC response"})),
+            None => Some(json!({"type": "JsonRpcError", "message": format!("No response for id {}", id)})),
+        };
+        if let Some(exception) = exception {
+            result.insert("exception".to_string(), exception.to_string());
+        }
+        (call.index, result)
+    }).collect()
+}
+
+/// 发送 JSON-RPC 2.0 调用，相同地址的调用合并为批量请求，按 id 对应回每个调用
+///
+/// batch_size 限制每个 HTTP 请求中的调用数，None 为不限，1 为不合并。
+/// 返回结构与 fetch_requests 相同，顺序与 requests 一致；response.content 为该调用的响应对象。
+#[pyfunction]
+#[pyo3(signature = (requests, batch_size=None))]
+pub fn fetch_jsonrpc<'py>(py: Python<'py>, requests: Vec<JsonRpcRequest>, batch_size: Option<usize>) -> PyResult<&'py PyAny> {
+    if batch_size == Some(0) {
+        return Err(PyValueError::new_err("batch_size must be at least 1"));
+    }
+    let default_scheme = GLOBAL_CLIENT_CONFIG.read().unwrap().default_scheme.clone();
+    let mut groups: Vec<(GroupKey, Vec<Call>)> = Vec::new();
+    let mut items: Vec<JsonRpcRequest> = Vec::with_capacity(requests.len());
+    for (index, req) in requests.into_iter().enumerate() {
+        let id = match (&req.id, req.notification) {
+            (_, true) => None,
+            (Some(id), false) => Some(py_to_json(py, id.as_ref(py))?),
+            (None, false) => Some(Value::from(NEXT_ID.fetch_add(1, Ordering::Relaxed))),
+        };
+        let mut envelope = json!({"jsonrpc": "2.0", "method": req.method});
+        if let Some(params) = &req.params {
+            envelope["params"] = py_to_json(py, params.as_ref(py))?;
+        }
+        if let Some(id) = &id {
+            envelope["id"] = id.clone();
+        }
+        let key = group_key(&req);
+        // 同一批中的 id 不能重复，否则无法对应响应
+        let fits = |calls: &Vec<Call>| {
+            batch_size.map_or(true, |n| calls.len() < n) && (id.is_none() || !calls.iter().any(|c| c.id == id))
+        };
+        let slot = groups.iter_mut().position(|(k, calls)| *k == key && fits(calls));
+        let call = Call { index, id, envelope };
+        match slot.map(|i| &mut groups[i]) {
+            Some((_, calls)) => calls.push(call),
+            None => groups.push((key, vec![call])),
+        }
+        items.push(req);
+    }
+
+    // 每组一个 HTTP 请求，使用组内第一个调用的选项
+    let mut batches = Vec::with_capacity(groups.len());
+    for (_, calls) in groups {
+        let first = &items[calls[0].index];
+        let headers = PyDict::new(py);
+        for (name, value) in first.headers.iter().flatten() {
+            headers.set_item(name, value)?;
+        }
+        if !headers.keys().iter().any(|k| k.extract::<String>().is_ok_and(|k| k.eq_ignore_ascii_case("content-type"))) {
+            headers.set_item("Content-Type", "application/json")?;
+        }
+        let item = RequestItem {
+            url: first.url.clone(),
+            method: Some("POST".to_string()),
+            headers: Some(headers.into()),
+            timeout: first.timeout,
+            tag: first.tag.clone(),
+            ..Default::default()
+        };
+        let body = match calls.as_slice() {
+            [single] => single.envelope.clone(),
+            _ => Value::Array(calls.iter().map(|c| c.envelope.clone()).collect()),
+        };
+        let prepared = normalize_url(&item.url, default_scheme.as_deref().unwrap_or("http"))
+            .map_err(|e| early_error_result(&item, "InvalidUrl", e))
+            .and_then(|url| PreparedRequest::from_item(py, &item, url)
+                .map_err(|e| early_error_result(&item, "SerializeError", e.to_string())))
+            .map(|mut prepared| {
+                prepared.body = Some(body.to_string().into_bytes());
+                prepared
+            });
+        batches.push((prepared, calls));
+    }
+
+    let total = items.len();
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let sent = join_all(batches.into_iter().map(|(prepared, calls)| async move {
+            let http = match prepared {
+                Ok(prepared) => execute_prepared(prepared).await,
+                Err(result) => result,
+            };
+            split_result(&http, &calls)
+        })).await;
+
+        let mut results: Vec<Option<HashMap<String, String>>> = vec![None; total];
+        for (index, result) in sent.into_iter().flatten() {
+            results[index] = Some(result);
+        }
+        Python::with_gil(|py| -> PyResult<PyObject> {
+            let list = PyList::empty(py);
+            for result in results.into_iter().flatten() {
+                list.append(batch_result_to_py(py, &result)?)?;
+            }
+            Ok(list.into_py(py))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(index: usize, id: Option<Value>) -> Call {
+        Call { index, id, envelope: json!({"jsonrpc": "2.0", "method": format!("m{}", index)}) }
+    }
+
+    fn http(content: &str, exception: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("response".to_string(), json!({"headers": {}, "content": content}).to_string()),
+            ("http_status".to_string(), "200".to_string()),
+            ("exception".to_string(), exception.to_string()),
+            ("meta".to_string(), "{}".to_string()),
+        ])
+    }
+
+    fn content(result: &HashMap<String, String>) -> Value {
+        let response: Value = serde_json::from_str(&result["response"]).unwrap();
+        serde_json::from_str(response["content"].as_str().unwrap()).unwrap_or(Value::Null)
+    }
+
+    #[test]
+    fn maps_out_of_order_replies_by_id() {
+        // 整数 1 与字符串 "1" 是不同的 id
+        let calls = [call(0, Some(json!(1))), call(1, Some(json!("1"))), call(2, None)];
+        let body = r#"[{"jsonrpc":"2.0","id":"1","result":"b"},{"jsonrpc":"2.0","id":1,"result":"a"}]"#;
+        let results = split_result(&http(body, "{}"), &calls);
+        assert_eq!(results.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(content(&results[0].1)["result"], "a");
+        assert_eq!(content(&results[1].1)["result"], "b");
+        assert_eq!(content(&results[2].1), Value::Null);
+        assert_eq!(results[0].1["exception"], "{}");
+        let meta: Value = serde_json::from_str(&results[1].1["meta"]).unwrap();
+        assert_eq!(meta["jsonrpc"], json!({"id": "1", "method": "m1", "batch_size": 3}));
+    }
+
+    #[test]
+    fn reports_errors_and_missing_replies() {
+        let calls = [call(0, Some(json!(1))), call(1, Some(json!(2)))];
+        let body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Method not found"}}"#;
+        let results = split_result(&http(body, "{}"), &calls);
+        let exception: Value = serde_json::from_str(&results[0].1["exception"]).unwrap();
+        assert_eq!(exception["type"], "JsonRpcError");
+        assert_eq!(exception["code"], -32601);
+        let exception: Value = serde_json::from_str(&results[1].1["exception"]).unwrap();
+        assert_eq!(exception["message"], "No response for id 2");
+
+        let results = split_result(&http("not json", "{}"), &calls);
+        let exception: Value = serde_json::from_str(&results[0].1["exception"]).unwrap();
+        assert_eq!(exception["message"], "Response is not a JSON-RPC response");
+
+        // HTTP 层失败时保留原来的异常
+        let failed = r#"{"type":"HttpStatusError","message":"HTTP status error: 500"}"#;
+        let results = split_result(&http("", failed), &calls);
+        assert!(results.iter().all(|(_, r)| r["exception"] == failed));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use crate::request::fair::{FairPermit, FairQueue, Group};
+
+/// 全局并发名额的分配方式
+pub(crate) const SCHEDULERS: [&str; 2] = ["fifo", "fair"];
+
+/// 进程级的并发和 QPS 限制，对所有 fetch_single / fetch_requests 调用生效
+#[derive(Default)]
+struct GlobalLimiter {
+    in_flight: Option<Arc<Semaphore>>,
+    // scheduler="fair" 时代替 in_flight
+    fair: Option<Arc<FairQueue>>,
+    fair_scheduling: bool,
+    interval: Option<Duration>,
+    max_in_flight: Option<usize>,
+    max_rps: Option<f64>,
+    max_per_host: Option<usize>,
+}
+
+type HostSlots = HashMap<(String, usize), Arc<Semaphore>>;
+
+static LIMITER: Lazy<RwLock<GlobalLimiter>> = Lazy::new(|| RwLock::new(GlobalLimiter::default()));
+// 下一个请求可以发出的时间
+static NEXT_SLOT: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+// 每个主机（host:port）的并发名额；键包含上限，修改上限后使用新的信号量
+static HOST_SLOTS: Lazy<std::sync::Mutex<HostSlots>> = Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+// 每个主机（host:port）下一个请求可以发出的时间，用于主机配置的 rate_limit
+static HOST_NEXT_SLOT: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 超过该数量时清理没有请求在使用的主机
+const HOST_SLOTS_PRUNE_AT: usize = 1024;
+
+/// 请求结束前保持持有的名额
+pub(crate) struct Permits {
+    _slots: Vec<OwnedSemaphorePermit>,
+    _fair: Option<FairPermit>,
+}
+
+/// 更新限制，0 表示不限制；已在等待或执行中的请求继续使用旧的限制
+///
+/// scheduler 为 "fifo"（按等待顺序）或 "fair"（按批次 priority 加权分配 max_in_flight）。
+pub(crate) fn configure(max_in_flight: Option<usize>, max_rps: Option<f64>, max_per_host: Option<usize>, scheduler: Option<&str>) {
+    let mut limiter = LIMITER.write().unwrap();
+    if let Some(max) = max_in_flight {
+        limiter.max_in_flight = (max > 0).then_some(max);
+    }
+    if let Some(scheduler) = scheduler {
+        limiter.fair_scheduling = scheduler == "fair";
+    }
+    if max_in_flight.is_some() || scheduler.is_some() {
+        let max = limiter.max_in_flight;
+        let fair = limiter.fair_scheduling;
+        limiter.in_flight = max.filter(|_| !fair).map(|max| Arc::new(Semaphore::new(max)));
+        limiter.fair = max.filter(|_| fair).map(FairQueue::new);
+    }
+    if let Some(rps) = max_rps {
+        limiter.interval = (rps > 0.0).then(|| Duration::from_secs_f64(1.0 / rps));
+        limiter.max_rps = (rps > 0.0).then_some(rps);
+    }
+    if let Some(max) = max_per_host {
+        limiter.max_per_host = (max > 0).then_some(max);
+    }
+}
+
+/// 当前生效的 (max_in_flight, max_rps, max_connections_per_host)，None 表示不限制
+pub(crate) fn limits() -> (Option<usize>, Option<f64>, Option<usize>) {
+    let limiter = LIMITER.read().unwrap();
+    (limiter.max_in_flight, limiter.max_rps, limiter.max_per_host)
+}
+
+/// 当前的调度方式
+pub(crate) fn scheduler() -> &'static str {
+    if LIMITER.read().unwrap().fair_scheduling { "fair" } else { "fifo" }
+}
+
+/// 主机的并发名额
+fn host_slots(host: &str, max: usize) -> Arc<Semaphore> {
+    let mut slots = HOST_SLOTS.lock().unwrap();
+    if slots.len() >= HOST_SLOTS_PRUNE_AT {
+        slots.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+    }
+    slots.entry((host.to_string(), max)).or_insert_with(|| Arc::new(Semaphore::new(max))).clone()
+}
+
+/// 等待主机名额、并发名额和 QPS 时间片，返回的 permit 在请求结束前保持持有
+///
+/// host 为 "host:port"；主机名额先于全局名额获取，等待同一主机时不占用全局名额。
+/// group 为请求所属的批次，只在 scheduler="fair" 时使用；rate_limit 为该主机每秒的请求数。
+pub(crate) async fn acquire(host: Option<&str>, group: Group, rate_limit: Option<f64>) -> Permits {
+    let (semaphore, fair, interval, max_per_host) = {
+        let limiter = LIMITER.read().unwrap();
+        (limiter.in_flight.clone(), limiter.fair.clone(), limiter.interval, limiter.max_per_host)
+    };
+
+    let mut slots = Vec::new();
+    if let (Some(host), Some(max)) = (host, max_per_host) {
+        slots.extend(host_slots(host, max).acquire_owned().await.ok());
+    }
+    if let Some(semaphore) = semaphore {
+        slots.extend(semaphore.acquire_owned().await.ok());
+    }
+    let fair = match fair {
+        Some(queue) => Some(queue.acquire(group).await),
+        None => None,
+    };
+
+    if let Some(interval) = interval {
+        let slot = {
+            let mut next = NEXT_SLOT.lock().await;
+            let now = Instant::now();
+            let slot = next.map_or(now, |n| n.max(now));
+            *next = Some(slot + interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    if let (Some(host), Some(rps)) = (host, rate_limit) {
+        let interval = Duration::from_secs_f64(1.0 / rps);
+        let slot = {
+            let mut slots = HOST_NEXT_SLOT.lock().await;
+            let now = Instant::now();
+            if slots.len() >= HOST_SLOTS_PRUNE_AT {
+                slots.retain(|_, next| *next > now);
+            }
+            let next = slots.entry(host.to_string()).or_insert(now);
+            let slot = (*next).max(now);
+            *next = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    Permits { _slots: slots, _fair: fair }
+}
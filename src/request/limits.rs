@@ -0,0 +1,23 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use reqwest::header::HeaderMap;
+
+/// 校验限制取值，0 没有意义
+pub(crate) fn check_limit(name: &str, value: Option<usize>) -> PyResult<()> {
+    match value {
+        Some(0) => Err(PyValueError::new_err(format!("{} must be at least 1", name))),
+        _ => Ok(()),
+    }
+}
+
+/// 检查响应头数量和总字节数，超出时返回错误信息
+///
+/// 字节数按 "name: value\r\n" 计算，与线上传输的大小一致。
+pub(crate) fn check_headers(headers: &HeaderMap, max_count: Option<usize>, max_bytes: Option<usize>) -> Option<String> {
+    if let Some(max) = max_count.filter(|max| headers.len() > *max) {
+        return Some(format!("response has {} headers, limit is {}", headers.len(), max));
+    }
+    let max = max_bytes?;
+    let size: usize = headers.iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum();
+    (size > max).then(|| format!("response headers are {} bytes, limit is {}", size, max))
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use futures::future::BoxFuture;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use crate::request::PreparedRequest;
+use crate::request::executor::{dispatch, early_error_result};
+use crate::request::hooks::{call_after, call_before, Before};
+use crate::request::retry::RetryPolicy;
+
+/// 包在请求发送外层的一层处理，如签名、缓存、重试、统计
+///
+/// 调用 next.run 把请求交给下一层，最内层发送请求；不调用则直接返回自己的结果。
+pub(crate) trait Middleware: Send + Sync {
+    fn handle<'a>(&'a self, prepared: PreparedRequest, next: Next<'a>) -> BoxFuture<'a, HashMap<String, String>>;
+}
+
+/// 剩余的中间件
+#[derive(Clone, Copy)]
+pub(crate) struct Next<'a> {
+    rest: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn run(self, prepared: PreparedRequest) -> BoxFuture<'a, HashMap<String, String>> {
+        match self.rest.split_first() {
+            Some((first, rest)) => first.handle(prepared, Next { rest }),
+            None => Box::pin(dispatch(prepared)),
+        }
+    }
+}
+
+type Chain = Arc<Vec<Arc<dyn Middleware>>>;
+
+static GLOBAL_CHAIN: Lazy<RwLock<Chain>> = Lazy::new(|| RwLock::new(Arc::new(Vec::new())));
+
+tokio::task_local! {
+    // fetch_requests(middleware=...) 在本批请求内生效，位于全局中间件内层
+    static BATCH_CHAIN: Chain;
+}
+
+/// Python 对象实现的中间件，方法都是可选的
+///
+/// process_request(prepared) 返回 None 时继续（可修改 prepared），返回 dict 时不再向内传递；
+/// process_response(prepared, result) 返回 dict 时替换结果。
+struct PyMiddleware {
+    process_request: Option<PyObject>,
+    process_response: Option<PyObject>,
+}
+
+impl Middleware for PyMiddleware {
+    fn handle<'a>(&'a self, prepared: PreparedRequest, next: Next<'a>) -> BoxFuture<'a, HashMap<String, String>> {
+        Box::pin(async move {
+            let prepared = match &self.process_request {
+                Some(hook) => {
+                    let req = prepared.request.clone();
+                    match call_before(hook, "process_request", prepared) {
+                        Ok(Before::Send(prepared)) => *prepared,
+                        // 短路的结果不经过本层的 process_response，仍经过外层中间件
+                        Ok(Before::Respond(result)) => return result,
+                        Err(message) => return early_error_result(&req, "MiddlewareError", message),
+                    }
+                }
+                None => prepared,
+            };
+            match &self.process_response {
+                Some(hook) => {
+                    let result = next.run(prepared.clone()).await;
+                    call_after(hook, "process_response", Some(&prepared), result, "MiddlewareError")
+                }
+                None => next.run(prepared).await,
+            }
+        })
+    }
+}
+
+/// 按策略重新执行内层中间件和请求
+struct RetryMiddleware(RetryPolicy);
+
+impl Middleware for RetryMiddleware {
+    fn handle<'a>(&'a self, prepared: PreparedRequest, next: Next<'a>) -> BoxFuture<'a, HashMap<String, String>> {
+        // 请求自带重试设置时已在外层按请求的策略重试
+        if prepared.request.retries.is_some() || prepared.request.retry_policy.is_some() {
+            return next.run(prepared);
+        }
+        Box::pin(self.0.execute_with(move || next.run(prepared.clone())))
+    }
+}
+
+/// 把 Python 列表转换为中间件链，按列表顺序由外到内
+///
+/// 每一项为 RetryPolicy，或带 process_request / process_response 方法的对象。
+pub(crate) fn parse_chain(py: Python, items: Vec<PyObject>) -> PyResult<Vec<Arc<dyn Middleware>>> {
+    items.into_iter().map(|item| -> PyResult<Arc<dyn Middleware>> {
+        let item = item.as_ref(py);
+        if let Ok(policy) = item.extract::<RetryPolicy>() {
+            return Ok(Arc::new(RetryMiddleware(policy)));
+        }
+        let method = |name: &str| -> PyResult<Option<PyObject>> {
+            match item.getattr(name) {
+                Ok(m) if m.is_callable() => Ok(Some(m.into_py(py))),
+                Ok(m) if m.is_none() => Ok(None),
+                Ok(_) => Err(PyTypeError::new_err(format!("middleware {} must be callable", name))),
+                Err(_) => Ok(None),
+            }
+        };
+        let process_request = method("process_request")?;
+        let process_response = method("process_response")?;
+        if process_request.is_none() && process_response.is_none() {
+            return Err(PyTypeError::new_err(format!(
+                "middleware must be a RetryPolicy or define process_request / process_response, got {}",
+                item.get_type().name().unwrap_or("?"),
+            )));
+        }
+        Ok(Arc::new(PyMiddleware { process_request, process_response }))
+    }).collect()
+}
+
+/// 设置所有请求的中间件，按列表顺序由外到内执行，替换之前的设置；None 或空列表清除
+#[pyfunction]
+#[pyo3(signature = (middleware=None))]
+pub fn set_middleware(py: Python, middleware: Option<Vec<PyObject>>) -> PyResult<()> {
+    let chain = parse_chain(py, middleware.unwrap_or_default())?;
+    *GLOBAL_CHAIN.write().unwrap() = Arc::new(chain);
+    Ok(())
+}
+
+/// 在 fut 执行期间追加批量中间件
+pub(crate) async fn scope<F: Future>(chain: Option<Vec<Arc<dyn Middleware>>>, fut: F) -> F::Output {
+    match chain {
+        Some(chain) if !chain.is_empty() => BATCH_CHAIN.scope(Arc::new(chain), fut).await,
+        _ => fut.await,
+    }
+}
+
+/// 依次经过全局和批量中间件后发送请求
+pub(crate) async fn run(prepared: PreparedRequest) -> HashMap<String, String> {
+    let global = GLOBAL_CHAIN.read().unwrap().clone();
+    let batch = BATCH_CHAIN.try_with(|c| c.clone()).ok();
+    match batch {
+        None if global.is_empty() => dispatch(prepared).await,
+        None => Next { rest: &global }.run(prepared).await,
+        Some(batch) => {
+            let chain: Vec<_> = global.iter().chain(batch.iter()).cloned().collect();
+            Next { rest: &chain }.run(prepared).await
+        }
+    }
+}
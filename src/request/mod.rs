@@ -4,9 +4,64 @@ pub mod request_item;
 pub mod executor;
 pub mod concurrency;
 pub mod config;
+pub mod projection;
+pub mod prepared;
+pub mod body;
+pub mod oauth2;
+pub mod serializer;
+pub mod limiter;
+pub mod fair;
+pub mod watchdog;
+pub mod assertions;
+pub mod replay;
+pub mod retry;
+pub mod sse;
+pub mod store;
+pub mod timeouts;
+pub mod isolation;
+pub mod limits;
+pub mod tagging;
+pub mod cache;
+pub mod ramp;
+pub mod transform;
+pub mod default_headers;
+pub mod host_profile;
+pub mod hooks;
+pub mod middleware;
+pub mod curl;
+pub mod jsonrpc;
+pub mod xml;
+pub mod protobuf;
+pub mod raw_body;
+pub mod spill;
+pub mod dedup;
+pub mod validators;
+pub mod hedge;
+pub mod stream;
+pub mod downloader;
+pub mod session;
 
 // 重新导出，方便上层直接使用
 pub use request_item::RequestItem;
 pub use executor::{execute_single_request, fetch_single, fetch_requests};
 pub use concurrency::{execute_with_select_all, execute_with_join_all};
-pub use config::set_global_proxy;
+pub use oauth2::OAuth2ClientCredentials;
+pub use serializer::{register_serializer, unregister_serializer};
+pub use transform::{register_transform, unregister_transform};
+pub use default_headers::set_default_headers;
+pub use host_profile::{set_host_profile, remove_host_profile};
+pub use hooks::set_hooks;
+pub use middleware::set_middleware;
+pub use jsonrpc::{fetch_jsonrpc, JsonRpcRequest};
+pub use watchdog::set_watchdog;
+pub use retry::{retry_failed, RetryPolicy};
+pub use ramp::RampUp;
+pub use sse::fetch_sse;
+pub use stream::{fetch_stream, ResponseStream};
+pub use downloader::{download, ChecksumMismatch};
+pub use isolation::configure_isolated_pool;
+pub use cache::{configure_response_cache, clear_response_cache};
+pub use validators::{export_validators, import_validators, clear_validators};
+pub use prepared::{PreparedRequest, prepare, send};
+pub use session::Session;
+pub use config::{set_global_proxy, configure_global_client, GlobalClientConfig};
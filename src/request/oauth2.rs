@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use reqwest::Client;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use crate::utils::sha256_hex;
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Option<Instant>,
+}
+
+/// OAuth2 client credentials 授权，自动获取并在过期前刷新 access token
+///
+/// 同一个对象的所有副本共享 token 缓存，批量请求只会获取一次。
+#[pyclass]
+#[derive(Clone)]
+pub struct OAuth2ClientCredentials {
+    #[pyo3(get)]
+    pub token_url: String,
+    #[pyo3(get)]
+    pub client_id: String,
+    client_secret: String,
+    #[pyo3(get)]
+    pub scopes: Option<Vec<String>>,
+    #[pyo3(get)]
+    pub auth_method: String,
+    #[pyo3(get)]
+    pub refresh_margin: f64,
+    cache: Arc<Mutex<Option<CachedToken>>>,
+}
+
+#[pymethods]
+impl OAuth2ClientCredentials {
+    #[new]
+    #[pyo3(signature = (token_url, client_id, client_secret, scopes=None, auth_method=None, refresh_margin=None))]
+    fn new(
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        scopes: Option<Vec<String>>,
+        auth_method: Option<String>,
+        refresh_margin: Option<f64>,
+    ) -> PyResult<Self> {
+        let auth_method = auth_method.unwrap_or_else(|| "basic".to_string()).to_lowercase();
+        if auth_method != "basic" && auth_method != "post" {
+            return Err(PyValueError::new_err(format!(
+                "Invalid auth_method: '{}'. Valid values: basic, post", auth_method
+            )));
+        }
+        Ok(Self {
+            token_url,
+            client_id,
+            client_secret,
+            scopes,
+            auth_method,
+            refresh_margin: refresh_margin.unwrap_or(30.0),
+            cache: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// 丢弃缓存的 token，下次请求时重新获取
+    fn invalidate<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let cache = self.cache.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            *cache.lock().await = None;
+            Ok(())
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("OAuth2ClientCredentials(token_url='{}', client_id='{}')", self.token_url, self.client_id)
+    }
+}
+
+impl OAuth2ClientCredentials {
+    /// 返回有效的 access token，缓存缺失或即将过期时重新获取
+    pub(crate) async fn access_token(&self, client: &Client) -> Result<String, String> {
+        let mut cache = self.cache.lock().await;
+        let margin = Duration::from_secs_f64(self.refresh_margin.max(0.0));
+
+        if let Some(token) = cache.as_ref() {
+            let fresh = token.expires_at.map_or(true, |at| Instant::now() + margin < at);
+            if fresh {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        let token = self.fetch_token(client).await?;
+        let access_token = token.access_token.clone();
+        *cache = Some(token);
+        Ok(access_token)
+    }
+
+    pub(crate) async fn invalidate_token(&self) {
+        *self.cache.lock().await = None;
+    }
+
+    /// 区分客户端的标识，密钥只保留摘要
+    pub(crate) fn fingerprint(&self) -> String {
+        format!("{} {} {} {:?} {}", self.token_url, self.client_id, sha256_hex(self.client_secret.as_bytes()), self.scopes, self.auth_method)
+    }
+
+    async fn fetch_token(&self, client: &Client) -> Result<CachedToken, String> {
+        let mut form = vec![("grant_type", "client_credentials".to_string())];
+        if let Some(scopes) = &self.scopes {
+            form.push(("scope", scopes.join(" ")));
+        }
+
+        let mut builder = client.post(&self.token_url);
+        if self.auth_method == "post" {
+            form.push(("client_id", self.client_id.clone()));
+            form.push(("client_secret", self.client_secret.clone()));
+        } else {
+            builder = builder.basic_auth(&self.client_id, Some(&self.client_secret));
+        }
+
+        let requested_at = Instant::now();
+        let res = builder.form(&form).send().await
+            .map_err(|e| format!("Token request to {} failed: {}", self.token_url, e))?;
+        let status = res.status();
+        let body: Value = res.json().await
+            .map_err(|e| format!("Invalid token response from {}: {}", self.token_url, e))?;
+
+        if !status.is_success() {
+            let error = body.get("error").and_then(|v| v.as_str()).unwrap_or("unknown_error");
+            return Err(format!("Token endpoint returned {}: {}", status.as_u16(), error));
+        }
+
+        let access_token = body.get("access_token").and_then(|v| v.as_str())
+            .ok_or_else(|| "Token response missing access_token".to_string())?
+            .to_string();
+        let expires_at = body.get("expires_in")
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+            .map(|secs| requested_at + Duration::from_secs_f64(secs.max(0.0)));
+
+        Ok(CachedToken { access_token, expires_at })
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyBytes, PyDict};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Method, RequestBuilder};
+use url::Url;
+use crate::request::RequestItem;
+use crate::request::serializer::serialize_params;
+use crate::request::default_headers;
+use crate::request::host_profile;
+use crate::request::xml;
+use crate::request::protobuf;
+use crate::request::executor::{execute_prepared, single_result_to_py};
+use crate::request::raw_body;
+use crate::utils::{normalize_url, py_to_json, BodyCompression};
+use crate::GLOBAL_CLIENT_CONFIG;
+
+/// 已解析完成的请求：方法、URL、请求头和序列化后的请求体
+#[pyclass]
+#[derive(Clone)]
+pub struct PreparedRequest {
+    #[pyo3(get, set)]
+    pub method: String,
+    #[pyo3(get, set)]
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    /// 原始请求，发送时使用其中的超时、代理等选项
+    pub request: RequestItem,
+}
+
+#[pymethods]
+impl PreparedRequest {
+    #[getter]
+    fn headers<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        for (k, v) in &self.headers { dict.set_item(k, v)?; }
+        Ok(dict)
+    }
+
+    #[setter]
+    fn set_headers(&mut self, headers: HashMap<String, String>) -> PyResult<()> {
+        for (k, v) in &headers {
+            HeaderName::from_bytes(k.as_bytes()).map_err(|e| PyValueError::new_err(format!("Invalid header name '{}': {}", k, e)))?;
+            HeaderValue::from_str(v).map_err(|e| PyValueError::new_err(format!("Invalid header value for '{}': {}", k, e)))?;
+        }
+        self.headers = headers.into_iter().collect();
+        Ok(())
+    }
+
+    #[getter]
+    fn body<'py>(&self, py: Python<'py>) -> Option<&'py PyBytes> {
+        self.body.as_ref().map(|b| PyBytes::new(py, b))
+    }
+
+    #[setter]
+    fn set_body(&mut self, body: Option<&PyBytes>) {
+        self.body = body.map(|b| b.as_bytes().to_vec());
+    }
+
+    #[getter]
+    fn request(&self) -> RequestItem {
+        self.request.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PreparedRequest({} {})", self.method, self.url)
+    }
+}
+
+impl PreparedRequest {
+    /// 解析 RequestItem：GET/DELETE 的 params 转为查询参数，其余方法按 Content-Type 序列化为请求体
+    ///
+    /// 注册了对应 Content-Type 的序列化函数时使用它，否则序列化为 JSON；设置了 protobuf 或 xml 时原样作为请求体。
+    pub(crate) fn from_item(py: Python, req: &RequestItem, url: String) -> PyResult<Self> {
+        let method = req.method.clone().unwrap_or_else(|| "GET".to_string()).to_uppercase();
+        let method = method.parse::<Method>().unwrap_or(Method::GET);
+        // 主机配置补全请求中未设置的超时，发送时使用补全后的请求
+        let profile = host_profile::lookup(&url);
+        let mut request = req.clone();
+        if let Some(profile) = &profile {
+            profile.fill(&mut request);
+        }
+        let mut url = url;
+        let mut headers = Vec::new();
+        let mut body = None;
+
+        if let Some(py_headers) = &req.headers {
+            if let Ok(dict) = py_headers.as_ref(py).downcast::<PyDict>() {
+                for (k, v) in dict.iter() {
+                    if let (Ok(k_str), Ok(v_str)) = (k.extract::<String>(), v.extract::<String>()) {
+                        if HeaderName::from_bytes(k_str.as_bytes()).is_ok() && HeaderValue::from_str(&v_str).is_ok() {
+                            headers.push((k_str, v_str));
+                        }
+                    }
+                }
+            }
+        }
+        // 请求中没有的默认请求头，在 Content-Type 默认值之前补全；主机配置优先于默认请求头
+        let has_auth = req.auth.is_some() || req.bearer_token.is_some() || req.oauth2.is_some();
+        if let Some(profile) = &profile {
+            profile.merge_headers(&mut headers, has_auth);
+        }
+        default_headers::merge(&mut headers, has_auth);
+
+        if let Some(message) = &req.protobuf {
+            let message = message.as_ref(py).as_bytes();
+            let grpc_web = req.grpc_web == Some(true);
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type")) {
+                let content_type = if grpc_web { protobuf::GRPC_WEB_CONTENT_TYPE } else { protobuf::PROTOBUF_CONTENT_TYPE };
+                headers.push(("content-type".to_string(), content_type.to_string()));
+            }
+            if grpc_web && !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("x-grpc-web")) {
+                headers.push(("x-grpc-web".to_string(), "1".to_string()));
+            }
+            body = Some(if grpc_web { protobuf::frame(message) } else { message.to_vec() });
+        } else if let Some(xml) = &req.xml {
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type")) {
+                headers.push(("content-type".to_string(), xml::default_content_type(xml).to_string()));
+            }
+            body = Some(xml.as_bytes().to_vec());
+        } else if let Some(params_dict) = &req.params {
+            let params = params_dict.as_ref(py);
+            match method {
+                Method::GET | Method::DELETE => {
+                    if let (Ok(json), Ok(mut parsed)) = (py_to_json(py, params), Url::parse(&url)) {
+                        if let Some(obj) = json.as_object() {
+                            parsed.query_pairs_mut().extend_pairs(
+                                obj.iter().map(|(k, v)| (k.clone(), v.to_string().trim_matches('"').to_string())),
+                            );
+                            url = parsed.to_string();
+                        }
+                    }
+                }
+                _ => {
+                    let content_type = headers.iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                        .map(|(_, v)| v.clone());
+                    if content_type.is_none() {
+                        headers.push(("content-type".to_string(), "application/json".to_string()));
+                    }
+                    let content_type = content_type.unwrap_or_else(|| "application/json".to_string());
+                    body = match serialize_params(py, &content_type, params) {
+                        Some(serialized) => Some(serialized?),
+                        None => py_to_json(py, params).ok().and_then(|json| serde_json::to_vec(&json).ok()),
+                    };
+                }
+            }
+        }
+
+        // 压缩请求体，已有 Content-Encoding 时视为调用方自行压缩
+        if let (Some(codec), Some(data)) = (req.compress_body.as_deref().and_then(|c| BodyCompression::parse(c).ok()), &body) {
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-encoding")) {
+                body = Some(codec.compress(data));
+                headers.push(("content-encoding".to_string(), codec.as_str().to_string()));
+            }
+        }
+
+        Ok(PreparedRequest { method: method.to_string(), url, headers, body, request })
+    }
+
+    /// 写入请求头和请求体；overrides 中的请求头替换同名的请求头
+    pub(crate) fn apply(&self, mut builder: RequestBuilder, overrides: &[(&str, &str)]) -> RequestBuilder {
+        for (k, v) in overrides {
+            builder = builder.header(*k, *v);
+        }
+        for (k, v) in &self.headers {
+            if overrides.iter().any(|(name, _)| name.eq_ignore_ascii_case(k)) {
+                continue;
+            }
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(k.as_bytes()), HeaderValue::from_str(v)) {
+                builder = builder.header(name, value);
+            }
+        }
+        if let Some(body) = &self.body {
+            builder = builder.body(body.clone());
+        }
+        if let Some((username, password)) = &self.request.auth {
+            builder = builder.basic_auth(username, Some(password));
+        }
+        builder
+    }
+}
+
+/// 解析请求但不发送
+#[pyfunction]
+pub fn prepare(py: Python, request: RequestItem) -> PyResult<PreparedRequest> {
+    let default_scheme = GLOBAL_CLIENT_CONFIG.read().unwrap().default_scheme.clone();
+    let url = normalize_url(&request.url, default_scheme.as_deref().unwrap_or("http"))
+        .map_err(PyValueError::new_err)?;
+    PreparedRequest::from_item(py, &request, url)
+}
+
+/// 发送 prepare 得到的请求，返回结构与 fetch_single 相同
+#[pyfunction]
+pub fn send<'py>(py: Python<'py>, prepared: PreparedRequest) -> PyResult<&'py PyAny> {
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let owner = raw_body::Owner::default();
+        let result = raw_body::scope(Some(owner.clone()), execute_prepared(prepared)).await;
+        Python::with_gil(|py| single_result_to_py(py, &result))
+    })
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+use crate::utils::json_to_py;
+
+/// 结果中以 JSON 字符串保存的顶层字段
+const JSON_FIELDS: [&str; 3] = ["response", "meta", "exception"];
+
+/// 按 fields 投影单个结果，只把需要的字段转换为 Python 对象
+///
+/// - `http_status` 等顶层字段与不投影时相同：`response` 为 JSON 字符串，`meta`、`exception` 为 dict
+/// - `meta.process_time` 这类路径会解析对应 JSON 并保留嵌套结构
+/// - 其余名称（如 `tag`）在 `meta` 中查找
+pub fn project_result<'py>(
+    py: Python<'py>,
+    res: &HashMap<String, String>,
+    fields: &[String],
+) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    let mut parsed: HashMap<&str, Value> = HashMap::new();
+
+    'fields: for field in fields {
+        let (root, path) = match field.split_once('.') {
+            Some((root, path)) => (root, Some(path)),
+            None if field == "http_status" || JSON_FIELDS.contains(&field.as_str()) => (field.as_str(), None),
+            None => ("meta", Some(field.as_str())),
+        };
+
+        if root == "http_status" {
+            let status = res.get("http_status").map(|s| s.as_str()).unwrap_or("0");
+            match status.parse::<u16>() {
+                Ok(code) => dict.set_item("http_status", code)?,
+                Err(_) => dict.set_item("http_status", status)?,
+            }
+            continue;
+        }
+        if !JSON_FIELDS.contains(&root) {
+            continue;
+        }
+        // 与 batch_result_to_py 一致，完整的 response 不解析，同时请求的 response.* 路径不再生效
+        if field == "response" {
+            dict.set_item("response", res.get("response").map(|s| s.as_str()).unwrap_or(""))?;
+            continue;
+        }
+
+        let root_value = parsed.entry(root).or_insert_with(|| {
+            res.get(root)
+                .and_then(|s| serde_json::from_str(s).ok())
+                .unwrap_or_else(|| Value::Object(serde_json::Map::new()))
+        });
+
+        let Some(path) = path else {
+            dict.set_item(root, json_to_py(py, root_value)?)?;
+            continue;
+        };
+
+        let mut value = &*root_value;
+        for key in path.split('.') {
+            value = value.get(key).unwrap_or(&Value::Null);
+        }
+
+        // 点路径保留嵌套结构，裸字段直接放在顶层
+        if field.contains('.') {
+            let mut target = match dict.get_item(root)? {
+                Some(existing) => match existing.downcast::<PyDict>() {
+                    Ok(existing) => existing,
+                    Err(_) => continue,
+                },
+                None => {
+                    let d = PyDict::new(py);
+                    dict.set_item(root, d)?;
+                    d
+                }
+            };
+            let keys: Vec<&str> = path.split('.').collect();
+            for key in &keys[..keys.len() - 1] {
+                target = match target.get_item(key)? {
+                    // 上级路径已是非对象的值（如同时请求 meta.a 和 meta.a.b）时忽略该路径
+                    Some(existing) => match existing.downcast::<PyDict>() {
+                        Ok(existing) => existing,
+                        Err(_) => continue 'fields,
+                    },
+                    None => {
+                        let d = PyDict::new(py);
+                        target.set_item(key, d)?;
+                        d
+                    }
+                };
+            }
+            target.set_item(keys[keys.len() - 1], json_to_py(py, value)?)?;
+        } else {
+            dict.set_item(field, json_to_py(py, value)?)?;
+        }
+    }
+
+    Ok(dict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_paths_below_scalar_values() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let res = HashMap::from([
+                ("http_status".to_string(), "200".to_string()),
+                ("meta".to_string(), r#"{"request_time":"t","retry":{"attempts":2}}"#.to_string()),
+            ]);
+            let fields = ["meta.request_time", "meta.request_time.x", "meta.retry.attempts", "http_status"]
+                .map(String::from);
+            let dict = project_result(py, &res, &fields).unwrap();
+            let meta = dict.get_item("meta").unwrap().unwrap().downcast::<PyDict>().unwrap();
+            assert_eq!(meta.get_item("request_time").unwrap().unwrap().extract::<String>().unwrap(), "t");
+            let retry = meta.get_item("retry").unwrap().unwrap().downcast::<PyDict>().unwrap();
+            assert_eq!(retry.get_item("attempts").unwrap().unwrap().extract::<i64>().unwrap(), 2);
+            assert_eq!(dict.get_item("http_status").unwrap().unwrap().extract::<u16>().unwrap(), 200);
+        });
+    }
+}
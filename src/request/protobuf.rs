@@ -0,0 +1,94 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+
+pub(crate) const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+pub(crate) const GRPC_WEB_CONTENT_TYPE: &str = "application/grpc-web+proto";
+
+// gRPC-Web 帧头：1 字节标志 + 4 字节大端长度；标志最高位表示 trailer 帧
+const FRAME_HEADER: usize = 5;
+const TRAILER_FLAG: u8 = 0x80;
+
+/// 校验请求体选项：protobuf、xml、params 只能设置一个，grpc_web 需要 protobuf
+pub(crate) fn check_protobuf(has_protobuf: bool, has_params: bool, has_xml: bool, grpc_web: Option<bool>) -> PyResult<()> {
+    if has_protobuf && (has_params || has_xml) {
+        return Err(PyValueError::new_err("protobuf cannot be combined with params or xml"));
+    }
+    if grpc_web == Some(true) && !has_protobuf {
+        return Err(PyValueError::new_err("grpc_web requires a protobuf body"));
+    }
+    Ok(())
+}
+
+/// 二进制的 protobuf / gRPC 响应，不按文本解码
+pub(crate) fn is_binary(content_type: Option<&str>) -> bool {
+    let Some(content_type) = content_type else { return false };
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    matches!(mime.as_str(), "application/x-protobuf" | "application/protobuf" | "application/vnd.google.protobuf")
+        || mime.starts_with("application/grpc")
+}
+
+/// 把消息封装为一个 gRPC-Web 数据帧
+pub(crate) fn frame(message: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(FRAME_HEADER + message.len());
+    framed.push(0);
+    framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    framed.extend_from_slice(message);
+    framed
+}
+
+/// 解开的 gRPC-Web 响应
+pub(crate) struct Unframed {
+    pub messages: Vec<Vec<u8>>,
+    pub trailers: Map<String, Value>,
+}
+
+impl Unframed {
+    /// grpc-status 和 grpc-message，trailer 帧中没有时使用响应头（trailers-only 响应）
+    pub(crate) fn status(&self, headers: &Map<String, Value>) -> (Option<i64>, Option<String>) {
+        let field = |name: &str| self.trailers.get(name).or_else(|| headers.get(name)).and_then(Value::as_str);
+        (
+            field("grpc-status").and_then(|s| s.trim().parse().ok()),
+            field("grpc-message").map(str::to_string),
+        )
+    }
+
+    /// 写入 meta.grpc 的摘要；流式响应有多个消息时，stream 中为全部消息的 base64
+    pub(crate) fn summary(&self, headers: &Map<String, Value>) -> Value {
+        let (status, message) = self.status(headers);
+        let mut summary = json!({"status": status, "message": message, "messages": self.messages.len(), "trailers": self.trailers});
+        if self.messages.len() > 1 {
+            summary["stream"] = self.messages.iter().map(|m| Value::String(STANDARD.encode(m))).collect();
+        }
+        summary
+    }
+}
+
+/// 拆分 gRPC-Web 响应体中的数据帧和 trailer 帧
+pub(crate) fn unframe(body: &[u8]) -> Result<Unframed, String> {
+    let mut unframed = Unframed { messages: Vec::new(), trailers: Map::new() };
+    let mut rest = body;
+    while !rest.is_empty() {
+        if rest.len() < FRAME_HEADER {
+            return Err(format!("Truncated gRPC-Web frame header ({} bytes)", rest.len()));
+        }
+        let flag = rest[0];
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let payload = rest.get(FRAME_HEADER..FRAME_HEADER + len)
+            .ok_or_else(|| format!("Truncated gRPC-Web frame: expected {} bytes, got {}", len, rest.len() - FRAME_HEADER))?;
+        if flag & TRAILER_FLAG != 0 {
+            // trailer 帧为 HTTP/1 风格的头部行
+            for line in String::from_utf8_lossy(payload).split("\r\n") {
+                if let Some((name, value)) = line.split_once(':') {
+                    unframed.trailers.insert(name.trim().to_ascii_lowercase(), Value::String(value.trim().to_string()));
+                }
+            }
+        } else {
+            unframed.messages.push(payload.to_vec());
+        }
+        rest = &rest[FRAME_HEADER + len..];
+    }
+    Ok(unframed)
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use std::time::Duration;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use tokio::sync::Semaphore;
+use crate::testing::clock;
+
+/// 批量请求的并发爬坡：从 start 个并发开始，每隔 interval 秒增加 step 个，直到 max
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct RampUp {
+    #[pyo3(get, set)]
+    pub start: usize,
+    #[pyo3(get, set)]
+    pub step: usize,
+    #[pyo3(get, set)]
+    pub interval: f64,
+    #[pyo3(get, set)]
+    pub max: Option<usize>,
+}
+
+#[pymethods]
+impl RampUp {
+    #[new]
+    #[pyo3(signature = (start=10, step=10, interval=1.0, max=None))]
+    fn new(start: usize, step: usize, interval: f64, max: Option<usize>) -> PyResult<Self> {
+        if start == 0 || step == 0 {
+            return Err(PyValueError::new_err("start and step must be at least 1"));
+        }
+        if !interval.is_finite() || interval <= 0.0 {
+            return Err(PyValueError::new_err(format!("interval must be a positive number of seconds, got {}", interval)));
+        }
+        if max.is_some_and(|m| m < start) {
+            return Err(PyValueError::new_err("max must not be smaller than start"));
+        }
+        Ok(RampUp { start, step, interval, max })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RampUp(start={}, step={}, interval={}, max={:?})", self.start, self.step, self.interval, self.max)
+    }
+}
+
+/// 按爬坡计划放开并发的信号量
+pub(crate) struct Ramp {
+    permits: Arc<Semaphore>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Ramp {
+    /// total 为批量请求数，并发达到 total（或 max）后停止增加
+    pub(crate) fn start(plan: &RampUp, total: usize) -> Self {
+        let limit = plan.max.unwrap_or(total).min(total).max(plan.start);
+        let permits = Arc::new(Semaphore::new(plan.start));
+        let (step, interval) = (plan.step, Duration::from_secs_f64(plan.interval));
+        let mut current = plan.start;
+        let semaphore = permits.clone();
+        let task = tokio::spawn(async move {
+            while current < limit {
+                clock::sleep(interval).await;
+                let added = step.min(limit - current);
+                semaphore.add_permits(added);
+                current += added;
+            }
+        });
+        Ramp { permits, task }
+    }
+
+    pub(crate) fn permits(&self) -> Arc<Semaphore> {
+        self.permits.clone()
+    }
+}
+
+impl Drop for Ramp {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
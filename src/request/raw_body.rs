@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+/// 结果中指向暂存响应体的键
+pub(crate) const BODY_KEY: &str = "body_id";
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+// 等待转换为 bytes 的响应体，不经过 String 和 JSON
+static BODIES: Lazy<Mutex<HashMap<u64, Vec<u8>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+struct Ids(Mutex<Vec<u64>>);
+
+impl Drop for Ids {
+    // 释放没有被取走的响应体，如重试时丢弃的结果
+    fn drop(&mut self) {
+        let ids = std::mem::take(self.0.get_mut().unwrap());
+        let mut bodies = BODIES.lock().unwrap();
+        for id in ids {
+            bodies.remove(&id);
+        }
+    }
+}
+
+/// 一次 fetch 调用暂存的响应体，最后一个引用释放时清理
+#[derive(Clone, Default)]
+pub(crate) struct Owner(Arc<Ids>);
+
+tokio::task_local! {
+    static OWNER: Owner;
+}
+
+/// 校验 raw_body 不与需要文本内容的选项同时使用
+pub(crate) fn check_raw_body(raw_body: Option<bool>, parse_xml: Option<bool>, has_transform: bool, has_compress_result: bool) -> PyResult<()> {
+    if raw_body == Some(true) && (parse_xml == Some(true) || has_transform || has_compress_result) {
+        return Err(PyValueError::new_err("raw_body cannot be combined with parse_xml, transform or compress_result"));
+    }
+    Ok(())
+}
+
+/// 在 fut 执行期间暂存的响应体归 owner 所有；调用方在转换结果后再释放 owner
+pub(crate) async fn scope<F: Future>(owner: Option<Owner>, fut: F) -> F::Output {
+    match owner {
+        Some(owner) => OWNER.scope(owner, fut).await,
+        None => fut.await,
+    }
+}
+
+/// 当前任务的 owner，用于传到隔离线程池
+pub(crate) fn current() -> Option<Owner> {
+    OWNER.try_with(|owner| owner.clone()).ok()
+}
+
+/// 暂存响应体并返回 id；不在 scope 中时原样返回数据
+pub(crate) fn stash(bytes: Vec<u8>) -> Result<String, Vec<u8>> {
+    let Some(owner) = current() else { return Err(bytes) };
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    BODIES.lock().unwrap().insert(id, bytes);
+    owner.0.0.lock().unwrap().push(id);
+    Ok(id.to_string())
+}
+
+/// 把结果引用的响应体直接复制进 bytes 对象，放在 dict["body"]
+///
+/// 合并的重复请求共享同一个 id，响应体留到 owner 释放时再清理。
+pub(crate) fn attach(py: Python, res: &HashMap<String, String>, dict: &PyDict) -> PyResult<()> {
+    let Some(id) = res.get(BODY_KEY).and_then(|id| id.parse::<u64>().ok()) else { return Ok(()) };
+    let bodies = BODIES.lock().unwrap();
+    let Some(bytes) = bodies.get(&id) else { return Ok(()) };
+    dict.set_item("body", PyBytes::new(py, bytes))
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde_json::{json, Value};
+use crate::request::executor::{execute_prepared, single_result_to_py};
+use crate::request::raw_body;
+use crate::request::{PreparedRequest, RequestItem};
+use crate::request::timeouts::check_positive;
+use crate::utils::{py_to_json, sha256_hex};
+
+// 最多保留的请求体数量，超出后丢弃最早的
+const BODY_STORE_CAPACITY: usize = 1024;
+
+#[derive(Default)]
+struct BodyStore {
+    bodies: HashMap<String, Vec<u8>>,
+    order: VecDeque<String>,
+}
+
+static BODY_STORE: Lazy<Mutex<BodyStore>> = Lazy::new(|| Mutex::new(BodyStore::default()));
+
+/// 记录请求的 method、url、headers 和请求体哈希，请求体保存在进程内供 replay 使用
+pub(crate) fn record(prepared: &PreparedRequest) -> Value {
+    let body_hash = prepared.body.as_ref().map(|body| {
+        let hash = sha256_hex(body);
+        let mut store = BODY_STORE.lock().unwrap();
+        if !store.bodies.contains_key(&hash) {
+            if store.order.len() >= BODY_STORE_CAPACITY {
+                if let Some(oldest) = store.order.pop_front() {
+                    store.bodies.remove(&oldest);
+                }
+            }
+            store.order.push_back(hash.clone());
+            store.bodies.insert(hash.clone(), body.clone());
+        }
+        hash
+    });
+    let headers: serde_json::Map<String, Value> = prepared.headers.iter()
+        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+        .collect();
+    let mut record = json!({
+        "method": prepared.method,
+        "url": prepared.url,
+        "headers": headers,
+        "body_sha256": body_hash,
+    });
+    // 传输方式也是请求的一部分，重放时需要走同一个 socket
+    if let Some(path) = &prepared.request.unix_socket {
+        record["unix_socket"] = Value::String(path.clone());
+    }
+    record
+}
+
+pub(crate) fn meta_of(py: Python, result: &PyDict) -> PyResult<Value> {
+    let meta = match result.get_item("meta")? {
+        Some(meta) => py_to_json(py, meta)?,
+        None => Value::Null,
+    };
+    Ok(match meta {
+        Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::Null),
+        other => other,
+    })
+}
+
+/// 从 meta.request 重建请求；load_body 为 false 时不读取已保存的请求体
+pub(crate) fn rebuild(meta: &Value, load_body: bool) -> PyResult<PreparedRequest> {
+    let recorded = meta.get("request")
+        .ok_or_else(|| PyValueError::new_err("result has no recorded request (meta.request)"))?;
+
+    let method = recorded["method"].as_str().unwrap_or("GET").to_string();
+    let url = recorded["url"].as_str().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = recorded["headers"].as_object()
+        .map(|h| h.iter().map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string())).collect())
+        .unwrap_or_default();
+    let mut body = None;
+    if let (Some(hash), true) = (recorded["body_sha256"].as_str(), load_body) {
+        body = Some(BODY_STORE.lock().unwrap().bodies.get(hash).cloned().ok_or_else(|| {
+            PyValueError::new_err("recorded request body is no longer available; pass it in override['body']")
+        })?);
+    }
+    let request = RequestItem {
+        url: url.clone(),
+        method: Some(method.clone()),
+        tag: meta.get("tag").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        unix_socket: recorded.get("unix_socket").and_then(|p| p.as_str()).map(|p| p.to_string()),
+        ..Default::default()
+    };
+    Ok(PreparedRequest { method, url, headers, body, request })
+}
+
+/// 根据结果中记录的请求重新发送，override 可以替换 method、url、headers、body、timeout、tag
+///
+/// headers 与原请求头合并；认证信息（auth、bearer_token 等）不会被记录，重放时使用全局配置。
+#[pyfunction]
+#[pyo3(signature = (result, r#override=None))]
+pub fn replay<'py>(py: Python<'py>, result: &PyDict, r#override: Option<&PyDict>) -> PyResult<&'py PyAny> {
+    let meta = meta_of(py, result)?;
+    let body_overridden = r#override.map_or(false, |o| o.contains("body").unwrap_or(false));
+    let PreparedRequest { mut method, mut url, mut headers, mut body, request: mut item } =
+        rebuild(&meta, !body_overridden)?;
+
+    if let Some(overrides) = r#override {
+        for (key, value) in overrides.iter() {
+            match key.extract::<String>()?.as_str() {
+                "method" => method = value.extract::<String>()?.to_uppercase(),
+                "url" => url = value.extract()?,
+                "headers" => {
+                    for (name, v) in value.extract::<HashMap<String, String>>()? {
+                        headers.retain(|(k, _)| !k.eq_ignore_ascii_case(&name));
+                        headers.push((name, v));
+                    }
+                }
+                "body" => {
+                    body = if value.is_none() {
+                        None
+                    } else if let Ok(bytes) = value.downcast::<PyBytes>() {
+                        Some(bytes.as_bytes().to_vec())
+                    } else {
+                        Some(value.extract::<String>()?.into_bytes())
+                    };
+                }
+                "timeout" => {
+                    item.timeout = value.extract()?;
+                    check_positive("timeout", item.timeout)?;
+                }
+                "tag" => item.tag = value.extract()?,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "Invalid override key: '{}'. Valid keys: method, url, headers, body, timeout, tag", other
+                    )));
+                }
+            }
+        }
+    }
+
+    item.url = url.clone();
+    item.method = Some(method.clone());
+    let prepared = PreparedRequest { method, url, headers, body, request: item };
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let owner = raw_body::Owner::default();
+        let result = raw_body::scope(Some(owner.clone()), execute_prepared(prepared)).await;
+        Python::with_gil(|py| single_result_to_py(py, &result))
+    })
+}
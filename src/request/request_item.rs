@@ -1,9 +1,30 @@
+use std::collections::HashMap;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
-use crate::network::{HttpVersion, ProxyConfig, SslVerify};
+use pyo3::types::{PyBytes, PyDict};
+use crate::network::{CaBundle, ClientIdentity, HttpVersion, ProxyConfig, SslVerifyArg, TlsVersion};
+use crate::network::tls_version::check_tls_range;
+use crate::network::relay::check_unix_socket;
+use crate::network::local_address::check_local_address;
+use crate::network::dns::{parse_resolve, IpVersion};
+use crate::request::timeouts::{check_positive, check_request};
+use crate::request::GlobalClientConfig;
+use crate::request::limits::check_limit;
+use crate::request::cache::CacheMode;
+use crate::request::validators::Conditional;
+use crate::request::retry::RetryPolicy;
+use crate::request::transform::check_transform;
+use crate::request::xml::check_xml;
+use crate::request::protobuf::check_protobuf;
+use crate::request::config::check_encoding;
+use crate::request::raw_body::check_raw_body;
+use crate::request::curl;
+use crate::request::prepared::prepare;
+use crate::network::impersonate::Impersonate;
+use crate::request::oauth2::OAuth2ClientCredentials;
+use crate::utils::{check_encoding_label, compile_patterns, BodyCompression, DecodePolicy, NormalizeStep, ResultCompression};
 
 #[pyclass]
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RequestItem {
     #[pyo3(get, set)]
     pub url: String,
@@ -23,6 +44,98 @@ pub struct RequestItem {
     pub http_version: Option<HttpVersion>,
     #[pyo3(get, set)]
     pub ssl_verify: Option<bool>,
+    #[pyo3(get, set)]
+    pub connect_timeout: Option<f64>,
+    #[pyo3(get, set)]
+    pub read_timeout: Option<f64>,
+    #[pyo3(get, set)]
+    pub allow_redirects: Option<bool>,
+    #[pyo3(get, set)]
+    pub max_redirects: Option<usize>,
+    #[pyo3(get, set)]
+    pub normalize: Option<Vec<String>>,
+    #[pyo3(get, set)]
+    pub auth: Option<(String, String)>,
+    #[pyo3(get, set)]
+    pub bearer_token: Option<String>,
+    #[pyo3(get, set)]
+    pub decode_errors: Option<String>,
+    #[pyo3(get, set)]
+    pub dns_timeout: Option<f64>,
+    #[pyo3(get, set)]
+    pub oauth2: Option<OAuth2ClientCredentials>,
+    #[pyo3(get, set)]
+    pub identity: Option<ClientIdentity>,
+    #[pyo3(get, set)]
+    pub ca_bundle: Option<CaBundle>,
+    #[pyo3(get, set)]
+    pub compress_result: Option<String>,
+    #[pyo3(get, set)]
+    pub tls_min_version: Option<TlsVersion>,
+    #[pyo3(get, set)]
+    pub tls_max_version: Option<TlsVersion>,
+    #[pyo3(get, set)]
+    pub impersonate: Option<String>,
+    #[pyo3(get, set)]
+    pub fingerprint: Option<Vec<String>>,
+    #[pyo3(get, set)]
+    pub assert_json: Option<Py<PyDict>>,
+    #[pyo3(get, set)]
+    pub assert_contains: Option<String>,
+    #[pyo3(get, set)]
+    pub assert_header: Option<HashMap<String, String>>,
+    #[pyo3(get, set)]
+    pub unix_socket: Option<String>,
+    #[pyo3(get, set)]
+    pub body_store: Option<String>,
+    #[pyo3(get, set)]
+    pub resolve: Option<HashMap<String, String>>,
+    #[pyo3(get, set)]
+    pub ip_version: Option<String>,
+    #[pyo3(get, set)]
+    pub local_address: Option<String>,
+    #[pyo3(get, set)]
+    pub isolated: Option<bool>,
+    #[pyo3(get, set)]
+    pub max_response_headers: Option<usize>,
+    #[pyo3(get, set)]
+    pub max_header_bytes: Option<usize>,
+    #[pyo3(get, set)]
+    pub keepalive_interval: Option<f64>,
+    #[pyo3(get, set)]
+    pub cache: Option<String>,
+    #[pyo3(get, set)]
+    pub transform: Option<String>,
+    #[pyo3(get, set)]
+    pub xml: Option<String>,
+    #[pyo3(get, set)]
+    pub parse_xml: Option<bool>,
+    #[pyo3(get, set)]
+    pub protobuf: Option<Py<PyBytes>>,
+    #[pyo3(get, set)]
+    pub grpc_web: Option<bool>,
+    #[pyo3(get, set)]
+    pub max_response_bytes: Option<usize>,
+    #[pyo3(get, set)]
+    pub accept_encoding: Option<Vec<String>>,
+    #[pyo3(get, set)]
+    pub decompress: Option<bool>,
+    #[pyo3(get, set)]
+    pub compress_body: Option<String>,
+    #[pyo3(get, set)]
+    pub encoding: Option<String>,
+    #[pyo3(get, set)]
+    pub raw_body: Option<bool>,
+    #[pyo3(get, set)]
+    pub memory_threshold: Option<usize>,
+    #[pyo3(get, set)]
+    pub conditional: Option<String>,
+    #[pyo3(get, set)]
+    pub retries: Option<u32>,
+    #[pyo3(get, set)]
+    pub retry_policy: Option<RetryPolicy>,
+    #[pyo3(get, set)]
+    pub hedge_after: Option<f64>,
 }
 
 #[pymethods]
@@ -37,8 +150,163 @@ impl RequestItem {
         headers: Option<Py<PyDict>>,
         proxy: Option<ProxyConfig>,
         http_version: Option<HttpVersion>,
-        ssl_verify: Option<bool>,
-    ) -> Self {
-        Self { url, method, params, timeout, tag, headers, proxy, http_version, ssl_verify }
+        ssl_verify: Option<SslVerifyArg>,
+        connect_timeout: Option<f64>,
+        read_timeout: Option<f64>,
+        allow_redirects: Option<bool>,
+        max_redirects: Option<usize>,
+        normalize: Option<Vec<String>>,
+        auth: Option<(String, String)>,
+        bearer_token: Option<String>,
+        decode_errors: Option<String>,
+        dns_timeout: Option<f64>,
+        oauth2: Option<OAuth2ClientCredentials>,
+        identity: Option<ClientIdentity>,
+        ca_bundle: Option<CaBundle>,
+        compress_result: Option<String>,
+        tls_min_version: Option<TlsVersion>,
+        tls_max_version: Option<TlsVersion>,
+        impersonate: Option<String>,
+        fingerprint: Option<Vec<String>>,
+        assert_json: Option<Py<PyDict>>,
+        assert_contains: Option<String>,
+        assert_header: Option<HashMap<String, String>>,
+        unix_socket: Option<String>,
+        body_store: Option<String>,
+        resolve: Option<HashMap<String, String>>,
+        ip_version: Option<String>,
+        local_address: Option<String>,
+        isolated: Option<bool>,
+        max_response_headers: Option<usize>,
+        max_header_bytes: Option<usize>,
+        keepalive_interval: Option<f64>,
+        cache: Option<String>,
+        transform: Option<String>,
+        xml: Option<String>,
+        parse_xml: Option<bool>,
+        protobuf: Option<Py<PyBytes>>,
+        grpc_web: Option<bool>,
+        max_response_bytes: Option<usize>,
+        accept_encoding: Option<Vec<String>>,
+        decompress: Option<bool>,
+        compress_body: Option<String>,
+        encoding: Option<String>,
+        raw_body: Option<bool>,
+        memory_threshold: Option<usize>,
+        conditional: Option<String>,
+        retries: Option<u32>,
+        retry_policy: Option<RetryPolicy>,
+        hedge_after: Option<f64>,
+    ) -> PyResult<Self> {
+        let item = Self {
+            url, method, params, timeout, tag, headers, proxy, http_version,
+            ssl_verify: ssl_verify.map(|v| v.get()),
+            connect_timeout, read_timeout, allow_redirects, max_redirects, normalize, auth,
+            bearer_token, decode_errors, dns_timeout, oauth2, identity,
+            ca_bundle, compress_result, tls_min_version, tls_max_version, impersonate,
+            fingerprint, assert_json, assert_contains, assert_header, unix_socket, body_store,
+            resolve, ip_version, local_address, isolated, max_response_headers, max_header_bytes,
+            keepalive_interval, cache, transform, xml, parse_xml, protobuf, grpc_web, max_response_bytes,
+            accept_encoding, decompress, compress_body, encoding, raw_body, memory_threshold, conditional,
+            retries, retry_policy, hedge_after,
+        };
+        item.validate(None)?;
+        Ok(item)
+    }
+
+    /// 从 curl 命令（如浏览器开发者工具中 "Copy as cURL" 的结果）创建请求
+    #[staticmethod]
+    fn from_curl(py: Python, command: &str) -> PyResult<Self> {
+        let item = curl::from_curl(py, command)?;
+        item.validate(None)?;
+        Ok(item)
+    }
+
+    /// 生成可在命令行重现该请求的 curl 命令
+    fn to_curl(&self, py: Python) -> PyResult<String> {
+        Ok(curl::to_curl(&prepare(py, self.clone())?))
+    }
+}
+
+impl RequestItem {
+    /// 校验各字段的取值，构造、提交和执行前都会调用
+    ///
+    /// 字段可在构造后通过属性修改，执行时依赖这里的校验而不是静默忽略无效值；
+    /// 传入 global 时超时层级按全局配置补全后校验。
+    pub(crate) fn validate(&self, global: Option<&GlobalClientConfig>) -> PyResult<()> {
+        if let Some(steps) = &self.normalize {
+            NormalizeStep::parse_all(steps)?;
+        }
+        if let Some(policy) = &self.decode_errors {
+            DecodePolicy::parse(policy)?;
+        }
+        if let Some(compression) = &self.compress_result {
+            ResultCompression::parse(compression)?;
+        }
+        check_tls_range(self.tls_min_version, self.tls_max_version)?;
+        if let Some(profile) = &self.impersonate {
+            Impersonate::parse(profile)?;
+        }
+        if let Some(patterns) = &self.fingerprint {
+            compile_patterns(patterns)?;
+        }
+        if let Some(path) = &self.unix_socket {
+            check_unix_socket(path)?;
+        }
+        if let Some(overrides) = &self.resolve {
+            parse_resolve(overrides)?;
+        }
+        if let Some(version) = &self.ip_version {
+            IpVersion::parse(version)?;
+        }
+        if let Some(local) = &self.local_address {
+            check_local_address(local)?;
+        }
+        check_limit("max_response_headers", self.max_response_headers)?;
+        check_limit("max_header_bytes", self.max_header_bytes)?;
+        check_positive("keepalive_interval", self.keepalive_interval)?;
+        if let Some(mode) = &self.cache {
+            CacheMode::parse(mode)?;
+        }
+        if let Some(name) = &self.transform {
+            check_transform(name)?;
+        }
+        check_xml(self.xml.as_deref(), self.params.is_some())?;
+        check_protobuf(self.protobuf.is_some(), self.params.is_some(), self.xml.is_some(), self.grpc_web)?;
+        check_limit("max_response_bytes", self.max_response_bytes)?;
+        check_encoding(self.accept_encoding.as_deref(), self.decompress, self.compress_result.as_deref())?;
+        if let Some(codec) = &self.compress_body {
+            BodyCompression::parse(codec)?;
+        }
+        if let Some(label) = &self.encoding {
+            check_encoding_label(label)?;
+        }
+        check_raw_body(self.raw_body, self.parse_xml, self.transform.is_some(), self.compress_result.is_some())?;
+        check_limit("memory_threshold", self.memory_threshold)?;
+        if let Some(mode) = &self.conditional {
+            Conditional::parse(mode)?;
+        }
+        check_positive("hedge_after", self.hedge_after)?;
+        check_request(self, global)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_fields_set_after_construction() {
+        let mut item = RequestItem { url: "http://example.com".to_string(), ..Default::default() };
+        assert!(item.validate(None).is_ok());
+        item.cache = Some("sometimes".to_string());
+        assert!(item.validate(None).is_err());
+        item.cache = None;
+        item.max_response_bytes = Some(0);
+        assert!(item.validate(None).is_err());
+        item.max_response_bytes = None;
+        item.timeout = Some(1.0);
+        item.read_timeout = Some(5.0);
+        assert!(item.validate(None).is_err());
     }
 }
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+use futures::future::join_all;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+use crate::request::executor::{batch_result_to_py, execute_once};
+use crate::request::{PreparedRequest, RequestItem};
+use crate::request::replay::{meta_of, rebuild};
+use crate::request::timeouts;
+use crate::testing::clock;
+use crate::utils::py_to_json;
+
+/// 重试策略：最多尝试次数、指数退避，以及可选的异常类型 / 状态码过滤
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    #[pyo3(get, set)]
+    pub max_attempts: u32,
+    #[pyo3(get, set)]
+    pub backoff: f64,
+    #[pyo3(get, set)]
+    pub backoff_factor: f64,
+    #[pyo3(get, set)]
+    pub max_backoff: f64,
+    #[pyo3(get, set)]
+    pub retry_on: Option<Vec<String>>,
+    #[pyo3(get, set)]
+    pub retry_on_status: Option<Vec<u16>>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff: 0.5,
+            backoff_factor: 2.0,
+            max_backoff: 30.0,
+            retry_on: None,
+            retry_on_status: None,
+        }
+    }
+}
+
+#[pymethods]
+impl RetryPolicy {
+    #[new]
+    #[pyo3(signature = (max_attempts=3, backoff=0.5, backoff_factor=2.0, max_backoff=30.0, retry_on=None, retry_on_status=None))]
+    fn new(
+        max_attempts: u32,
+        backoff: f64,
+        backoff_factor: f64,
+        max_backoff: f64,
+        retry_on: Option<Vec<String>>,
+        retry_on_status: Option<Vec<u16>>,
+    ) -> PyResult<Self> {
+        if max_attempts == 0 {
+            return Err(PyValueError::new_err("max_attempts must be at least 1"));
+        }
+        if backoff < 0.0 || backoff_factor < 1.0 || max_backoff < 0.0 {
+            return Err(PyValueError::new_err("backoff and max_backoff must be >= 0, backoff_factor must be >= 1"));
+        }
+        Ok(RetryPolicy { max_attempts, backoff, backoff_factor, max_backoff, retry_on, retry_on_status })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RetryPolicy(max_attempts={}, backoff={}, backoff_factor={}, max_backoff={})",
+            self.max_attempts, self.backoff, self.backoff_factor, self.max_backoff
+        )
+    }
+}
+
+impl RetryPolicy {
+    /// 第 attempt 次重试前的等待时间（attempt 从 1 开始）
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let secs = self.backoff * self.backoff_factor.powi(attempt.saturating_sub(1) as i32);
+        Duration::from_secs_f64(secs.min(self.max_backoff))
+    }
+
+    /// 发送请求，失败且符合条件时按退避时间重试，meta.attempts 记录执行次数
+    pub(crate) async fn execute(&self, prepared: PreparedRequest) -> HashMap<String, String> {
+        self.execute_with(|| execute_once(prepared.clone())).await
+    }
+
+    /// 执行 attempt，失败且符合条件时按退避时间重新执行
+    pub(crate) async fn execute_with<F, Fut>(&self, mut attempt_fn: F) -> HashMap<String, String>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = HashMap<String, String>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let mut result = attempt_fn().await;
+            let (exc_type, status) = outcome(&result);
+            // 429 / 503 按 Retry-After 等待（不受 max_backoff 限制），等待时间超出 total_timeout 的剩余时间时
+            // 不再重试，直接返回这次的响应
+            let wait = retry_after(&result, status).unwrap_or_else(|| self.delay(attempt));
+            let out_of_time = timeouts::remaining().is_some_and(|remaining| wait >= remaining);
+            if attempt >= self.max_attempts || out_of_time || !self.should_retry(exc_type.as_deref(), status) {
+                if let Some(Value::Object(mut meta)) = result.get("meta").and_then(|m| serde_json::from_str(m).ok()) {
+                    meta.insert("attempts".to_string(), Value::from(attempt));
+                    result.insert("meta".to_string(), Value::Object(meta).to_string());
+                }
+                return result;
+            }
+            clock::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    /// 结果是否失败且符合重试条件；未设置过滤条件时所有失败都重试
+    pub(crate) fn should_retry(&self, exc_type: Option<&str>, status: u16) -> bool {
+        let Some(exc_type) = exc_type else { return false };
+        if self.retry_on.is_none() && self.retry_on_status.is_none() {
+            return true;
+        }
+        self.retry_on.as_ref().is_some_and(|types| types.iter().any(|t| t == exc_type))
+            || self.retry_on_status.as_ref().is_some_and(|codes| codes.contains(&status))
+    }
+}
+
+/// 请求级的重试设置：retry_policy 替换 base 策略，retries 只覆盖尝试次数
+pub(crate) fn for_request(req: &RequestItem, base: Option<RetryPolicy>) -> Option<RetryPolicy> {
+    let policy = req.retry_policy.clone().or(base);
+    match req.retries {
+        Some(retries) => Some(RetryPolicy { max_attempts: retries.saturating_add(1), ..policy.unwrap_or_default() }),
+        None => policy,
+    }
+}
+
+fn exception_type(exc: &Value) -> Option<&str> {
+    exc.get("type").and_then(|t| t.as_str())
+}
+
+/// 429 / 503 响应的 Retry-After：秒数或 HTTP 日期
+fn retry_after(result: &HashMap<String, String>, status: u16) -> Option<Duration> {
+    if status != 429 && status != 503 {
+        return None;
+    }
+    let response: Value = serde_json::from_str(result.get("response")?).ok()?;
+    let value = response.get("headers")?.get("retry-after")?.as_str()?.trim();
+    match value.parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        // 已过去的日期表示可以立即重试
+        Err(_) => httpdate::parse_http_date(value).ok()
+            .map(|at| at.duration_since(SystemTime::now()).unwrap_or_default()),
+    }
+}
+
+/// 执行结果的异常类型和状态码
+fn outcome(result: &HashMap<String, String>) -> (Option<String>, u16) {
+    let exc = result.get("exception")
+        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+        .unwrap_or(Value::Null);
+    let status = result.get("http_status").and_then(|s| s.parse().ok()).unwrap_or(0);
+    (exception_type(&exc).map(|t| t.to_string()), status)
+}
+
+/// 重新执行 fetch_requests 结果中失败的请求，按原顺序合并回结果列表
+///
+/// 只有带 meta.request 记录的失败结果会被重试（如 InvalidUrl 这类未发出的请求保持原样）。
+#[pyfunction]
+#[pyo3(signature = (results, policy=None))]
+pub fn retry_failed<'py>(py: Python<'py>, results: &PyList, policy: Option<RetryPolicy>) -> PyResult<&'py PyAny> {
+    let policy = policy.unwrap_or_default();
+    let merged: Vec<PyObject> = results.iter().map(|r| r.into_py(py)).collect();
+
+    let mut pending = Vec::new();
+    for (index, item) in results.iter().enumerate() {
+        let Ok(result) = item.downcast::<PyDict>() else { continue };
+        let exc = match result.get_item("exception")? {
+            Some(exc) => py_to_json(py, exc)?,
+            None => Value::Null,
+        };
+        let exc = match exc {
+            Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::Null),
+            other => other,
+        };
+        let status = result.get_item("http_status")?
+            .and_then(|s| s.extract::<u16>().ok().or_else(|| s.extract::<String>().ok()?.parse().ok()))
+            .unwrap_or(0);
+        if !policy.should_retry(exception_type(&exc), status) {
+            continue;
+        }
+        let meta = meta_of(py, result)?;
+        if meta.get("request").is_none() {
+            continue;
+        }
+        pending.push((index, rebuild(&meta, true)?));
+    }
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let retried = join_all(pending.into_iter().map(|(index, prepared)| {
+            let policy = policy.clone();
+            async move { (index, policy.execute(prepared).await) }
+        })).await;
+
+        Python::with_gil(|py| -> PyResult<PyObject> {
+            let mut merged = merged;
+            for (index, result) in retried {
+                merged[index] = batch_result_to_py(py, &result)?;
+            }
+            Ok(PyList::new(py, merged).into_py(py))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn limited(retry_after: &str) -> HashMap<String, String> {
+        let mut result = HashMap::new();
+        result.insert("http_status".to_string(), "429".to_string());
+        result.insert("exception".to_string(), r#"{"type":"HttpStatusError"}"#.to_string());
+        result.insert("response".to_string(), serde_json::json!({"headers": {"retry-after": retry_after}, "content": ""}).to_string());
+        result.insert("meta".to_string(), "{}".to_string());
+        result
+    }
+
+    fn attempts(result: &HashMap<String, String>) -> u64 {
+        let meta: Value = serde_json::from_str(&result["meta"]).unwrap();
+        meta["attempts"].as_u64().unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_full_retry_after_seconds() {
+        let policy = RetryPolicy { max_attempts: 2, ..Default::default() };
+        let started = tokio::time::Instant::now();
+        let result = policy.execute_with(|| async { limited("120") }).await;
+        // 超过 max_backoff（30 秒）也按服务端要求等待
+        assert!(started.elapsed() >= Duration::from_secs(120));
+        assert_eq!(attempts(&result), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_until_retry_after_date() {
+        let policy = RetryPolicy { max_attempts: 2, ..Default::default() };
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(90));
+        let started = tokio::time::Instant::now();
+        let result = policy.execute_with(|| async { limited(&date) }).await;
+        // HTTP 日期精确到秒
+        assert!(started.elapsed() >= Duration::from_secs(88));
+        assert_eq!(attempts(&result), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn returns_response_when_retry_after_exceeds_deadline() {
+        let policy = RetryPolicy { max_attempts: 3, ..Default::default() };
+        let started = tokio::time::Instant::now();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let result = timeouts::with_deadline(deadline, policy.execute_with(|| async { limited("60") })).await;
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        assert_eq!(result["http_status"], "429");
+        assert_eq!(attempts(&result), 1);
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
+
+/// 按 Content-Type 注册的请求体序列化函数
+static SERIALIZERS: Lazy<RwLock<HashMap<String, PyObject>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 取出 Content-Type 的 MIME 部分，忽略 charset 等参数
+pub(crate) fn mime_of(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase()
+}
+
+/// 是否为 Content-Type 注册了序列化函数
+pub(crate) fn has_serializer(content_type: &str) -> bool {
+    SERIALIZERS.read().unwrap().contains_key(&mime_of(content_type))
+}
+
+/// 使用已注册的序列化函数转换 params，未注册时返回 None
+pub(crate) fn serialize_params(py: Python, content_type: &str, params: &PyDict) -> Option<PyResult<Vec<u8>>> {
+    let serializer = SERIALIZERS.read().unwrap().get(&mime_of(content_type))?.clone_ref(py);
+    Some(serializer.call1(py, (params,)).and_then(|out| {
+        let out = out.as_ref(py);
+        if let Ok(bytes) = out.downcast::<PyBytes>() {
+            Ok(bytes.as_bytes().to_vec())
+        } else if let Ok(text) = out.downcast::<PyString>() {
+            Ok(text.to_str()?.as_bytes().to_vec())
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "Serializer for '{}' must return bytes or str, got {}", content_type, out.get_type().name()?
+            )))
+        }
+    }))
+}
+
+/// 注册请求体序列化函数，params 按请求的 Content-Type 选择序列化方式
+#[pyfunction]
+pub fn register_serializer(py: Python, content_type: String, serializer: PyObject) -> PyResult<()> {
+    if !serializer.as_ref(py).is_callable() {
+        return Err(PyTypeError::new_err("serializer must be callable"));
+    }
+    SERIALIZERS.write().unwrap().insert(mime_of(&content_type), serializer);
+    Ok(())
+}
+
+/// 移除已注册的序列化函数，返回是否存在
+#[pyfunction]
+pub fn unregister_serializer(content_type: String) -> bool {
+    SERIALIZERS.write().unwrap().remove(&mime_of(&content_type)).is_some()
+}
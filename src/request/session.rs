@@ -0,0 +1,183 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+use serde_json::json;
+use url::Url;
+use crate::audit;
+use crate::network::client::evict_host;
+use crate::request::RequestItem;
+use crate::testing::cassette;
+use crate::utils::{json_to_py, normalize_url};
+
+/// 会话内请求的统计
+#[derive(Default)]
+struct SessionState {
+    hosts: BTreeSet<String>,
+    sent: u64,
+    succeeded: u64,
+    failed: u64,
+    closed: bool,
+}
+
+impl SessionState {
+    fn add_url(&mut self, url: &str) {
+        let host = normalize_url(url, "http").ok()
+            .and_then(|u| Url::parse(&u).ok())
+            .and_then(|u| u.host_str().map(|h| h.trim_matches(['[', ']']).to_lowercase()));
+        if let Some(host) = host {
+            self.hosts.insert(host);
+        }
+    }
+
+    /// 记录一个结果字典：成败和重定向后的主机
+    fn add_result(&mut self, result: &PyAny) {
+        let Ok(result) = result.downcast::<PyDict>() else { return };
+        self.sent += 1;
+        let failed = result.get_item("exception").ok().flatten()
+            .is_some_and(|exc| exc.is_true().unwrap_or(false));
+        if failed { self.failed += 1 } else { self.succeeded += 1 }
+        let final_url = result.get_item("meta").ok().flatten()
+            .and_then(|meta| meta.get_item("final_url").ok())
+            .and_then(|url| url.extract::<String>().ok());
+        if let Some(url) = final_url {
+            self.add_url(&url);
+        }
+    }
+}
+
+/// 关闭会话：移除访问过的主机的客户端，写入审计日志和磁带
+fn shutdown(state: &Mutex<SessionState>) {
+    let hosts = {
+        let mut state = state.lock().unwrap();
+        state.closed = true;
+        state.hosts.clone()
+    };
+    for host in hosts {
+        evict_host(host);
+    }
+    audit::flush();
+    cassette::flush();
+}
+
+/// 一组请求的资源句柄，配合 async with 使用
+///
+/// 退出时关闭会话访问过的主机的连接池，并把审计日志和录制中的磁带写入磁盘。
+#[pyclass]
+pub struct Session {
+    state: Arc<Mutex<SessionState>>,
+}
+
+impl Session {
+    /// 通过模块函数发送，等待结果后记录统计
+    fn call<'py>(&self, py: Python<'py>, function: &str, args: &PyTuple, kwargs: Option<&PyDict>) -> PyResult<&'py PyAny> {
+        if self.state.lock().unwrap().closed {
+            return Err(PyRuntimeError::new_err("Session is closed"));
+        }
+        let awaitable = py.import("rusty_req")?.getattr(function)?.call(args, kwargs)?;
+        let results = pyo3_asyncio::tokio::into_future(awaitable)?;
+        let state = self.state.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let results = results.await?;
+            Python::with_gil(|py| {
+                let mut state = state.lock().unwrap();
+                let results = results.as_ref(py);
+                match results.downcast::<PyList>() {
+                    Ok(list) => list.iter().for_each(|r| state.add_result(r)),
+                    Err(_) => state.add_result(results),
+                }
+            });
+            Ok(results)
+        })
+    }
+}
+
+#[pymethods]
+impl Session {
+    #[new]
+    fn new() -> Self {
+        Session { state: Arc::new(Mutex::new(SessionState::default())) }
+    }
+
+    /// 参数与模块级 fetch_single 相同
+    #[pyo3(signature = (*args, **kwargs))]
+    fn fetch_single<'py>(&self, py: Python<'py>, args: &PyTuple, kwargs: Option<&PyDict>) -> PyResult<&'py PyAny> {
+        let url = match args.get_item(0) {
+            Ok(url) => Some(url),
+            Err(_) => kwargs.and_then(|k| k.get_item("url").ok().flatten()),
+        };
+        if let Some(url) = url.and_then(|u| u.extract::<String>().ok()) {
+            self.state.lock().unwrap().add_url(&url);
+        }
+        self.call(py, "fetch_single", args, kwargs)
+    }
+
+    /// 参数与模块级 fetch_requests 相同
+    #[pyo3(signature = (*args, **kwargs))]
+    fn fetch_requests<'py>(&self, py: Python<'py>, args: &PyTuple, kwargs: Option<&PyDict>) -> PyResult<&'py PyAny> {
+        let requests = match args.get_item(0) {
+            Ok(requests) => Some(requests),
+            Err(_) => kwargs.and_then(|k| k.get_item("requests").ok().flatten()),
+        };
+        if let Some(requests) = requests.and_then(|r| r.iter().ok()) {
+            let mut state = self.state.lock().unwrap();
+            for item in requests.flatten() {
+                if let Ok(item) = item.extract::<PyRef<RequestItem>>() {
+                    state.add_url(&item.url);
+                }
+            }
+        }
+        self.call(py, "fetch_requests", args, kwargs)
+    }
+
+    /// 会话内的请求统计：sent、succeeded、failed 和访问过的主机
+    #[getter]
+    fn stats(&self, py: Python) -> PyResult<PyObject> {
+        let state = self.state.lock().unwrap();
+        json_to_py(py, &json!({
+            "sent": state.sent,
+            "succeeded": state.succeeded,
+            "failed": state.failed,
+            "hosts": state.hosts,
+        }))
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// 关闭会话，可重复调用
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let state = self.state.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            shutdown(&state);
+            Ok(())
+        })
+    }
+
+    fn __aenter__<'py>(slf: Py<Self>, py: Python<'py>) -> PyResult<&'py PyAny> {
+        pyo3_asyncio::tokio::future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// 退出 async with 时关闭会话，不吞掉异常
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+    ) -> PyResult<&'py PyAny> {
+        let state = self.state.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            shutdown(&state);
+            Ok(false)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        let state = self.state.lock().unwrap();
+        format!("Session(hosts={}, sent={}, closed={})", state.hosts.len(), state.sent, state.closed)
+    }
+}
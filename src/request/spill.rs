@@ -0,0 +1,117 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use serde_json::{json, Value};
+use tempfile::TempPath;
+use tokio::io::AsyncWriteExt;
+use crate::utils::DecompressError;
+
+/// 在系统临时目录中新建文件：随机文件名、独占创建（不跟随已存在的符号链接），权限仅限当前用户
+///
+/// 返回的 TempPath 在 drop 时删除文件，调用 keep 后才交给调用方。
+fn temp_file() -> std::io::Result<(std::fs::File, TempPath)> {
+    Ok(tempfile::Builder::new().prefix("rusty-req-").suffix(".body").tempfile()?.into_parts())
+}
+
+/// 写入临时文件的响应体；文件交给调用方，不会自动删除
+pub struct Spilled {
+    pub path: PathBuf,
+    pub size: usize,
+}
+
+impl Spilled {
+    /// 写入 meta.body_file 的引用
+    pub(crate) fn reference(&self, content_encoding: &str) -> Value {
+        json!({"path": self.path.to_string_lossy(), "size": self.size, "content_encoding": content_encoding})
+    }
+
+    pub(crate) async fn discard(self) {
+        let _ = tokio::fs::remove_file(&self.path).await;
+    }
+}
+
+/// 正在写入的临时文件；未调用 finish 就被 drop（如读取中途超时、对冲请求落败）时删除文件
+pub(crate) struct SpillFile {
+    file: tokio::fs::File,
+    path: TempPath,
+    size: usize,
+}
+
+impl SpillFile {
+    pub(crate) async fn create() -> std::io::Result<Self> {
+        let (file, path) = tokio::task::spawn_blocking(temp_file).await??;
+        Ok(SpillFile { file: tokio::fs::File::from_std(file), path, size: 0 })
+    }
+
+    pub(crate) async fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(bytes).await?;
+        self.size += bytes.len();
+        Ok(())
+    }
+
+    pub(crate) async fn finish(mut self) -> std::io::Result<Spilled> {
+        // 出错时 self 被 drop，文件随之删除
+        self.file.flush().await?;
+        Ok(Spilled { path: self.path.keep()?, size: self.size })
+    }
+
+    pub(crate) async fn discard(self) {
+        drop(self.file);
+        let _ = tokio::task::spawn_blocking(move || self.path.close()).await;
+    }
+}
+
+/// deflate 数据是否带 zlib 头；部分服务端发送不带头的原始 deflate 数据
+fn is_zlib(header: &[u8]) -> bool {
+    match header {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+/// 按 Content-Encoding 的相反顺序叠加流式解码器
+fn decoder(content_encoding: &str, file: std::fs::File) -> Result<Box<dyn Read + Send>, String> {
+    let mut reader: Box<dyn Read + Send> = Box::new(file);
+    for coding in content_encoding.split(',').map(|c| c.trim().to_lowercase()).rev() {
+        reader = match coding.as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => Box::new(MultiGzDecoder::new(reader)),
+            "deflate" => {
+                let mut buffered = BufReader::new(reader);
+                let zlib = is_zlib(buffered.fill_buf().map_err(|e| e.to_string())?);
+                match zlib {
+                    true => Box::new(ZlibDecoder::new(buffered)),
+                    false => Box::new(DeflateDecoder::new(buffered)),
+                }
+            }
+            "br" => Box::new(brotli::Decompressor::new(reader, 4096)),
+            "zstd" => Box::new(zstd::stream::read::Decoder::new(reader).map_err(|e| e.to_string())?),
+            other => return Err(format!("unsupported content encoding '{}'", other)),
+        };
+    }
+    Ok(reader)
+}
+
+/// 把临时文件中的压缩数据流式解压到新的临时文件，替换原文件
+///
+/// 解压结果超出 max_bytes 时停止解压并删除新文件。
+pub(crate) async fn decompress(spilled: Spilled, content_encoding: &str, max_bytes: Option<usize>) -> Result<Spilled, DecompressError> {
+    let content_encoding = content_encoding.to_string();
+    let source = spilled.path.clone();
+    let decoded = tokio::task::spawn_blocking(move || -> Result<Spilled, DecompressError> {
+        let file = std::fs::File::open(&source).map_err(|e| DecompressError::Invalid(e.to_string()))?;
+        let reader = decoder(&content_encoding, file).map_err(DecompressError::Invalid)?;
+        // 多读一个字节用于判断是否超出上限
+        let limit = max_bytes.map_or(u64::MAX, |max| max as u64 + 1);
+        // 解压失败时 TempPath 被 drop，文件随之删除
+        let (mut out, path) = temp_file().map_err(|e| DecompressError::Invalid(e.to_string()))?;
+        let size = std::io::copy(&mut reader.take(limit), &mut out)
+            .map_err(|e| DecompressError::Invalid(format!("failed to decompress {} body: {}", content_encoding, e)))?;
+        if max_bytes.is_some_and(|max| size > max as u64) {
+            return Err(DecompressError::TooLarge);
+        }
+        Ok(Spilled { path: path.keep().map_err(|e| DecompressError::Invalid(e.to_string()))?, size: size as usize })
+    }).await.map_err(|e| DecompressError::Invalid(e.to_string()))?;
+    spilled.discard().await;
+    decoded
+}
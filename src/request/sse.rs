@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use pyo3::exceptions::{PyConnectionError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use crate::network::client::get_or_create_client;
+use crate::network::ProxyConfig;
+use crate::request::executor::client_options;
+use crate::request::RequestItem;
+use crate::request::timeouts::NO_TOTAL_TIMEOUT;
+use crate::testing::clock;
+use crate::GLOBAL_CLIENT_CONFIG;
+
+// 服务端未指定 retry 时的默认重连间隔
+const DEFAULT_RECONNECT_DELAY: f64 = 3.0;
+
+/// 一个完整的 SSE 事件
+struct Event {
+    event: String,
+    data: String,
+    id: Option<String>,
+}
+
+/// text/event-stream 解析器，按 WHATWG 规范处理 \r\n、\r、\n 三种换行
+#[derive(Default)]
+struct EventParser {
+    buf: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl EventParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Event> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n' || *b == b'\r') {
+            // \r 在末尾时可能是 \r\n 的前半部分，等待更多数据
+            if self.buf[pos] == b'\r' && pos + 1 == self.buf.len() {
+                break;
+            }
+            let skip = if self.buf[pos] == b'\r' && self.buf[pos + 1] == b'\n' { 2 } else { 1 };
+            let line: Vec<u8> = self.buf.drain(..pos + skip).take(pos).collect();
+            if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<Event> {
+        // 空行分发事件
+        if line.is_empty() {
+            let event = self.event.take();
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(Event {
+                event: event.unwrap_or_else(|| "message".to_string()),
+                data: std::mem::take(&mut self.data).join("\n"),
+                id: self.last_event_id.clone(),
+            });
+        }
+        // 冒号开头为注释
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(ms) = value.parse::<u64>() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// 回调返回 False 时停止
+fn dispatch(on_event: &PyObject, event: &Event) -> PyResult<bool> {
+    Python::with_gil(|py| {
+        let dict = PyDict::new(py);
+        dict.set_item("event", &event.event)?;
+        dict.set_item("data", &event.data)?;
+        dict.set_item("id", &event.id)?;
+        let ret = on_event.call1(py, (dict,))?;
+        Ok(!ret.as_ref(py).is(pyo3::types::PyBool::new(py, false)))
+    })
+}
+
+/// 订阅 Server-Sent Events，每个事件调用一次 on_event(event)
+///
+/// 连接断开后等待 retry（服务端指定或 reconnect_delay）并携带 Last-Event-ID 自动重连；
+/// 服务端返回 204、非 2xx 状态码或回调返回 False 时结束。
+#[pyfunction]
+#[pyo3(signature = (url, on_event, headers=None, last_event_id=None, reconnect_delay=None, max_reconnects=None, read_timeout=None, proxy=None, ssl_verify=None))]
+pub fn fetch_sse<'py>(
+    py: Python<'py>,
+    url: String,
+    on_event: PyObject,
+    headers: Option<HashMap<String, String>>,
+    last_event_id: Option<String>,
+    reconnect_delay: Option<f64>,
+    max_reconnects: Option<u32>,
+    read_timeout: Option<f64>,
+    proxy: Option<ProxyConfig>,
+    ssl_verify: Option<bool>,
+) -> PyResult<&'py PyAny> {
+    if !on_event.as_ref(py).is_callable() {
+        return Err(PyValueError::new_err("on_event must be callable"));
+    }
+    if reconnect_delay.is_some_and(|d| d < 0.0) || read_timeout.is_some_and(|t| t <= 0.0) {
+        return Err(PyValueError::new_err("reconnect_delay must be >= 0 and read_timeout must be > 0"));
+    }
+    let req = RequestItem { url: url.clone(), proxy, ssl_verify, ..Default::default() };
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let global_config = GLOBAL_CLIENT_CONFIG.read().unwrap().clone();
+        let options = client_options(&req, &global_config, true).await;
+        let client = get_or_create_client(&url, &options).await
+            .map_err(|e| PyConnectionError::new_err(format!("Failed to build reqwest client: {}", e)))?;
+
+        let mut parser = EventParser { last_event_id, ..Default::default() };
+        let mut delay = Duration::from_secs_f64(reconnect_delay.unwrap_or(DEFAULT_RECONNECT_DELAY));
+        let mut events = 0u64;
+        let mut reconnects = 0u32;
+        let mut last_error: Option<String>;
+
+        'connection: loop {
+            last_error = None;
+            // 事件流是长连接，不设总超时，断流由 read_timeout 判断
+            let mut builder = client.get(&url)
+                .timeout(NO_TOTAL_TIMEOUT)
+                .header("Accept", "text/event-stream")
+                .header("Cache-Control", "no-cache");
+            for (name, value) in headers.iter().flatten() {
+                builder = builder.header(name, value);
+            }
+            if let Some(id) = &parser.last_event_id {
+                builder = builder.header("Last-Event-ID", id);
+            }
+            if let Some(token) = &global_config.bearer_token {
+                builder = builder.bearer_auth(token);
+            }
+
+            match builder.send().await {
+                Ok(mut res) => {
+                    let status = res.status();
+                    if status == reqwest::StatusCode::NO_CONTENT {
+                        break;
+                    }
+                    if !status.is_success() {
+                        return Err(PyConnectionError::new_err(format!("SSE request failed with HTTP status {}", status.as_u16())));
+                    }
+                    // 新连接丢弃上一次连接中未完成的事件
+                    parser.buf.clear();
+                    parser.data.clear();
+                    parser.event = None;
+                    loop {
+                        let chunk = match read_timeout {
+                            Some(secs) => match tokio::time::timeout(Duration::from_secs_f64(secs), res.chunk()).await {
+                                Ok(chunk) => chunk,
+                                Err(_) => {
+                                    last_error = Some(format!("no data received within {:.2} seconds", secs));
+                                    break;
+                                }
+                            },
+                            None => res.chunk().await,
+                        };
+                        match chunk {
+                            Ok(Some(bytes)) => {
+                                for event in parser.feed(&bytes) {
+                                    events += 1;
+                                    if !dispatch(&on_event, &event)? {
+                                        break 'connection;
+                                    }
+                                }
+                            }
+                            Ok(None) => break,
+                            Err(e) => {
+                                last_error = Some(e.to_string());
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => last_error = Some(e.to_string()),
+            }
+
+            if max_reconnects.is_some_and(|max| reconnects >= max) {
+                if let Some(e) = last_error {
+                    return Err(PyConnectionError::new_err(format!("SSE connection lost after {} reconnects: {}", reconnects, e)));
+                }
+                break;
+            }
+            if let Some(retry) = parser.retry {
+                delay = retry;
+            }
+            clock::sleep(delay).await;
+            reconnects += 1;
+        }
+
+        Python::with_gil(|py| -> PyResult<PyObject> {
+            let summary = PyDict::new(py);
+            summary.set_item("events", events)?;
+            summary.set_item("reconnects", reconnects)?;
+            summary.set_item("last_event_id", &parser.last_event_id)?;
+            Ok(summary.into_py(py))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&[u8]]) -> (EventParser, Vec<(String, String, Option<String>)>) {
+        let mut parser = EventParser::default();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed(chunk).into_iter().map(|e| (e.event, e.data, e.id)));
+        }
+        (parser, events)
+    }
+
+    #[test]
+    fn parses_fields_and_multiline_data() {
+        let (parser, events) = parse(&[b": comment\nevent: update\ndata: a\ndata:b\nid: 7\nretry: 1500\n\ndata: next\n\n"]);
+        assert_eq!(events, [
+            ("update".to_string(), "a\nb".to_string(), Some("7".to_string())),
+            ("message".to_string(), "next".to_string(), Some("7".to_string())),
+        ]);
+        assert_eq!(parser.retry, Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn handles_all_line_endings_across_chunks() {
+        // \r\n 被拆在两个数据块之间时不产生多余的空行
+        let (_, events) = parse(&[b"data: one\r", b"\n\r", b"\ndata: two\r\rdata: three\n", b"\n"]);
+        let data: Vec<_> = events.into_iter().map(|(_, data, _)| data).collect();
+        assert_eq!(data, ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn ignores_events_without_data_and_invalid_fields() {
+        let (parser, events) = parse(&[b"event: ping\n\nid: a\0b\nretry: soon\nunknown: x\ndata\n\n"]);
+        // 只有 "data" 字段名时数据为空字符串，事件类型已在上一个空行重置
+        assert_eq!(events, [("message".to_string(), String::new(), None)]);
+        assert_eq!(parser.retry, None);
+    }
+}
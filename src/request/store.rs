@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use serde_json::{json, Value};
+use crate::utils::sha256_hex;
+
+// 临时文件序号，避免并发写入同一内容时互相覆盖
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 把响应体写入按内容寻址的目录，返回引用信息
+///
+/// 文件路径为 <dir>/<哈希前两位>/<sha256>，相同内容只写一次；
+/// 先写临时文件再重命名，读取方不会看到写了一半的文件。
+pub(crate) async fn put(dir: &str, bytes: &[u8]) -> std::io::Result<Value> {
+    let hash = sha256_hex(bytes);
+    let shard = PathBuf::from(dir).join(&hash[..2]);
+    let path = shard.join(&hash);
+
+    let deduplicated = tokio::fs::try_exists(&path).await?;
+    if !deduplicated {
+        tokio::fs::create_dir_all(&shard).await?;
+        let tmp = shard.join(format!(
+            "{}.{}.{}.tmp", hash, std::process::id(), TMP_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::write(&tmp, bytes).await?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(e);
+        }
+    }
+
+    Ok(json!({
+        "sha256": hash,
+        "path": path.to_string_lossy(),
+        "size": bytes.len(),
+        "deduplicated": deduplicated,
+    }))
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use std::time::Duration;
+use pyo3::exceptions::{PyConnectionError, PyStopAsyncIteration, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use tokio::sync::Mutex;
+use crate::network::client::get_or_create_client;
+use crate::network::ProxyConfig;
+use crate::request::executor::client_options;
+use crate::request::timeouts::{check_positive, NO_TOTAL_TIMEOUT};
+use crate::request::{PreparedRequest, RequestItem};
+use crate::utils::normalize_url;
+use crate::GLOBAL_CLIENT_CONFIG;
+
+/// 流式读取的响应：状态码和响应头在创建时可用，响应体按块异步迭代
+///
+/// 每次迭代才从连接读取下一块，调用方处理慢时服务端随之被限速。
+#[pyclass]
+pub struct ResponseStream {
+    #[pyo3(get)]
+    url: String,
+    #[pyo3(get)]
+    status: u16,
+    headers: Vec<(String, String)>,
+    read_timeout: Option<f64>,
+    // 读完或 close 后为 None
+    response: Arc<Mutex<Option<reqwest::Response>>>,
+}
+
+#[pymethods]
+impl ResponseStream {
+    #[getter]
+    fn headers<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        for (name, value) in &self.headers {
+            dict.set_item(name, value)?;
+        }
+        Ok(dict)
+    }
+
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    /// 下一块响应体（bytes），读完后抛出 StopAsyncIteration
+    fn __anext__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let response = self.response.clone();
+        let read_timeout = self.read_timeout;
+        let next = pyo3_asyncio::tokio::future_into_py::<_, PyObject>(py, async move {
+            let mut guard = response.lock().await;
+            let Some(res) = guard.as_mut() else {
+                return Err(PyStopAsyncIteration::new_err(()));
+            };
+            let chunk = match read_timeout {
+                Some(secs) => tokio::time::timeout(Duration::from_secs_f64(secs), res.chunk()).await
+                    .map_err(|_| PyTimeoutError::new_err(format!("No data received within {:.2} seconds", secs)))?,
+                None => res.chunk().await,
+            };
+            match chunk {
+                Ok(Some(bytes)) => Ok(Python::with_gil(|py| PyBytes::new(py, &bytes).into_py(py))),
+                Ok(None) => {
+                    *guard = None;
+                    Err(PyStopAsyncIteration::new_err(()))
+                }
+                Err(e) => {
+                    *guard = None;
+                    Err(PyConnectionError::new_err(format!("Failed to read response body: {}", e)))
+                }
+            }
+        })?;
+        Ok(Some(next.into_py(py)))
+    }
+
+    /// 关闭连接，丢弃未读取的响应体
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let response = self.response.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            response.lock().await.take();
+            Ok(())
+        })
+    }
+
+    fn __aenter__<'py>(slf: Py<Self>, py: Python<'py>) -> PyResult<&'py PyAny> {
+        pyo3_asyncio::tokio::future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+    ) -> PyResult<&'py PyAny> {
+        let response = self.response.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            response.lock().await.take();
+            Ok(false)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ResponseStream(status={}, url='{}')", self.status, self.url)
+    }
+}
+
+/// 发送请求并在收到响应头后返回 ResponseStream，响应体不在内存中缓冲
+///
+/// timeout 限制收到响应头之前的时间，read_timeout 限制每一块之间的间隔。
+/// 不经过钩子、中间件、重试和磁带，gzip / deflate / br 由客户端边读边解压。
+#[pyfunction]
+#[pyo3(signature = (url, method=None, params=None, headers=None, timeout=None, read_timeout=None, proxy=None, ssl_verify=None, bearer_token=None))]
+pub fn fetch_stream<'py>(
+    py: Python<'py>,
+    url: String,
+    method: Option<String>,
+    params: Option<Py<PyDict>>,
+    headers: Option<Py<PyDict>>,
+    timeout: Option<f64>,
+    read_timeout: Option<f64>,
+    proxy: Option<ProxyConfig>,
+    ssl_verify: Option<bool>,
+    bearer_token: Option<String>,
+) -> PyResult<&'py PyAny> {
+    check_positive("timeout", timeout)?;
+    check_positive("read_timeout", read_timeout)?;
+    let global_config = GLOBAL_CLIENT_CONFIG.read().unwrap().clone();
+    let url = normalize_url(&url, global_config.default_scheme.as_deref().unwrap_or("http"))
+        .map_err(PyValueError::new_err)?;
+    let req = RequestItem { url: url.clone(), method, params, headers, timeout, proxy, ssl_verify, bearer_token, ..Default::default() };
+    let prepared = PreparedRequest::from_item(py, &req, url)?;
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let options = client_options(&prepared.request, &global_config, true).await;
+        let client = get_or_create_client(&prepared.url, &options).await
+            .map_err(|e| PyConnectionError::new_err(format!("Failed to build reqwest client: {}", e)))?;
+        let method = prepared.method.parse::<reqwest::Method>().unwrap_or(reqwest::Method::GET);
+        // 总超时会在读取中途切断流，这里只限制等待响应头的时间
+        let mut builder = prepared.apply(client.request(method, &prepared.url), &[]).timeout(NO_TOTAL_TIMEOUT);
+        let token = prepared.request.bearer_token.as_ref().or(global_config.bearer_token.as_ref());
+        if let Some(token) = token.filter(|_| prepared.request.auth.is_none()) {
+            builder = builder.bearer_auth(token);
+        }
+
+        let timeout = timeout.or(global_config.timeout).unwrap_or(30.0);
+        let res = tokio::time::timeout(Duration::from_secs_f64(timeout), builder.send()).await
+            .map_err(|_| PyTimeoutError::new_err(format!("No response within {:.2} seconds", timeout)))?
+            .map_err(|e| PyConnectionError::new_err(format!("Request error: {}", e)))?;
+        let headers = res.headers().iter()
+            .map(|(k, v)| (k.to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+            .collect::<Vec<_>>();
+        Ok(ResponseStream {
+            url: res.url().to_string(),
+            status: res.status().as_u16(),
+            headers,
+            read_timeout,
+            response: Arc::new(Mutex::new(Some(res))),
+        })
+    })
+}
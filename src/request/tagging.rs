@@ -0,0 +1,80 @@
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use url::Url;
+use crate::request::RequestItem;
+use crate::utils::normalize_url;
+
+// 模板中可用的占位符
+const PLACEHOLDERS: [&str; 5] = ["index", "method", "host", "path", "name"];
+
+/// 为未设置 tag 的请求生成 tag：模板字符串或 Python 可调用对象
+pub(crate) enum TagGenerator {
+    /// 如 "{method}-{path}-{index}"
+    Template(String),
+    /// 以 (request, index) 调用，返回 str 或 None
+    Callable(PyObject),
+}
+
+impl TagGenerator {
+    pub(crate) fn extract(obj: &PyAny) -> PyResult<Self> {
+        if let Ok(template) = obj.extract::<String>() {
+            check_template(&template)?;
+            return Ok(TagGenerator::Template(template));
+        }
+        if obj.is_callable() {
+            return Ok(TagGenerator::Callable(obj.into()));
+        }
+        Err(PyTypeError::new_err("tag_generator must be a template string or a callable"))
+    }
+
+    /// 只处理 tag 为 None 的请求，已有的 tag 保持不变
+    pub(crate) fn apply(&self, py: Python, requests: &mut [RequestItem], default_scheme: &str) -> PyResult<()> {
+        for (index, req) in requests.iter_mut().enumerate() {
+            if req.tag.is_some() {
+                continue;
+            }
+            req.tag = match self {
+                TagGenerator::Template(template) => Some(render(template, req, index, default_scheme)),
+                TagGenerator::Callable(func) => {
+                    let tag = func.call1(py, (req.clone(), index))?;
+                    tag.extract::<Option<String>>(py).map_err(|_| {
+                        PyTypeError::new_err(format!("tag_generator must return str or None, got {}", tag.as_ref(py).get_type().name().unwrap_or("?")))
+                    })?
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+fn check_template(template: &str) -> PyResult<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return Err(PyValueError::new_err(format!("Invalid tag template '{}': unclosed '{{'", template)));
+        };
+        let name = &rest[start + 1..start + len];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid tag template '{}': unknown placeholder '{{{}}}', expected one of {}",
+                template, name, PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+            )));
+        }
+        rest = &rest[start + len + 1..];
+    }
+    Ok(())
+}
+
+fn render(template: &str, req: &RequestItem, index: usize, default_scheme: &str) -> String {
+    let parsed = normalize_url(&req.url, default_scheme).ok().and_then(|u| Url::parse(&u).ok());
+    let host = parsed.as_ref().and_then(|u| u.host_str()).unwrap_or_default();
+    let path = parsed.as_ref().map(|u| u.path()).unwrap_or_default();
+    // 最后一段非空路径，如 /api/users/42 → 42
+    let name = path.rsplit('/').find(|s| !s.is_empty()).unwrap_or_default();
+    template
+        .replace("{index}", &index.to_string())
+        .replace("{method}", &req.method.as_deref().unwrap_or("GET").to_ascii_uppercase())
+        .replace("{host}", host)
+        .replace("{path}", path)
+        .replace("{name}", name)
+}
@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use crate::request::{GlobalClientConfig, RequestItem};
+
+tokio::task_local! {
+    // 当前请求必须结束的时间，即 fetch_requests 的 total_timeout 到期时
+    static DEADLINE: Instant;
+}
+
+/// 在 fut 执行期间以 deadline 为截止时间
+pub(crate) async fn with_deadline<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    DEADLINE.scope(deadline, fut).await
+}
+
+/// 流式读取的请求不设总时长，用于覆盖客户端的 30 秒超时
+///
+/// reqwest 的请求超时一直持续到响应体读完，长时间的下载和流会被中途切断；这类请求由 read_timeout 限制数据块之间的间隔。
+/// tokio 对溢出的时长按永不到期处理。
+pub(crate) const NO_TOTAL_TIMEOUT: Duration = Duration::MAX;
+
+/// 距离截止时间的剩余时长，不在批次中时为 None
+pub(crate) fn remaining() -> Option<Duration> {
+    DEADLINE.try_with(|deadline| deadline.saturating_duration_since(Instant::now())).ok()
+}
+
+/// 校验超时取值为有限正数
+pub(crate) fn check_positive(name: &str, value: Option<f64>) -> PyResult<()> {
+    match value {
+        Some(v) if !v.is_finite() || v <= 0.0 => {
+            Err(PyValueError::new_err(format!("{} must be a positive number of seconds, got {}", name, v)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 校验单个请求的超时层级：各阶段超时不能超过整个请求的 timeout
+///
+/// 只检查显式指定的值（请求或全局配置），未指定时的默认值不参与比较。
+pub(crate) fn check_request(req: &RequestItem, global: Option<&GlobalClientConfig>) -> PyResult<()> {
+    let connect_timeout = req.connect_timeout.or(global.and_then(|g| g.connect_timeout));
+    let read_timeout = req.read_timeout.or(global.and_then(|g| g.read_timeout));
+    let dns_timeout = req.dns_timeout.or(global.and_then(|g| g.dns_timeout));
+
+    check_positive("timeout", req.timeout)?;
+    check_positive("connect_timeout", connect_timeout)?;
+    check_positive("read_timeout", read_timeout)?;
+    check_positive("dns_timeout", dns_timeout)?;
+
+    let Some(timeout) = req.timeout.or(global.and_then(|g| g.timeout)) else { return Ok(()) };
+    for (name, value) in [("connect_timeout", connect_timeout), ("read_timeout", read_timeout), ("dns_timeout", dns_timeout)] {
+        if let Some(value) = value.filter(|v| *v > timeout) {
+            return Err(PyValueError::new_err(format!(
+                "{} ({}s) exceeds timeout ({}s){}", name, value, timeout, describe(req)
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 校验批量请求：每个请求的 timeout 不能超过 total_timeout
+pub(crate) fn check_batch(requests: &[RequestItem], total_timeout: Option<f64>, global: &GlobalClientConfig) -> PyResult<()> {
+    check_positive("total_timeout", total_timeout)?;
+    for (index, req) in requests.iter().enumerate() {
+        req.validate(Some(global)).map_err(|e| PyValueError::new_err(format!("requests[{}]: {}", index, e)))?;
+        if let (Some(timeout), Some(total)) = (req.timeout, total_timeout) {
+            if timeout > total {
+                return Err(PyValueError::new_err(format!(
+                    "requests[{}]: timeout ({}s) exceeds total_timeout ({}s){}", index, timeout, total, describe(req)
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn describe(req: &RequestItem) -> String {
+    match &req.tag {
+        Some(tag) => format!(" for request '{}'", tag),
+        None => String::new(),
+    }
+}
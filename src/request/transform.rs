@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+/// 按名称注册的响应后处理
+static TRANSFORMS: Lazy<RwLock<HashMap<String, Arc<Transform>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 用 JSONPath（RFC 9535）从 JSON 响应中提取字段
+pub(crate) struct Transform {
+    path: JsonPath,
+    // 只取第一个匹配，否则返回所有匹配组成的数组
+    first: bool,
+}
+
+impl Transform {
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("Response is not valid JSON: {}", e))?;
+        let nodes = self.path.query(&value);
+        let output = if self.first {
+            nodes.first().cloned().unwrap_or(Value::Null)
+        } else {
+            Value::Array(nodes.all().into_iter().cloned().collect())
+        };
+        Ok(output.to_string())
+    }
+}
+
+/// 注册响应后处理，请求通过 transform=name 引用
+///
+/// content 替换为提取结果的 JSON 字符串，在 tokio 阻塞线程池中执行，不占用 GIL。
+#[pyfunction]
+#[pyo3(signature = (name, jsonpath, first=false))]
+pub fn register_transform(name: String, jsonpath: String, first: bool) -> PyResult<()> {
+    let path = JsonPath::parse(&jsonpath)
+        .map_err(|e| PyValueError::new_err(format!("Invalid JSONPath '{}': {}", jsonpath, e)))?;
+    TRANSFORMS.write().unwrap().insert(name, Arc::new(Transform { path, first }));
+    Ok(())
+}
+
+/// 移除已注册的后处理，返回是否存在
+#[pyfunction]
+pub fn unregister_transform(name: String) -> bool {
+    TRANSFORMS.write().unwrap().remove(&name).is_some()
+}
+
+/// 校验 transform 已注册
+pub(crate) fn check_transform(name: &str) -> PyResult<()> {
+    if TRANSFORMS.read().unwrap().contains_key(name) {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!("Unknown transform '{}', register it with register_transform first", name)))
+    }
+}
+
+/// 在阻塞线程池中执行后处理，返回新的 content
+pub(crate) async fn run(name: &str, text: String) -> Result<String, String> {
+    let transform = TRANSFORMS.read().unwrap().get(name).cloned()
+        .ok_or_else(|| format!("Unknown transform '{}'", name))?;
+    tokio::task::spawn_blocking(move || transform.apply(&text)).await
+        .map_err(|e| format!("Transform '{}' failed: {}", name, e))?
+}
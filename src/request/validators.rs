@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Map, Value};
+use crate::utils::{json_to_py, py_to_json};
+
+/// 条件请求模式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Conditional {
+    /// 只保存 ETag / Last-Modified
+    Validators,
+    /// 同时保存响应内容，304 时作为 content 返回
+    Body,
+}
+
+impl Conditional {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "validators" => Ok(Conditional::Validators),
+            "body" => Ok(Conditional::Body),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid conditional: '{}'. Valid values: validators, body",
+                name
+            ))),
+        }
+    }
+}
+
+/// 某个 URL 上次响应的校验值
+#[derive(Clone, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: Option<String>,
+}
+
+static STORE: Lazy<Mutex<HashMap<String, Validators>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 上次响应的 If-None-Match / If-Modified-Since 请求头
+pub(crate) fn conditions(url: &str) -> Vec<(&'static str, String)> {
+    let store = STORE.lock().unwrap();
+    let Some(saved) = store.get(url) else { return Vec::new() };
+    let mut conditions = Vec::new();
+    if let Some(etag) = &saved.etag {
+        conditions.push(("If-None-Match", etag.clone()));
+    }
+    if let Some(modified) = &saved.last_modified {
+        conditions.push(("If-Modified-Since", modified.clone()));
+    }
+    conditions
+}
+
+/// 上次保存的响应内容
+pub(crate) fn previous_body(url: &str) -> Option<String> {
+    STORE.lock().unwrap().get(url).and_then(|saved| saved.body.clone())
+}
+
+/// 保存 2xx 响应的校验值；没有校验值的响应删除旧记录，避免下次发送过期的条件
+pub(crate) fn update(url: &str, headers: &Map<String, Value>, body: Option<String>) {
+    let header = |name: &str| headers.get(name).and_then(Value::as_str).map(str::to_string);
+    let saved = Validators { etag: header("etag"), last_modified: header("last-modified"), body };
+    let mut store = STORE.lock().unwrap();
+    if saved.etag.is_none() && saved.last_modified.is_none() {
+        store.remove(url);
+    } else {
+        store.insert(url.to_string(), saved);
+    }
+}
+
+/// 导出保存的校验值 {url: {"etag", "last_modified", "body"}}，可写入文件供下次运行导入
+#[pyfunction]
+pub fn export_validators(py: Python) -> PyResult<PyObject> {
+    let exported: Map<String, Value> = STORE.lock().unwrap().iter()
+        .map(|(url, saved)| (url.clone(), json!({
+            "etag": saved.etag,
+            "last_modified": saved.last_modified,
+            "body": saved.body,
+        })))
+        .collect();
+    json_to_py(py, &Value::Object(exported))
+}
+
+/// 导入 export_validators 导出的校验值，返回导入的条目数
+#[pyfunction]
+pub fn import_validators(py: Python, validators: &PyDict) -> PyResult<usize> {
+    let Value::Object(entries) = py_to_json(py, validators)? else { return Ok(0) };
+    let mut imported = Vec::with_capacity(entries.len());
+    for (url, entry) in entries {
+        let field = |name: &str| -> PyResult<Option<String>> {
+            match entry.get(name) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::String(s)) => Ok(Some(s.clone())),
+                Some(_) => Err(PyValueError::new_err(format!("validators['{}']['{}'] must be a string or None", url, name))),
+            }
+        };
+        let saved = Validators { etag: field("etag")?, last_modified: field("last_modified")?, body: field("body")? };
+        if saved.etag.is_some() || saved.last_modified.is_some() {
+            imported.push((url, saved));
+        }
+    }
+    let count = imported.len();
+    STORE.lock().unwrap().extend(imported);
+    Ok(count)
+}
+
+/// 清空保存的校验值，返回删除的条目数
+#[pyfunction]
+pub fn clear_validators() -> usize {
+    let mut store = STORE.lock().unwrap();
+    let count = store.len();
+    store.clear();
+    count
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use crate::debug::{write_log, Level};
+
+/// 请求当前所处的阶段
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Phase {
+    /// DNS 解析
+    Resolving,
+    /// 等待响应头，新连接时包含 TCP/TLS 建连
+    AwaitingHeaders,
+    /// 读取响应体
+    ReadingBody,
+}
+
+impl Phase {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Resolving => "resolving",
+            Phase::AwaitingHeaders => "awaiting_headers",
+            Phase::ReadingBody => "reading_body",
+        }
+    }
+}
+
+struct InFlight {
+    method: String,
+    url: String,
+    tag: Option<String>,
+    started: Instant,
+    phase: Phase,
+    /// 已报告过的阶段，每个阶段只报告一次
+    reported: Option<Phase>,
+}
+
+struct WatchdogConfig {
+    threshold: Duration,
+    callback: Option<PyObject>,
+}
+
+static CONFIG: Lazy<RwLock<Option<WatchdogConfig>>> = Lazy::new(|| RwLock::new(None));
+static IN_FLIGHT: Lazy<Mutex<HashMap<u64, InFlight>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    static CURRENT: u64;
+}
+
+fn update_phase(id: u64, phase: Phase) {
+    if let Some(entry) = IN_FLIGHT.lock().unwrap().get_mut(&id) {
+        entry.phase = phase;
+    }
+}
+
+/// 登记中的请求，drop 时移除
+pub(crate) struct Tracked(u64);
+
+impl Tracked {
+    pub(crate) fn set_phase(&self, phase: Phase) {
+        update_phase(self.0, phase);
+    }
+}
+
+/// 在请求的上下文中执行，DNS 解析器据此更新阶段
+pub(crate) async fn scope<F: Future>(tracked: Option<&Tracked>, fut: F) -> F::Output {
+    match tracked {
+        Some(tracked) => CURRENT.scope(tracked.0, fut).await,
+        None => fut.await,
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        IN_FLIGHT.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// 开启看门狗时登记请求
+pub(crate) fn track(method: &str, url: &str, tag: Option<String>) -> Option<Tracked> {
+    CONFIG.read().unwrap().as_ref()?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    IN_FLIGHT.lock().unwrap().insert(id, InFlight {
+        method: method.to_string(),
+        url: url.to_string(),
+        tag,
+        started: Instant::now(),
+        phase: Phase::AwaitingHeaders,
+        reported: None,
+    });
+    Some(Tracked(id))
+}
+
+/// 更新当前任务中请求的阶段，不在看门狗上下文中时忽略
+pub(crate) fn set_current_phase(phase: Phase) {
+    if let Ok(id) = CURRENT.try_with(|id| *id) {
+        update_phase(id, phase);
+    }
+}
+
+/// 设置慢请求看门狗：请求超过 threshold 秒未完成时记录日志或调用 callback
+///
+/// threshold 为 None 时关闭。callback 接收包含 tag、url、method、phase、elapsed 的字典。
+#[pyfunction]
+#[pyo3(signature = (threshold=None, callback=None))]
+pub fn set_watchdog(threshold: Option<f64>, callback: Option<PyObject>) {
+    *CONFIG.write().unwrap() = threshold.map(|t| WatchdogConfig {
+        threshold: Duration::from_secs_f64(t.max(0.0)),
+        callback,
+    });
+    if threshold.is_some() && !RUNNING.swap(true, Ordering::SeqCst) {
+        pyo3_asyncio::tokio::get_runtime().spawn(run());
+    }
+}
+
+async fn run() {
+    loop {
+        let threshold = match CONFIG.read().unwrap().as_ref() {
+            Some(config) => config.threshold,
+            None => break,
+        };
+        tokio::time::sleep((threshold / 4).clamp(Duration::from_millis(50), Duration::from_secs(1))).await;
+        report(threshold);
+    }
+    RUNNING.store(false, Ordering::SeqCst);
+}
+
+fn report(threshold: Duration) {
+    let slow: Vec<(Option<String>, String, String, &'static str, f64)> = {
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+        in_flight.values_mut()
+            .filter(|entry| entry.started.elapsed() >= threshold && entry.reported != Some(entry.phase))
+            .map(|entry| {
+                entry.reported = Some(entry.phase);
+                (entry.tag.clone(), entry.url.clone(), entry.method.clone(), entry.phase.as_str(), entry.started.elapsed().as_secs_f64())
+            })
+            .collect()
+    };
+    if slow.is_empty() {
+        return;
+    }
+
+    let callback = CONFIG.read().unwrap().as_ref()
+        .and_then(|config| config.callback.as_ref().map(|cb| Python::with_gil(|py| cb.clone_ref(py))));
+    match callback {
+        Some(callback) => Python::with_gil(|py| {
+            for (tag, url, method, phase, elapsed) in slow {
+                let info = PyDict::new(py);
+                let _ = info.set_item("tag", tag);
+                let _ = info.set_item("url", url);
+                let _ = info.set_item("method", method);
+                let _ = info.set_item("phase", phase);
+                let _ = info.set_item("elapsed", elapsed);
+                if let Err(e) = callback.call1(py, (info,)) {
+                    e.print(py);
+                }
+            }
+        }),
+        None => {
+            for (tag, url, method, phase, elapsed) in slow {
+                write_log(Level::Warning, &format!(
+                    "[watchdog] [{}] {} {} still {} after {:.2}s",
+                    tag.as_deref().unwrap_or("no-tag"), method, url, phase, elapsed
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{Map, Value};
+
+const SOAP12_NAMESPACE: &str = "http://www.w3.org/2003/05/soap-envelope";
+
+/// 校验 xml 请求体：不能与 params 同时使用
+pub(crate) fn check_xml(xml: Option<&str>, has_params: bool) -> PyResult<()> {
+    if xml.is_some() && has_params {
+        return Err(PyValueError::new_err("xml and params cannot both be set"));
+    }
+    Ok(())
+}
+
+/// xml 请求体的默认 Content-Type：SOAP 1.2 信封为 application/soap+xml，其余为 text/xml
+pub(crate) fn default_content_type(xml: &str) -> &'static str {
+    if xml.contains(SOAP12_NAMESPACE) {
+        "application/soap+xml; charset=utf-8"
+    } else {
+        "text/xml; charset=utf-8"
+    }
+}
+
+/// 一个未闭合的元素
+struct Element {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl Element {
+    fn open(start: &BytesStart) -> Result<Self, String> {
+        let mut fields = Map::new();
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| e.to_string())?;
+            let value = attr.unescape_value().map_err(|e| e.to_string())?;
+            fields.insert(
+                format!("@{}", String::from_utf8_lossy(attr.key.as_ref())),
+                Value::String(value.into_owned()),
+            );
+        }
+        Ok(Element { name: String::from_utf8_lossy(start.name().as_ref()).into_owned(), fields, text: String::new() })
+    }
+
+    /// 只有文本的元素转为字符串，空元素为 null，其余为对象，文本放在 #text
+    fn close(self) -> (String, Value) {
+        let value = match (self.fields.is_empty(), self.text.is_empty()) {
+            (true, true) => Value::Null,
+            (true, false) => Value::String(self.text),
+            (false, _) => {
+                let mut fields = self.fields;
+                if !self.text.is_empty() {
+                    fields.insert("#text".to_string(), Value::String(self.text));
+                }
+                Value::Object(fields)
+            }
+        };
+        (self.name, value)
+    }
+}
+
+/// 把子元素加入父元素，同名的子元素合并为数组
+fn insert_child(fields: &mut Map<String, Value>, name: String, value: Value) {
+    match fields.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+/// 把 XML 文档转为嵌套的 JSON 对象，如 <a x="1"><b>2</b><b>3</b></a> 转为
+/// {"a": {"@x": "1", "b": ["2", "3"]}}；元素名保留命名空间前缀
+pub(crate) fn to_json(text: &str) -> Result<Value, String> {
+    let mut reader = Reader::from_str(text);
+    reader.trim_text(true);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = Map::new();
+
+    loop {
+        let event = reader.read_event()
+            .map_err(|e| format!("Invalid XML at position {}: {}", reader.buffer_position(), e))?;
+        let closed = match event {
+            Event::Start(start) => {
+                stack.push(Element::open(&start)?);
+                None
+            }
+            Event::Empty(start) => Some(Element::open(&start)?.close()),
+            Event::End(_) => stack.pop().map(Element::close),
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| e.to_string())?;
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+                None
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data.into_inner()));
+                }
+                None
+            }
+            Event::Eof => break,
+            _ => None,
+        };
+        if let Some((name, value)) = closed {
+            match stack.last_mut() {
+                Some(parent) => insert_child(&mut parent.fields, name, value),
+                None => insert_child(&mut root, name, value),
+            }
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(format!("Invalid XML: unclosed element <{}>", stack[stack.len() - 1].name));
+    }
+    if root.is_empty() {
+        return Err("Invalid XML: no root element".to_string());
+    }
+    Ok(Value::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_elements_attributes_and_repeats() {
+        let value = to_json(r#"<?xml version="1.0"?><a x="1"><b>2</b><b>3</b><c/><d y="z">t &amp; u</d></a>"#).unwrap();
+        assert_eq!(value, json!({"a": {"@x": "1", "b": ["2", "3"], "c": null, "d": {"@y": "z", "#text": "t & u"}}}));
+    }
+
+    #[test]
+    fn keeps_namespace_prefixes_and_cdata() {
+        let value = to_json(concat!(
+            r#"<soap:Envelope xmlns:soap="http://www.w3.org/2003/05/soap-envelope">"#,
+            "<soap:Body><m:r xmlns:m=\"urn:x\"><![CDATA[<raw>]]></m:r></soap:Body></soap:Envelope>",
+        )).unwrap();
+        assert_eq!(value["soap:Envelope"]["soap:Body"]["m:r"]["#text"], "<raw>");
+        assert_eq!(default_content_type(SOAP12_NAMESPACE), "application/soap+xml; charset=utf-8");
+        assert_eq!(default_content_type("<a/>"), "text/xml; charset=utf-8");
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(to_json("<a><b></a>").is_err());
+        assert_eq!(to_json("<a><b>").unwrap_err(), "Invalid XML: unclosed element <b>");
+        assert_eq!(to_json("  ").unwrap_err(), "Invalid XML: no root element");
+        assert!(check_xml(Some("<a/>"), true).is_err());
+        assert!(check_xml(Some("<a/>"), false).is_ok());
+    }
+}
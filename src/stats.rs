@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use chrono::{Local, SecondsFormat};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use crate::utils::json_to_py;
+
+// 百分位数基于最近的这么多个请求
+const LATENCY_WINDOW: usize = 1024;
+// 不同 tag / 主机的数量上限，超出后归入 "other"
+const MAX_KEYS: usize = 1000;
+const OTHER: &str = "other";
+
+#[derive(Default)]
+struct Counters {
+    sent: u64,
+    succeeded: u64,
+    failed: u64,
+    errors: BTreeMap<String, u64>,
+    bytes_sent: u64,
+    bytes_received: u64,
+    latency_sum: f64,
+    latency_min: Option<f64>,
+    latency_max: f64,
+    recent: VecDeque<f64>,
+}
+
+struct Stats {
+    since: String,
+    total: Counters,
+    by_tag: HashMap<String, Counters>,
+    by_host: HashMap<String, Counters>,
+}
+
+impl Stats {
+    fn new() -> Self {
+        Stats {
+            since: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            total: Counters::default(),
+            by_tag: HashMap::new(),
+            by_host: HashMap::new(),
+        }
+    }
+}
+
+static STATS: Lazy<Mutex<Stats>> = Lazy::new(|| Mutex::new(Stats::new()));
+
+/// 一个请求的结果，包括未发出即失败的
+pub(crate) struct Outcome<'a> {
+    pub tag: Option<&'a str>,
+    pub host: Option<&'a str>,
+    pub error: Option<&'a str>,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+    pub duration: f64,
+}
+
+impl Counters {
+    fn add(&mut self, outcome: &Outcome) {
+        self.sent += 1;
+        match outcome.error {
+            Some(error) => {
+                self.failed += 1;
+                *self.errors.entry(error.to_string()).or_default() += 1;
+            }
+            None => self.succeeded += 1,
+        }
+        self.bytes_sent += outcome.bytes_sent as u64;
+        self.bytes_received += outcome.bytes_received as u64;
+        self.latency_sum += outcome.duration;
+        self.latency_min = Some(self.latency_min.map_or(outcome.duration, |min| min.min(outcome.duration)));
+        self.latency_max = self.latency_max.max(outcome.duration);
+        if self.recent.len() == LATENCY_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(outcome.duration);
+    }
+
+    fn to_json(&self) -> Value {
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * (sorted.len().max(1) - 1) as f64).round() as usize;
+            sorted.get(rank).copied()
+        };
+        json!({
+            "sent": self.sent,
+            "succeeded": self.succeeded,
+            "failed": self.failed,
+            "errors": self.errors,
+            "bytes_sent": self.bytes_sent,
+            "bytes_received": self.bytes_received,
+            "latency": {
+                "mean": (self.sent > 0).then(|| self.latency_sum / self.sent as f64),
+                "min": self.latency_min,
+                "max": (self.sent > 0).then_some(self.latency_max),
+                "p50": percentile(50.0),
+                "p95": percentile(95.0),
+                "p99": percentile(99.0),
+            },
+        })
+    }
+}
+
+fn counters_for<'a>(map: &'a mut HashMap<String, Counters>, key: &str) -> &'a mut Counters {
+    let key = if map.contains_key(key) || map.len() < MAX_KEYS { key } else { OTHER };
+    map.entry(key.to_string()).or_default()
+}
+
+/// 记录一个请求的结果
+pub(crate) fn record(outcome: Outcome) {
+    let mut stats = STATS.lock().unwrap();
+    stats.total.add(&outcome);
+    if let Some(tag) = outcome.tag {
+        counters_for(&mut stats.by_tag, tag).add(&outcome);
+    }
+    if let Some(host) = outcome.host {
+        counters_for(&mut stats.by_host, host).add(&outcome);
+    }
+}
+
+/// 进程内的请求统计：总计以及按 tag、主机分组
+///
+/// latency 的 p50/p95/p99 基于每组最近 1024 个请求，其余为 reset_stats 之后的累计值。
+#[pyfunction]
+pub fn get_stats(py: Python) -> PyResult<PyObject> {
+    let stats = STATS.lock().unwrap();
+    let group = |map: &HashMap<String, Counters>| {
+        map.iter().map(|(k, c)| (k.clone(), c.to_json())).collect::<serde_json::Map<_, _>>()
+    };
+    let value = json!({
+        "since": stats.since,
+        "total": stats.total.to_json(),
+        "by_tag": group(&stats.by_tag),
+        "by_host": group(&stats.by_host),
+    });
+    json_to_py(py, &value)
+}
+
+/// 清空统计，从当前时间重新开始
+#[pyfunction]
+pub fn reset_stats() {
+    *STATS.lock().unwrap() = Stats::new();
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use opentelemetry::trace::{
+    Span as _, SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceFlags, TraceId, TraceState, Tracer as _,
+    TracerProvider as _,
+};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{self as sdktrace, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::Value;
+use url::Url;
+use crate::request::timeouts::check_positive;
+
+const DEFAULT_SERVICE_NAME: &str = "rusty-req";
+
+struct Tracing {
+    provider: TracerProvider,
+    tracer: sdktrace::Tracer,
+    propagate: bool,
+}
+
+static TRACING: Lazy<RwLock<Option<Tracing>>> = Lazy::new(|| RwLock::new(None));
+
+/// 配置 OpenTelemetry 链路追踪，每个请求导出一个 CLIENT span
+///
+/// endpoint 为 OTLP/HTTP 收集器地址（如 http://localhost:4318，自动追加 /v1/traces），None 时关闭。
+/// 重新配置或关闭时先导出已结束的 span。
+#[pyfunction]
+#[pyo3(signature = (endpoint=None, service_name=None, headers=None, timeout=None, propagate=None))]
+pub fn configure_tracing<'py>(
+    py: Python<'py>,
+    endpoint: Option<String>,
+    service_name: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    timeout: Option<f64>,
+    propagate: Option<bool>,
+) -> PyResult<&'py PyAny> {
+    if let Some(endpoint) = &endpoint {
+        let parsed = Url::parse(endpoint).map_err(|e| PyValueError::new_err(format!("Invalid endpoint '{}': {}", endpoint, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(PyValueError::new_err(format!("Invalid endpoint '{}': expected an http or https URL", endpoint)));
+        }
+    }
+    check_positive("timeout", timeout)?;
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let tracing = match endpoint {
+            Some(endpoint) => {
+                let mut exporter = opentelemetry_otlp::new_exporter().http()
+                    .with_endpoint(endpoint.trim_end_matches('/'))
+                    .with_headers(headers.unwrap_or_default());
+                if let Some(timeout) = timeout {
+                    exporter = exporter.with_timeout(Duration::from_secs_f64(timeout));
+                }
+                let exporter = exporter.build_span_exporter()
+                    .map_err(|e| PyValueError::new_err(format!("Failed to create OTLP exporter: {}", e)))?;
+                let service_name = service_name.unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+                let provider = TracerProvider::builder()
+                    .with_batch_exporter(exporter, runtime::Tokio)
+                    .with_config(sdktrace::config().with_resource(Resource::new([KeyValue::new("service.name", service_name)])))
+                    .build();
+                let tracer = provider.versioned_tracer("rusty-req", Some(env!("CARGO_PKG_VERSION")), None::<&str>, None);
+                Some(Tracing { provider, tracer, propagate: propagate.unwrap_or(true) })
+            }
+            None => None,
+        };
+        let previous = std::mem::replace(&mut *TRACING.write().unwrap(), tracing);
+        // 导出剩余的 span 会阻塞，放到阻塞线程池
+        if let Some(previous) = previous {
+            let _ = tokio::task::spawn_blocking(move || {
+                previous.provider.force_flush();
+            }).await;
+        }
+        Ok(())
+    })
+}
+
+/// 解析 W3C traceparent 请求头：00-<trace-id>-<parent-id>-<flags>
+fn parse_traceparent(value: &str) -> Option<SpanContext> {
+    let parts: Vec<&str> = value.trim().split('-').collect();
+    let [version, trace_id, span_id, flags] = parts.as_slice() else { return None };
+    if *version == "ff" || trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+        return None;
+    }
+    let trace_id = TraceId::from_hex(trace_id).ok().filter(|id| *id != TraceId::INVALID)?;
+    let span_id = SpanId::from_hex(span_id).ok().filter(|id| *id != SpanId::INVALID)?;
+    let flags = TraceFlags::new(u8::from_str_radix(flags, 16).ok()?);
+    Some(SpanContext::new(trace_id, span_id, flags, true, TraceState::default()))
+}
+
+/// 一个请求的 span，请求结束时调用 finish；中途返回时随 drop 结束
+pub(crate) struct RequestSpan {
+    span: sdktrace::Span,
+    traceparent: Option<String>,
+}
+
+/// 开始请求的 span；请求头中的 traceparent 作为父 span，未配置追踪时返回 None
+pub(crate) fn start(method: &str, url: &str, headers: &[(String, String)]) -> Option<RequestSpan> {
+    let guard = TRACING.read().unwrap();
+    let tracing = guard.as_ref()?;
+    let parent = headers.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("traceparent"))
+        .and_then(|(_, v)| parse_traceparent(v));
+    let cx = match parent {
+        Some(parent) => Context::new().with_remote_span_context(parent),
+        None => Context::new(),
+    };
+
+    let mut attributes = vec![KeyValue::new("http.request.method", method.to_string())];
+    if let Ok(mut parsed) = Url::parse(url) {
+        // 不导出 URL 中的用户名和密码
+        let _ = parsed.set_username("");
+        let _ = parsed.set_password(None);
+        if let Some(host) = parsed.host_str() {
+            attributes.push(KeyValue::new("server.address", host.to_string()));
+        }
+        if let Some(port) = parsed.port_or_known_default() {
+            attributes.push(KeyValue::new("server.port", port as i64));
+        }
+        attributes.push(KeyValue::new("url.full", parsed.to_string()));
+    }
+    let span = tracing.tracer.span_builder(method.to_string())
+        .with_kind(SpanKind::Client)
+        .with_attributes(attributes)
+        .start_with_context(&tracing.tracer, &cx);
+    let traceparent = tracing.propagate.then(|| {
+        let sc = span.span_context();
+        format!("00-{}-{}-{:02x}", sc.trace_id(), sc.span_id(), sc.trace_flags().to_u8())
+    });
+    Some(RequestSpan { span, traceparent })
+}
+
+impl RequestSpan {
+    /// 传给下游的 traceparent，propagate=False 时为 None
+    pub(crate) fn traceparent(&self) -> Option<&str> {
+        self.traceparent.as_deref()
+    }
+
+    /// 记录状态码、异常和分阶段耗时后结束 span
+    pub(crate) fn finish(mut self, result: &HashMap<String, String>) {
+        let status = result.get("http_status").and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
+        if status > 0 {
+            self.span.set_attribute(KeyValue::new("http.response.status_code", status));
+        }
+        let meta = result.get("meta").and_then(|m| serde_json::from_str::<Value>(m).ok()).unwrap_or(Value::Null);
+        if let Some(tag) = meta.get("tag").and_then(|t| t.as_str()) {
+            self.span.set_attribute(KeyValue::new("rusty_req.tag", tag.to_string()));
+        }
+        for key in ["process_time", "dns_time", "ttfb"] {
+            if let Some(seconds) = meta.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()) {
+                self.span.set_attribute(KeyValue::new(format!("rusty_req.{}", key), seconds));
+            }
+        }
+        let exception = result.get("exception").and_then(|e| serde_json::from_str::<Value>(e).ok()).unwrap_or(Value::Null);
+        if let Some(exc_type) = exception.get("type").and_then(|t| t.as_str()) {
+            self.span.set_attribute(KeyValue::new("error.type", exc_type.to_string()));
+            let message = exception.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+            self.span.set_status(Status::error(message.to_string()));
+        }
+        self.span.end();
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use crate::debug::{DEFAULT_DENY_HEADERS, REDACTED};
+use crate::request::PreparedRequest;
+use crate::utils::sha256_hex;
+
+const MATCH_FIELDS: [&str; 3] = ["method", "url", "body"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// 有匹配的记录时回放，否则发送并记录
+    Auto,
+    /// 总是发送，清空文件后重新记录
+    Record,
+    /// 只回放，没有匹配的记录时返回 CassetteMiss
+    Replay,
+}
+
+impl Mode {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Ok(Mode::Auto),
+            "record" => Ok(Mode::Record),
+            "replay" => Ok(Mode::Replay),
+            _ => Err(PyValueError::new_err(format!("Invalid cassette mode: '{}'. Valid values: auto, record, replay", name))),
+        }
+    }
+}
+
+/// 匹配用的键，未参与匹配的字段为 None
+type Key = (Option<String>, Option<String>, Option<String>);
+
+struct Cassette {
+    path: String,
+    mode: Mode,
+    match_on: Vec<String>,
+    // 相同的请求按记录顺序回放，用完后重复最后一个
+    interactions: HashMap<Key, (Vec<HashMap<String, String>>, usize)>,
+    file: Option<File>,
+}
+
+static CASSETTE: Lazy<Mutex<Option<Cassette>>> = Lazy::new(|| Mutex::new(None));
+
+impl Cassette {
+    fn key(&self, method: &str, url: &str, body_sha256: Option<&str>) -> Key {
+        let matched = |field: &str| self.match_on.iter().any(|f| f == field);
+        (
+            matched("method").then(|| method.to_uppercase()),
+            matched("url").then(|| url.to_string()),
+            matched("body").then(|| body_sha256.unwrap_or_default().to_string()),
+        )
+    }
+
+    fn load(&mut self) -> PyResult<()> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(PyIOError::new_err(format!("Failed to open cassette '{}': {}", self.path, e))),
+        };
+        for (n, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| PyIOError::new_err(format!("Failed to read cassette '{}': {}", self.path, e)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = || PyValueError::new_err(format!("Invalid cassette '{}' at line {}", self.path, n + 1));
+            let entry: Value = serde_json::from_str(&line).map_err(|_| invalid())?;
+            let key = self.key(
+                entry["method"].as_str().ok_or_else(invalid)?,
+                entry["url"].as_str().ok_or_else(invalid)?,
+                entry["body_sha256"].as_str(),
+            );
+            let result = entry["result"].as_object().ok_or_else(invalid)?.iter()
+                .map(|(k, v)| (k.clone(), v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())))
+                .collect();
+            self.interactions.entry(key).or_default().0.push(result);
+        }
+        Ok(())
+    }
+}
+
+/// 录制或回放请求结果，用于不访问真实服务的测试；path 为 None 时停止
+///
+/// 记录按 JSON lines 追加写入 path，每行一个请求的 method、url、请求体哈希和结果；
+/// meta.request 中的 Authorization、Cookie 等请求头写入前隐藏。
+#[pyfunction]
+#[pyo3(signature = (path=None, mode=None, match_on=None))]
+pub fn use_cassette(path: Option<String>, mode: Option<String>, match_on: Option<Vec<String>>) -> PyResult<()> {
+    let Some(path) = path else {
+        *CASSETTE.lock().unwrap() = None;
+        return Ok(());
+    };
+    let mode = Mode::parse(mode.as_deref().unwrap_or("auto"))?;
+    let match_on = match_on.unwrap_or_else(|| MATCH_FIELDS.iter().map(|f| f.to_string()).collect());
+    if let Some(field) = match_on.iter().find(|f| !MATCH_FIELDS.contains(&f.as_str())) {
+        return Err(PyValueError::new_err(format!("Invalid match_on field: '{}'. Valid values: method, url, body", field)));
+    }
+    let mut cassette = Cassette { path, mode, match_on, interactions: HashMap::new(), file: None };
+    if mode != Mode::Record {
+        cassette.load()?;
+    }
+    if mode != Mode::Replay {
+        let file = OpenOptions::new().create(true).write(true)
+            .append(mode == Mode::Auto)
+            .truncate(mode == Mode::Record)
+            .open(&cassette.path)
+            .map_err(|e| PyIOError::new_err(format!("Failed to open cassette '{}': {}", cassette.path, e)))?;
+        cassette.file = Some(file);
+    }
+    *CASSETTE.lock().unwrap() = Some(cassette);
+    Ok(())
+}
+
+/// 把录制中的磁带文件写入磁盘
+pub(crate) fn flush() {
+    if let Some(file) = CASSETTE.lock().unwrap().as_mut().and_then(|c| c.file.as_mut()) {
+        let _ = file.flush().and_then(|_| file.sync_data());
+    }
+}
+
+/// 请求在磁带中的处理方式
+pub(crate) enum Playback {
+    /// 未启用磁带
+    Off,
+    /// 回放记录的结果
+    Hit(HashMap<String, String>),
+    /// replay 模式下没有匹配的记录
+    Miss(String),
+    /// 发送请求，结束后调用 record
+    Record(Pending),
+}
+
+/// 等待记录的请求
+pub(crate) struct Pending {
+    method: String,
+    url: String,
+    body_sha256: Option<String>,
+}
+
+/// 查找请求对应的记录
+pub(crate) fn lookup(prepared: &PreparedRequest) -> Playback {
+    let mut guard = CASSETTE.lock().unwrap();
+    let Some(cassette) = guard.as_mut() else { return Playback::Off };
+    let body_sha256 = prepared.body.as_ref().map(|b| sha256_hex(b));
+    if cassette.mode != Mode::Record {
+        let key = cassette.key(&prepared.method, &prepared.url, body_sha256.as_deref());
+        if let Some((results, next)) = cassette.interactions.get_mut(&key) {
+            let mut result = results[(*next).min(results.len() - 1)].clone();
+            *next += 1;
+            if let Some(Value::Object(mut meta)) = result.get("meta").and_then(|m| serde_json::from_str(m).ok()) {
+                meta.insert("replayed".to_string(), Value::Bool(true));
+                result.insert("meta".to_string(), Value::Object(meta).to_string());
+            }
+            return Playback::Hit(result);
+        }
+        if cassette.mode == Mode::Replay {
+            return Playback::Miss(format!("No recorded response for {} {} in cassette '{}'", prepared.method, prepared.url, cassette.path));
+        }
+    }
+    Playback::Record(Pending { method: prepared.method.clone(), url: prepared.url.clone(), body_sha256 })
+}
+
+/// 把请求结果追加到磁带；写入失败时忽略，不影响请求结果
+pub(crate) fn record(pending: Pending, result: &HashMap<String, String>) {
+    let mut guard = CASSETTE.lock().unwrap();
+    let Some(cassette) = guard.as_mut() else { return };
+    // meta 和 exception 以 JSON 对象写入，便于阅读和手工修改
+    let mut stored = serde_json::Map::new();
+    for (key, value) in result {
+        let value = match key.as_str() {
+            "meta" | "exception" => serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.clone())),
+            _ => Value::String(value.clone()),
+        };
+        stored.insert(key.clone(), value);
+    }
+    if let Some(Value::Object(headers)) = stored.get_mut("meta").and_then(|m| m.pointer_mut("/request/headers")) {
+        for (name, value) in headers.iter_mut() {
+            if DEFAULT_DENY_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                *value = Value::from(REDACTED);
+            }
+        }
+    }
+    let entry = json!({
+        "method": pending.method,
+        "url": pending.url,
+        "body_sha256": pending.body_sha256,
+        "result": stored,
+    });
+    if let Some(file) = cassette.file.as_mut() {
+        let _ = writeln!(file, "{}", entry).and_then(|_| file.flush());
+    }
+    // auto 模式下后续相同的请求直接回放
+    if cassette.mode == Mode::Auto {
+        let key = cassette.key(&pending.method, &pending.url, pending.body_sha256.as_deref());
+        cassette.interactions.entry(key).or_default().0.push(result.clone());
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use pyo3::pyfunction;
+use tokio::sync::watch;
+
+static VIRTUAL: AtomicBool = AtomicBool::new(false);
+// 虚拟时钟的当前时间，从 0 开始
+static NOW: Lazy<watch::Sender<Duration>> = Lazy::new(|| watch::channel(Duration::ZERO).0);
+
+/// 等待指定时长；启用虚拟时钟时只有 advance_clock 推进时间后才会返回
+pub(crate) async fn sleep(duration: Duration) {
+    if !VIRTUAL.load(Ordering::SeqCst) {
+        tokio::time::sleep(duration).await;
+        return;
+    }
+    let target = *NOW.borrow() + duration;
+    let mut rx = NOW.subscribe();
+    let _ = rx.wait_for(|now| *now >= target || !VIRTUAL.load(Ordering::SeqCst)).await;
+}
+
+/// 启用或关闭虚拟时钟，只影响注入的延迟；关闭时唤醒所有等待中的请求
+#[pyfunction]
+pub fn use_virtual_clock(enabled: bool) {
+    VIRTUAL.store(enabled, Ordering::SeqCst);
+    // 重置时间，同时通知等待中的请求重新检查
+    NOW.send_modify(|now| *now = Duration::ZERO);
+}
+
+/// 推进虚拟时钟，到期的注入延迟随之结束
+#[pyfunction]
+pub fn advance_clock(seconds: f64) {
+    let step = Duration::from_secs_f64(seconds.max(0.0));
+    NOW.send_modify(|now| *now = now.saturating_add(step));
+}
@@ -0,0 +1,107 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::testing::clock;
+
+/// 注入的故障
+pub(crate) enum Injected {
+    /// 注入延迟超过请求超时
+    Timeout(Duration),
+    /// 按错误率注入的失败，(异常类型, 消息)
+    Error(String, String),
+}
+
+struct Fault {
+    pattern: String,
+    latency: Option<Duration>,
+    error_rate: f64,
+    error_type: String,
+    // xorshift 随机数状态，相同 seed 得到相同的失败序列
+    state: u64,
+}
+
+impl Fault {
+    fn next_random(&mut self) -> f64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+static FAULTS: Lazy<Mutex<Vec<Fault>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 简单的通配符匹配，* 匹配任意字符序列
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// 为匹配 URL 的请求注入延迟和失败，按注册顺序使用第一个匹配的规则
+pub(crate) async fn apply(url: &str, timeout: Duration) -> Option<Injected> {
+    let (latency, failure) = {
+        let mut faults = FAULTS.lock().unwrap();
+        let fault = faults.iter_mut().find(|f| glob_match(&f.pattern, url))?;
+        let failed = fault.error_rate > 0.0 && fault.next_random() < fault.error_rate;
+        let failure = failed.then(|| (fault.error_type.clone(), format!("Injected failure for {}", url)));
+        (fault.latency, failure)
+    };
+
+    if let Some(latency) = latency {
+        if latency >= timeout {
+            clock::sleep(timeout).await;
+            return Some(Injected::Timeout(timeout));
+        }
+        clock::sleep(latency).await;
+    }
+    failure.map(|(exc_type, message)| Injected::Error(exc_type, message))
+}
+
+/// 为匹配 pattern（支持 * 通配符）的请求注入延迟和失败，用于测试超时和重试逻辑
+#[pyfunction]
+#[pyo3(signature = (pattern, latency=None, error_rate=None, error_type=None, seed=None))]
+pub fn inject_fault(
+    pattern: String,
+    latency: Option<f64>,
+    error_rate: Option<f64>,
+    error_type: Option<String>,
+    seed: Option<u64>,
+) -> PyResult<()> {
+    let error_rate = error_rate.unwrap_or(0.0);
+    if !(0.0..=1.0).contains(&error_rate) {
+        return Err(PyValueError::new_err("error_rate must be between 0 and 1"));
+    }
+    FAULTS.lock().unwrap().push(Fault {
+        pattern,
+        latency: latency.map(|l| Duration::from_secs_f64(l.max(0.0))),
+        error_rate,
+        error_type: error_type.unwrap_or_else(|| "InjectedError".to_string()),
+        // xorshift 的状态不能为 0
+        state: seed.unwrap_or(0x2545_F491_4F6C_DD1D).max(1),
+    });
+    Ok(())
+}
+
+/// 移除所有注入的故障
+#[pyfunction]
+pub fn clear_faults() {
+    FAULTS.lock().unwrap().clear();
+}
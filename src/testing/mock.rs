@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use reqwest::ResponseBuilderExt;
+use reqwest::StatusCode;
+use url::Url;
+use crate::request::PreparedRequest;
+use crate::request::default_headers::parse_headers;
+use crate::testing::faults::glob_match;
+use crate::utils::py_to_json;
+
+/// 一条模拟规则
+struct Route {
+    method: Option<String>,
+    pattern: String,
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    error: Option<String>,
+    /// 剩余可用次数，None 为不限
+    remaining: Option<usize>,
+}
+
+#[derive(Default)]
+struct Routes {
+    routes: Vec<Route>,
+    calls: Vec<PreparedRequest>,
+    passthrough: bool,
+}
+
+/// 模拟传输：请求按注册的规则返回预设的响应，不访问网络
+///
+/// 通过 use_mock_transport 启用；Python 中持有的对象与生效的是同一份规则。
+#[pyclass]
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    inner: Arc<Mutex<Routes>>,
+}
+
+#[pymethods]
+impl MockTransport {
+    #[new]
+    #[pyo3(signature = (passthrough=false))]
+    fn new(passthrough: bool) -> Self {
+        let transport = MockTransport::default();
+        transport.inner.lock().unwrap().passthrough = passthrough;
+        transport
+    }
+
+    /// 注册规则，按注册顺序使用第一个匹配且未用完的规则
+    #[pyo3(signature = (url, method=None, status=200, body=None, json=None, headers=None, error=None, times=None))]
+    fn add(
+        &self,
+        py: Python,
+        url: String,
+        method: Option<String>,
+        status: u16,
+        body: Option<&PyAny>,
+        json: Option<&PyAny>,
+        headers: Option<HashMap<String, String>>,
+        error: Option<String>,
+        times: Option<usize>,
+    ) -> PyResult<()> {
+        let status = StatusCode::from_u16(status)
+            .map_err(|_| PyValueError::new_err(format!("Invalid status code: {}", status)))?;
+        let mut headers = parse_headers(headers.unwrap_or_default())?;
+        let body = response_body(py, body, json, &mut headers)?;
+        let route = Route {
+            method: method.map(|m| m.to_uppercase()),
+            pattern: url,
+            status,
+            headers,
+            body,
+            error,
+            remaining: times,
+        };
+        self.inner.lock().unwrap().routes.push(route);
+        Ok(())
+    }
+
+    /// 已处理的请求，按发送顺序排列
+    #[getter]
+    fn calls(&self) -> Vec<PreparedRequest> {
+        self.inner.lock().unwrap().calls.clone()
+    }
+
+    /// 清除规则和请求记录
+    fn reset(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.routes.clear();
+        inner.calls.clear();
+    }
+}
+
+/// 预设响应的响应体：body 为 str 或 bytes，json 序列化后补全 Content-Type
+pub(crate) fn response_body(
+    py: Python,
+    body: Option<&PyAny>,
+    json: Option<&PyAny>,
+    headers: &mut Vec<(String, String)>,
+) -> PyResult<Vec<u8>> {
+    match (body, json) {
+        (Some(_), Some(_)) => Err(PyValueError::new_err("body and json cannot both be set")),
+        (Some(body), None) => {
+            if let Ok(bytes) = body.downcast::<PyBytes>() {
+                Ok(bytes.as_bytes().to_vec())
+            } else if let Ok(text) = body.downcast::<PyString>() {
+                Ok(text.to_str()?.as_bytes().to_vec())
+            } else {
+                Err(PyValueError::new_err("body must be str or bytes"))
+            }
+        }
+        (None, Some(json)) => {
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type")) {
+                headers.push(("content-type".to_string(), "application/json".to_string()));
+            }
+            Ok(serde_json::to_vec(&py_to_json(py, json)?).unwrap_or_default())
+        }
+        (None, None) => Ok(Vec::new()),
+    }
+}
+
+static ACTIVE: Lazy<RwLock<Option<MockTransport>>> = Lazy::new(|| RwLock::new(None));
+
+/// 启用模拟传输，所有请求按其规则返回；None 恢复使用网络
+#[pyfunction]
+#[pyo3(signature = (transport=None))]
+pub fn use_mock_transport(transport: Option<MockTransport>) {
+    *ACTIVE.write().unwrap() = transport;
+}
+
+/// 模拟传输给出的结果
+pub(crate) enum Mocked {
+    Response(reqwest::Response),
+    /// (异常类型, 消息)
+    Error(String, String),
+}
+
+/// 启用模拟传输时返回请求的模拟结果；passthrough 且没有匹配的规则时返回 None
+pub(crate) fn respond(prepared: &PreparedRequest) -> Option<Mocked> {
+    let transport = ACTIVE.read().unwrap().clone()?;
+    let mut inner = transport.inner.lock().unwrap();
+    inner.calls.push(prepared.clone());
+    let route = inner.routes.iter_mut().find(|r| {
+        r.remaining != Some(0)
+            && r.method.as_ref().map_or(true, |m| *m == prepared.method)
+            && glob_match(&r.pattern, &prepared.url)
+    });
+    let Some(route) = route else {
+        if inner.passthrough {
+            return None;
+        }
+        return Some(Mocked::Error(
+            "NoMockMatch".to_string(),
+            format!("No mock response for {} {}", prepared.method, prepared.url),
+        ));
+    };
+    if let Some(remaining) = route.remaining.as_mut() {
+        *remaining -= 1;
+    }
+    if let Some(exc_type) = &route.error {
+        return Some(Mocked::Error(exc_type.clone(), format!("Mocked failure for {}", prepared.url)));
+    }
+    let mut builder = http::Response::builder().status(route.status);
+    if let Ok(url) = Url::parse(&prepared.url) {
+        builder = builder.url(url);
+    }
+    for (name, value) in &route.headers {
+        builder = builder.header(name, value);
+    }
+    match builder.body(route.body.clone()) {
+        Ok(response) => Some(Mocked::Response(response.into())),
+        Err(e) => Some(Mocked::Error("MockError".to_string(), format!("Invalid mock response: {}", e))),
+    }
+}
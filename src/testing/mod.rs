@@ -0,0 +1,14 @@
+// testing/mod.rs
+// 测试辅助：故障注入、虚拟时钟、录制回放、模拟传输和本地测试服务
+
+pub mod clock;
+pub mod faults;
+pub mod cassette;
+pub mod mock;
+pub mod server;
+
+pub use clock::{use_virtual_clock, advance_clock};
+pub use faults::{inject_fault, clear_faults};
+pub use cassette::use_cassette;
+pub use mock::{use_mock_transport, MockTransport};
+pub use server::TestServer;
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, StatusCode};
+use pyo3::exceptions::{PyOSError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use tokio::sync::oneshot;
+use crate::request::default_headers::parse_headers;
+use crate::testing::clock;
+use crate::testing::faults::glob_match;
+use crate::testing::mock::response_body;
+
+/// 一条路由规则
+struct Route {
+    method: Option<String>,
+    pattern: String,
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Option<Duration>,
+    /// 剩余可用次数，None 为不限
+    remaining: Option<usize>,
+}
+
+/// 收到的请求
+struct Received {
+    method: String,
+    path: String,
+    query: Option<String>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    received: Vec<Received>,
+    in_flight: usize,
+    max_in_flight: usize,
+}
+
+/// 测试用的本地 HTTP 服务，按注册的路由返回预设的状态码、响应体和延迟
+///
+/// 在后台线程池中运行，close 或对象被回收时停止。
+#[pyclass]
+pub struct TestServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+#[pymethods]
+impl TestServer {
+    // 避免 pytest 把它当作测试类收集
+    #[classattr]
+    #[allow(non_upper_case_globals)]
+    const __test__: bool = false;
+
+    #[new]
+    #[pyo3(signature = (host="127.0.0.1", port=0))]
+    fn new(host: &str, port: u16) -> PyResult<Self> {
+        let listener = TcpListener::bind((host, port))
+            .map_err(|e| PyOSError::new_err(format!("Failed to bind {}:{}: {}", host, port, e)))?;
+        listener.set_nonblocking(true).map_err(|e| PyOSError::new_err(e.to_string()))?;
+        let addr = listener.local_addr().map_err(|e| PyOSError::new_err(e.to_string()))?;
+
+        let state = Arc::new(Mutex::new(State::default()));
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let runtime = pyo3_asyncio::tokio::get_runtime();
+        let _guard = runtime.enter();
+        let service_state = state.clone();
+        let server = hyper::Server::from_tcp(listener)
+            .map_err(|e| PyOSError::new_err(format!("Failed to start test server: {}", e)))?
+            .serve(make_service_fn(move |_| {
+                let state = service_state.clone();
+                async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+            }))
+            .with_graceful_shutdown(async { let _ = stopped.await; });
+        runtime.spawn(server);
+        Ok(TestServer { addr, state, shutdown: Some(shutdown) })
+    }
+
+    /// 服务地址，如 http://127.0.0.1:54321
+    #[getter]
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    #[getter]
+    fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// 注册路由，按注册顺序使用第一个匹配且未用完的路由；path 支持 * 通配符
+    #[pyo3(signature = (path, method=None, status=200, body=None, json=None, headers=None, delay=None, times=None))]
+    fn route(
+        &self,
+        py: Python,
+        path: String,
+        method: Option<String>,
+        status: u16,
+        body: Option<&PyAny>,
+        json: Option<&PyAny>,
+        headers: Option<HashMap<String, String>>,
+        delay: Option<f64>,
+        times: Option<usize>,
+    ) -> PyResult<()> {
+        let status = StatusCode::from_u16(status)
+            .map_err(|_| PyValueError::new_err(format!("Invalid status code: {}", status)))?;
+        if delay.is_some_and(|d| !d.is_finite() || d < 0.0) {
+            return Err(PyValueError::new_err("delay must be a non-negative number of seconds"));
+        }
+        let mut headers = parse_headers(headers.unwrap_or_default())?;
+        let body = response_body(py, body, json, &mut headers)?;
+        let route = Route {
+            method: method.map(|m| m.to_uppercase()),
+            pattern: path,
+            status,
+            headers,
+            body,
+            delay: delay.map(Duration::from_secs_f64),
+            remaining: times,
+        };
+        self.state.lock().unwrap().routes.push(route);
+        Ok(())
+    }
+
+    /// 收到的请求：method、path、query、headers、body
+    #[getter]
+    fn requests(&self, py: Python) -> PyResult<Vec<PyObject>> {
+        let state = self.state.lock().unwrap();
+        state.received.iter().map(|r| {
+            let dict = PyDict::new(py);
+            dict.set_item("method", &r.method)?;
+            dict.set_item("path", &r.path)?;
+            dict.set_item("query", &r.query)?;
+            let headers = PyDict::new(py);
+            for (name, value) in &r.headers {
+                headers.set_item(name, value)?;
+            }
+            dict.set_item("headers", headers)?;
+            dict.set_item("body", PyBytes::new(py, &r.body))?;
+            Ok(dict.into_py(py))
+        }).collect()
+    }
+
+    /// 同时处理中的请求数的最大值，用于检查并发限制
+    #[getter]
+    fn max_in_flight(&self) -> usize {
+        self.state.lock().unwrap().max_in_flight
+    }
+
+    /// 清除路由和请求记录
+    fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.routes.clear();
+        state.received.clear();
+        state.max_in_flight = state.in_flight;
+    }
+
+    /// 停止服务，等待中的请求处理完后关闭连接
+    fn close(&mut self) -> PyResult<()> {
+        match self.shutdown.take() {
+            Some(shutdown) => {
+                let _ = shutdown.send(());
+                Ok(())
+            }
+            None => Err(PyRuntimeError::new_err("TestServer is already closed")),
+        }
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(&mut self, _exc_type: PyObject, _exc: PyObject, _tb: PyObject) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// 同时处理中的请求，drop 时计数减一
+struct InFlight(Arc<Mutex<State>>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        state.in_flight = state.in_flight.saturating_sub(1);
+    }
+}
+
+async fn handle(state: Arc<Mutex<State>>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let _in_flight = {
+        let mut guard = state.lock().unwrap();
+        guard.in_flight += 1;
+        guard.max_in_flight = guard.max_in_flight.max(guard.in_flight);
+        InFlight(state.clone())
+    };
+    let (parts, body) = req.into_parts();
+    let body = hyper::body::to_bytes(body).await.map(|b| b.to_vec()).unwrap_or_default();
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+
+    let matched = {
+        let mut guard = state.lock().unwrap();
+        guard.received.push(Received {
+            method: method.clone(),
+            path: path.clone(),
+            query: parts.uri.query().map(|q| q.to_string()),
+            headers: parts.headers.iter()
+                .map(|(k, v)| (k.to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+                .collect(),
+            body,
+        });
+        guard.routes.iter_mut()
+            .find(|r| r.remaining != Some(0) && r.method.as_ref().map_or(true, |m| *m == method) && glob_match(&r.pattern, &path))
+            .map(|route| {
+                if let Some(remaining) = route.remaining.as_mut() {
+                    *remaining -= 1;
+                }
+                (route.status, route.headers.clone(), route.body.clone(), route.delay)
+            })
+    };
+
+    let Some((status, headers, body, delay)) = matched else {
+        let mut response = Response::new(Body::from(format!("No route for {} {}", method, path)));
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return Ok(response);
+    };
+    // 延迟使用测试时钟，启用虚拟时钟后由 advance_clock 推进
+    if let Some(delay) = delay {
+        clock::sleep(delay).await;
+    }
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            hyper::header::HeaderName::from_bytes(name.as_bytes()),
+            hyper::header::HeaderValue::from_str(&value),
+        ) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    Ok(response)
+}
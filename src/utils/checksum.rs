@@ -0,0 +1,67 @@
+use md5::Md5;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use sha2::{Digest, Sha256};
+
+/// 期望的摘要，格式为 "<算法>:<十六进制>"，如 "sha256:9f86d0..."
+#[derive(Clone, Debug)]
+pub struct Checksum {
+    pub algorithm: &'static str,
+    pub expected: String,
+}
+
+impl Checksum {
+    pub fn parse(spec: &str) -> PyResult<Self> {
+        let (algorithm, hex) = spec.split_once(':')
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid checksum '{}': expected '<algorithm>:<hex digest>'", spec)))?;
+        let (algorithm, len) = match algorithm.trim().to_ascii_lowercase().as_str() {
+            "md5" => ("md5", 32),
+            "sha256" => ("sha256", 64),
+            other => return Err(PyValueError::new_err(format!("Unsupported checksum algorithm '{}', expected md5 or sha256", other))),
+        };
+        let expected = hex.trim().to_ascii_lowercase();
+        if expected.len() != len || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(PyValueError::new_err(format!("Invalid {} digest '{}'", algorithm, hex)));
+        }
+        Ok(Checksum { algorithm, expected })
+    }
+
+    pub fn hasher(&self) -> Hasher {
+        match self.algorithm {
+            "md5" => Hasher::Md5(Md5::new()),
+            _ => Hasher::Sha256(Sha256::new()),
+        }
+    }
+}
+
+/// 边读边计算的摘要
+#[derive(Clone)]
+pub enum Hasher {
+    Md5(Md5),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    /// 丢弃已计算的部分，从头开始
+    pub fn reset(&mut self) {
+        match self {
+            Hasher::Md5(h) => Digest::reset(h),
+            Hasher::Sha256(h) => Digest::reset(h),
+        }
+    }
+
+    pub fn hex(self) -> String {
+        let digest = match self {
+            Hasher::Md5(h) => h.finalize().to_vec(),
+            Hasher::Sha256(h) => h.finalize().to_vec(),
+        };
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
@@ -0,0 +1,186 @@
+use std::io::{self, Read, Write};
+use base64::Engine;
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+
+/// 以压缩形式返回响应体
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ResultCompression {
+    /// 解压后重新用 gzip 压缩
+    Gzip,
+    /// 不解压，原样返回服务端发送的字节
+    Received,
+}
+
+impl ResultCompression {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "gzip" => Ok(ResultCompression::Gzip),
+            "received" => Ok(ResultCompression::Received),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid compress_result: '{}'. Valid values: gzip, received",
+                name
+            ))),
+        }
+    }
+
+    /// 压缩响应体并编码为 base64，返回 (content, codec)
+    ///
+    /// Received 模式下 codec 取自响应的 Content-Encoding，没有时为 identity。
+    pub fn encode(&self, bytes: &[u8], content_encoding: Option<&str>) -> (String, String) {
+        let (data, codec) = match self {
+            ResultCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                // 写入内存缓冲区不会失败
+                let _ = encoder.write_all(bytes);
+                (encoder.finish().unwrap_or_default(), "gzip".to_string())
+            }
+            ResultCompression::Received => (
+                bytes.to_vec(),
+                content_encoding.map(|e| e.trim().to_lowercase()).unwrap_or_else(|| "identity".to_string()),
+            ),
+        };
+        (base64::engine::general_purpose::STANDARD.encode(data), codec)
+    }
+}
+
+/// 发送前压缩请求体
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BodyCompression {
+    Gzip,
+    Zstd,
+}
+
+impl BodyCompression {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "gzip" => Ok(BodyCompression::Gzip),
+            "zstd" => Ok(BodyCompression::Zstd),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid compress_body: '{}'. Valid values: gzip, zstd",
+                name
+            ))),
+        }
+    }
+
+    /// Content-Encoding 中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyCompression::Gzip => "gzip",
+            BodyCompression::Zstd => "zstd",
+        }
+    }
+
+    pub fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            BodyCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                let _ = encoder.write_all(bytes);
+                encoder.finish().unwrap_or_default()
+            }
+            // 写入内存缓冲区不会失败
+            BodyCompression::Zstd => zstd::encode_all(bytes, zstd::DEFAULT_COMPRESSION_LEVEL).unwrap_or_default(),
+        }
+    }
+}
+
+/// 解压失败的原因
+#[derive(Debug, PartialEq)]
+pub enum DecompressError {
+    /// 解压结果超出 max_bytes，已停止解压
+    TooLarge,
+    Invalid(String),
+}
+
+/// 按 Content-Encoding 解压响应体，多重编码按相反顺序依次解压
+///
+/// allow_truncated 为 true 时（如读取超时的部分响应）保留截断前已解压的内容。
+/// max_bytes 限制每一层解压的输出，超出时立即停止，避免高压缩比的数据耗尽内存。
+pub fn decompress(bytes: &[u8], content_encoding: &str, allow_truncated: bool, max_bytes: Option<usize>) -> Result<Vec<u8>, DecompressError> {
+    let mut data = bytes.to_vec();
+    for coding in content_encoding.split(',').map(|c| c.trim().to_lowercase()).rev() {
+        let decoded = match coding.as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => read_all(MultiGzDecoder::new(&data[..]), allow_truncated, max_bytes),
+            // 部分服务端发送不带 zlib 头的原始 deflate 数据
+            "deflate" => match read_all(ZlibDecoder::new(&data[..]), allow_truncated, max_bytes) {
+                Err(DecompressError::Invalid(_)) => read_all(DeflateDecoder::new(&data[..]), allow_truncated, max_bytes),
+                decoded => decoded,
+            },
+            "br" => read_all(brotli::Decompressor::new(&data[..], 4096), allow_truncated, max_bytes),
+            "zstd" => match zstd::stream::read::Decoder::new(&data[..]) {
+                Ok(decoder) => read_all(decoder, allow_truncated, max_bytes),
+                Err(e) => Err(DecompressError::Invalid(e.to_string())),
+            },
+            other => return Err(DecompressError::Invalid(format!("unsupported content encoding '{}'", other))),
+        };
+        data = decoded.map_err(|e| match e {
+            DecompressError::Invalid(cause) => DecompressError::Invalid(format!("failed to decompress {} body: {}", coding, cause)),
+            too_large => too_large,
+        })?;
+    }
+    Ok(data)
+}
+
+fn read_all(mut reader: impl Read, allow_truncated: bool, max_bytes: Option<usize>) -> Result<Vec<u8>, DecompressError> {
+    let mut out = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(out),
+            Ok(n) => {
+                out.extend_from_slice(&buf[..n]);
+                if max_bytes.is_some_and(|max| out.len() > max) {
+                    return Err(DecompressError::TooLarge);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) if allow_truncated => return Ok(out),
+            Err(e) => return Err(DecompressError::Invalid(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        BodyCompression::Gzip.compress(bytes)
+    }
+
+    #[test]
+    fn stops_inflating_past_limit() {
+        // 8 MiB 的零压缩后只有几 KB
+        let bomb = gzip(&vec![0u8; 8 * 1024 * 1024]);
+        assert!(bomb.len() < 64 * 1024);
+        assert_eq!(decompress(&bomb, "gzip", false, Some(1024 * 1024)), Err(DecompressError::TooLarge));
+        assert_eq!(decompress(&bomb, "gzip", false, None).map(|d| d.len()), Ok(8 * 1024 * 1024));
+    }
+
+    #[test]
+    fn limits_every_layer() {
+        let layered = gzip(&gzip(&vec![b'a'; 4 * 1024 * 1024]));
+        assert_eq!(decompress(&layered, "gzip, gzip", false, Some(64 * 1024)), Err(DecompressError::TooLarge));
+        let zstd = BodyCompression::Zstd.compress(&vec![0u8; 4 * 1024 * 1024]);
+        assert_eq!(decompress(&zstd, "zstd", false, Some(64 * 1024)), Err(DecompressError::TooLarge));
+    }
+
+    #[test]
+    fn raw_deflate_and_truncated_bodies() {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"hello hello hello").unwrap();
+        let raw = encoder.finish().unwrap();
+        assert_eq!(decompress(&raw, "deflate", false, Some(1024)).unwrap(), b"hello hello hello");
+
+        let body = gzip(&vec![b'x'; 100_000]);
+        let truncated = &body[..body.len() / 2];
+        assert!(matches!(decompress(truncated, "gzip", false, None), Err(DecompressError::Invalid(_))));
+        assert!(decompress(truncated, "gzip", true, None).is_ok());
+        assert!(matches!(decompress(b"abc", "compress", false, None), Err(DecompressError::Invalid(_))));
+    }
+}
@@ -0,0 +1,147 @@
+use base64::Engine;
+use encoding_rs::{Encoding, UTF_8};
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+
+/// 响应体中无效字节的处理方式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DecodePolicy {
+    /// 替换为 U+FFFD（默认）
+    Replace,
+    /// 视为错误，返回 DecodeError 异常
+    Error,
+    /// 不解码，content 为 base64 编码的原始字节
+    Bytes,
+}
+
+/// 解码结果
+pub enum Decoded {
+    Text { text: String, replaced: usize },
+    Base64(String),
+    Invalid(String),
+}
+
+impl DecodePolicy {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "replace" => Ok(DecodePolicy::Replace),
+            "error" | "strict" => Ok(DecodePolicy::Error),
+            "bytes" => Ok(DecodePolicy::Bytes),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid decode_errors: '{}'. Valid values: replace, error, bytes",
+                name
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecodePolicy::Replace => "replace",
+            DecodePolicy::Error => "error",
+            DecodePolicy::Bytes => "bytes",
+        }
+    }
+
+    /// 按 encoding 解码，非 UTF-8 编码交给 encoding_rs
+    pub fn decode(&self, bytes: &[u8], encoding: &'static Encoding) -> Decoded {
+        if encoding != UTF_8 && *self != DecodePolicy::Bytes {
+            return self.decode_legacy(bytes, encoding);
+        }
+        match self {
+            DecodePolicy::Bytes => Decoded::Base64(base64::engine::general_purpose::STANDARD.encode(bytes)),
+            DecodePolicy::Error => match std::str::from_utf8(bytes) {
+                Ok(text) => Decoded::Text { text: text.to_string(), replaced: 0 },
+                Err(e) => Decoded::Invalid(format!("Invalid UTF-8 in response body: {}", e)),
+            },
+            DecodePolicy::Replace => {
+                let mut text = String::with_capacity(bytes.len());
+                let mut replaced = 0;
+                for (valid, invalid) in utf8_chunks(bytes) {
+                    text.push_str(valid);
+                    if !invalid.is_empty() {
+                        text.push('\u{fffd}');
+                        replaced += 1;
+                    }
+                }
+                Decoded::Text { text, replaced }
+            }
+        }
+    }
+
+    fn decode_legacy(&self, bytes: &[u8], encoding: &'static Encoding) -> Decoded {
+        if *self == DecodePolicy::Error {
+            return match encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+                Some(text) => Decoded::Text { text: text.into_owned(), replaced: 0 },
+                None => Decoded::Invalid(format!("Invalid {} in response body", encoding.name())),
+            };
+        }
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        let replaced = text.matches('\u{fffd}').count();
+        Decoded::Text { text: text.into_owned(), replaced }
+    }
+}
+
+/// 按无效字节序列切分：每段为有效的 UTF-8 文本和紧随其后的无效字节（可能为空）
+///
+/// 与 <[u8]>::utf8_chunks 相同，该方法需要 Rust 1.79。
+pub(crate) fn utf8_chunks(mut bytes: &[u8]) -> Vec<(&str, &[u8])> {
+    let mut chunks = Vec::new();
+    while !bytes.is_empty() {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => {
+                chunks.push((text, &bytes[bytes.len()..]));
+                break;
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                // error_len 为 None 表示结尾是不完整的字符
+                let invalid = e.error_len().unwrap_or(rest.len());
+                chunks.push((std::str::from_utf8(valid).unwrap_or_default(), &rest[..invalid]));
+                bytes = &rest[invalid..];
+            }
+        }
+    }
+    chunks
+}
+
+/// 响应文本的编码及其来源
+pub struct Charset {
+    pub encoding: &'static Encoding,
+    /// override、bom、header、detected 或 default
+    pub source: &'static str,
+}
+
+/// 校验 encoding 参数是 WHATWG 编码标准中的名称，如 gbk、shift_jis、iso-8859-1
+pub fn check_encoding_label(label: &str) -> PyResult<()> {
+    match Encoding::for_label(label.trim().as_bytes()) {
+        Some(_) => Ok(()),
+        None => Err(PyValueError::new_err(format!("Unknown encoding: '{}'", label))),
+    }
+}
+
+/// 确定响应文本的编码：显式指定 > BOM > Content-Type 的 charset > 合法的 UTF-8 > 按内容检测
+///
+/// detect 为 false 时不检测，无效的 UTF-8 按 UTF-8 处理。
+pub fn resolve_charset(bytes: &[u8], content_type: Option<&str>, override_label: Option<&str>, detect: bool) -> Charset {
+    if let Some(encoding) = override_label.and_then(|label| Encoding::for_label(label.trim().as_bytes())) {
+        return Charset { encoding, source: "override" };
+    }
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return Charset { encoding, source: "bom" };
+    }
+    let declared = content_type.and_then(|ct| {
+        ct.split(';').skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+            .and_then(|(_, value)| Encoding::for_label(value.trim().trim_matches('"').as_bytes()))
+    });
+    if let Some(encoding) = declared {
+        return Charset { encoding, source: "header" };
+    }
+    if !detect || std::str::from_utf8(bytes).is_ok() {
+        return Charset { encoding: UTF_8, source: "default" };
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    Charset { encoding: detector.guess(None, true), source: "detected" }
+}
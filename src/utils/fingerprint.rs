@@ -0,0 +1,26 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+/// 编译需要忽略的易变内容（时间戳、CSRF token 等）的正则
+pub fn compile_patterns(patterns: &[String]) -> PyResult<Vec<Regex>> {
+    patterns.iter()
+        .map(|p| Regex::new(p).map_err(|e| PyValueError::new_err(format!("Invalid fingerprint pattern '{}': {}", p, e))))
+        .collect()
+}
+
+/// 计算规范化后的内容指纹：去掉匹配的易变内容并合并空白后取 SHA-256
+pub fn content_fingerprint(content: &str, ignore: &[Regex]) -> String {
+    let mut text = content.to_string();
+    for pattern in ignore {
+        text = pattern.replace_all(&text, "").into_owned();
+    }
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("sha256:{}", sha256_hex(normalized.as_bytes()))
+}
+
+/// SHA-256 的十六进制表示
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,26 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+
+pub fn json_to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    match value {
+        Value::Null => Ok(py.None()),
+        Value::Bool(b) => Ok(b.into_py(py)),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() { return Ok(i.into_py(py)); }
+            if let Some(u) = n.as_u64() { return Ok(u.into_py(py)); }
+            Ok(n.as_f64().unwrap_or(0.0).into_py(py))
+        }
+        Value::String(s) => Ok(s.into_py(py)),
+        Value::Array(arr) => {
+            let list = PyList::empty(py);
+            for v in arr { list.append(json_to_py(py, v)?)?; }
+            Ok(list.into_py(py))
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map { dict.set_item(k, json_to_py(py, v)?)?; }
+            Ok(dict.into_py(py))
+        }
+    }
+}
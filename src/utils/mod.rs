@@ -1,5 +1,21 @@
 pub mod py_to_json;
+pub mod checksum;
+pub mod compress;
+pub mod decode;
+pub mod fingerprint;
+pub mod json_to_py;
+pub mod normalize;
+pub mod server_timing;
 pub mod time;
+pub mod url_input;
 
 pub use py_to_json::py_to_json;
+pub use checksum::{Checksum, Hasher};
+pub use compress::{decompress, BodyCompression, DecompressError, ResultCompression};
+pub use decode::{check_encoding_label, resolve_charset, DecodePolicy, Decoded};
+pub use fingerprint::{compile_patterns, content_fingerprint, sha256_hex};
+pub use json_to_py::json_to_py;
+pub use normalize::{normalize_text, NormalizeStep};
+pub use server_timing::parse_server_timing;
 pub use time::format_datetime;
+pub use url_input::{normalize_url, parse_host_rewrites, rewrite_host};
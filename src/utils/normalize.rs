@@ -0,0 +1,105 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+
+/// 响应文本的规范化步骤，按传入顺序依次执行
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NormalizeStep {
+    StripBom,
+    Newlines,
+    CollapseWhitespace,
+    HtmlEntities,
+}
+
+impl NormalizeStep {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "strip_bom" | "bom" => Ok(NormalizeStep::StripBom),
+            "newlines" | "normalize_newlines" => Ok(NormalizeStep::Newlines),
+            "collapse_whitespace" | "whitespace" => Ok(NormalizeStep::CollapseWhitespace),
+            "html_entities" | "unescape_html" => Ok(NormalizeStep::HtmlEntities),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid normalize step: '{}'. Valid values: strip_bom, newlines, collapse_whitespace, html_entities",
+                name
+            ))),
+        }
+    }
+
+    pub fn parse_all(names: &[String]) -> PyResult<Vec<Self>> {
+        names.iter().map(|n| Self::parse(n)).collect()
+    }
+
+    fn apply(&self, text: String) -> String {
+        match self {
+            NormalizeStep::StripBom => match text.strip_prefix('\u{feff}') {
+                Some(rest) => rest.to_string(),
+                None => text,
+            },
+            NormalizeStep::Newlines => text.replace("\r\n", "\n").replace('\r', "\n"),
+            NormalizeStep::CollapseWhitespace => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            NormalizeStep::HtmlEntities => decode_html_entities(&text),
+        }
+    }
+}
+
+pub fn normalize_text(text: String, steps: &[NormalizeStep]) -> String {
+    steps.iter().fold(text, |acc, step| step.apply(acc))
+}
+
+fn decode_html_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        // 实体最长按 32 个字符查找结尾的 ';'
+        let decoded = rest[1..]
+            .char_indices()
+            .take(32)
+            .find(|&(_, c)| c == ';')
+            .and_then(|(end, _)| decode_entity(&rest[1..end + 1]).map(|c| (c, end + 2)));
+
+        match decoded {
+            Some((c, consumed)) => {
+                out.push(c);
+                rest = &rest[consumed..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix('x').or_else(|| num.strip_prefix('X')) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse::<u32>().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        "copy" => Some('©'),
+        "reg" => Some('®'),
+        "trade" => Some('™'),
+        "hellip" => Some('…'),
+        "mdash" => Some('—'),
+        "ndash" => Some('–'),
+        "lsquo" => Some('‘'),
+        "rsquo" => Some('’'),
+        "ldquo" => Some('“'),
+        "rdquo" => Some('”'),
+        _ => None,
+    }
+}
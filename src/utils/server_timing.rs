@@ -0,0 +1,81 @@
+use serde_json::{Map, Value};
+
+/// 解析 Server-Timing 响应头，每个指标返回 {"name", "dur"?, "desc"?, ...}
+///
+/// 多个头部按顺序合并，无法识别的参数按原样保留为字符串。
+pub fn parse_server_timing<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<Value> {
+    values.into_iter()
+        .flat_map(|value| split_unquoted(value, ','))
+        .filter_map(|metric| parse_metric(&metric))
+        .collect()
+}
+
+fn parse_metric(metric: &str) -> Option<Value> {
+    let mut parts = split_unquoted(metric, ';').into_iter();
+    let name = parts.next()?.trim().to_string();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut entry = Map::new();
+    entry.insert("name".to_string(), Value::String(name));
+    for param in parts {
+        let (key, raw) = match param.split_once('=') {
+            Some((k, v)) => (k.trim().to_lowercase(), v.trim()),
+            None => (param.trim().to_lowercase(), ""),
+        };
+        if key.is_empty() || entry.contains_key(&key) {
+            continue;
+        }
+        let value = unquote(raw);
+        let parsed = match key.as_str() {
+            "dur" => value.parse::<f64>().ok().map(Value::from).unwrap_or(Value::String(value)),
+            _ => Value::String(value),
+        };
+        entry.insert(key, parsed);
+    }
+    Some(Value::Object(entry))
+}
+
+/// 按分隔符拆分，忽略引号内的分隔符
+fn split_unquoted(input: &str, sep: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in input.chars() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c == sep && !quoted {
+            parts.push(std::mem::take(&mut current));
+            continue;
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+}
+
+fn unquote(raw: &str) -> String {
+    match raw.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inner) => {
+            let mut out = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    if let Some(next) = chars.next() {
+                        out.push(next);
+                    }
+                } else {
+                    out.push(c);
+                }
+            }
+            out
+        }
+        None => raw.to_string(),
+    }
+}
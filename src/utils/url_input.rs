@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use url::Url;
+
+/// 规范化请求地址，允许传入不带 scheme 的 host[:port][/path]
+///
+/// 端口为 443 时使用 https，为 80 时使用 http，其余使用 default_scheme。
+pub fn normalize_url(input: &str, default_scheme: &str) -> Result<String, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err("URL is empty".to_string());
+    }
+
+    let candidate = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        let authority = trimmed.split(['/', '?', '#']).next().unwrap_or(trimmed);
+        let port = authority.rsplit_once(':').and_then(|(_, p)| p.parse::<u16>().ok());
+        let scheme = match port {
+            Some(443) => "https",
+            Some(80) => "http",
+            _ => default_scheme,
+        };
+        format!("{}://{}", scheme, trimmed)
+    };
+
+    let parsed = Url::parse(&candidate).map_err(|e| format!("Invalid URL '{}': {}", input, e))?;
+    if parsed.host_str().is_none() {
+        return Err(format!("Invalid URL '{}': missing host", input));
+    }
+    Ok(parsed.to_string())
+}
+
+/// 按映射替换 URL 的主机，键可以是 host 或 host:port，值可以是 host 或 host:port
+///
+/// 只替换主机（和指定的端口），Host 请求头和 TLS SNI 随 URL 一起变为新主机。
+pub fn rewrite_host(url: &str, rewrites: &HashMap<String, String>) -> Result<String, String> {
+    let mut parsed = Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    let Some(host) = parsed.host_str().map(|h| h.to_ascii_lowercase()) else { return Ok(url.to_string()) };
+    let with_port = parsed.port_or_known_default().map(|p| format!("{}:{}", host, p));
+    let target = with_port.as_ref().and_then(|hp| rewrites.get(hp)).or_else(|| rewrites.get(&host));
+    let Some(target) = target else { return Ok(url.to_string()) };
+
+    let (new_host, new_port) = split_host_port(target)?;
+    parsed.set_host(Some(new_host)).map_err(|e| format!("Invalid rewrite target '{}': {}", target, e))?;
+    if let Some(port) = new_port {
+        let _ = parsed.set_port(Some(port));
+    }
+    Ok(parsed.to_string())
+}
+
+/// 校验并规范化 host_rewrites，键统一为小写
+pub fn parse_host_rewrites(rewrites: HashMap<String, String>) -> PyResult<HashMap<String, String>> {
+    rewrites.into_iter().map(|(from, to)| {
+        if from.trim().is_empty() {
+            return Err(PyValueError::new_err("host_rewrites keys must not be empty"));
+        }
+        split_host_port(&to).map_err(PyValueError::new_err)?;
+        Ok((from.trim().to_ascii_lowercase(), to.trim().to_string()))
+    }).collect()
+}
+
+fn split_host_port(target: &str) -> Result<(&str, Option<u16>), String> {
+    let target = target.trim();
+    let (host, port) = match target.rsplit_once(':') {
+        // 排除未加方括号的 IPv6 地址
+        Some((host, port)) if !host.contains(':') || host.ends_with(']') => {
+            let port = port.parse::<u16>().map_err(|_| format!("Invalid rewrite target '{}': bad port", target))?;
+            (host, Some(port))
+        }
+        _ => (target, None),
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid rewrite target '{}': missing host", target));
+    }
+    Ok((host, port))
+}